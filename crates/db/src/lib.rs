@@ -1,6 +1,9 @@
 use fjall::{Config, Keyspace, PartitionCreateOptions, PartitionHandle, UserKey, UserValue};
 use format_bytes::format_bytes;
-use shared_types::{LogEntry, LogValue};
+use shared_types::{AccessEntry, AccessList, LogEntry, LogKey, LogValue, QueuedPayout, TxStatus};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 struct DbLogEntry(LogEntry);
 pub struct SerializableLogValue<'a>(pub &'a LogValue);
 
@@ -16,12 +19,88 @@ pub struct DBMeta {
     pub claim_segments: usize,
 }
 
+// partitions of the network that was served before multi-network support keep their old names
+const LEGACY_NETWORK: &str = "sepolia";
+
+// cooldowns and logs are kept apart for every network
+#[derive(Clone)]
+struct NetworkPartitions {
+    registry: PartitionHandle,
+    logs: PartitionHandle,
+    // batched payouts waiting to be sent, not FIFO
+    queue: PartitionHandle,
+    // keys of logged claims still queued or pending, not FIFO, see pending_logs
+    pending: PartitionHandle,
+}
+
 #[derive(Clone)]
 pub struct DB {
-    partition_registry: PartitionHandle,
-    partition_logs: PartitionHandle,
+    networks: Arc<HashMap<String, NetworkPartitions>>,
+    // next nonce of every sending wallet, not FIFO, entries must never be dropped
+    nonces: PartitionHandle,
+    // wei paid out per network and day or hour, not FIFO, see reserve_spend
+    spending: PartitionHandle,
+    // claims per network, quota and UTC day, not FIFO, see reserve_quota
+    quotas: PartitionHandle,
+    // listed recipients and the reason, by address or name, not FIFO
+    allowlist: PartitionHandle,
+    denylist: PartitionHandle,
+    // used proof of work challenges by expiry and id, not FIFO, see use_challenge
+    challenges: PartitionHandle,
     keyspace: Keyspace,
     size_limit: u64,
+    claim_lock: Arc<Mutex<()>>,
+    // log keys are picked and logged claims rewritten under it, see insert_k_v_logs
+    log_lock: Arc<Mutex<()>>,
+    // next batch queue key, shared by all networks
+    queue_seq: Arc<AtomicU64>,
+}
+
+// claim slots taken by reserve_claim, previous timestamps are kept to roll back on release
+#[derive(Debug)]
+pub struct ClaimReservation {
+    pub network: String,
+    pub timestamp: u64,
+    // (key, previous claim), kept with queued payouts to release them later
+    pub slots: Vec<(String, Option<u64>)>,
+}
+
+#[derive(Debug)]
+pub enum ReserveOutcome {
+    Reserved(ClaimReservation),
+    // index of the first key (as passed to reserve_claim) that is still in cooldown
+    InCooldown { index: usize, ends_at: u64 },
+}
+
+// UTC day or hour a spending total is kept for
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BudgetPeriod {
+    Day,
+    Hour,
+}
+
+impl BudgetPeriod {
+    pub fn secs(&self) -> u64 {
+        match self {
+            Self::Day => 86400,
+            Self::Hour => 3600,
+        }
+    }
+
+    fn key(&self, network: &str, timestamp: u64) -> String {
+        let period = match self {
+            Self::Day => "day",
+            Self::Hour => "hour",
+        };
+        format!("{}:{}:{}", network, period, timestamp / self.secs())
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum SpendOutcome {
+    Reserved,
+    // the amount would take the total of this period over its limit
+    OverBudget(BudgetPeriod),
 }
 
 pub trait ToBytes {
@@ -37,12 +116,12 @@ impl ToBytes for LogValue {
 impl TryFrom<(UserKey, UserValue)> for DbLogEntry {
     type Error = DBErrors;
     fn try_from((key, value): (UserKey, UserValue)) -> Result<Self, Self::Error> {
-        let key_tuple = convert_slice_to_tuple(&key)?;
+        let log_key = convert_slice_to_log_key(&key)?;
         let log_value: LogValue = rmp_serde::from_slice(&value)
             .map_err(|e| DBErrors::ConversionError(format!("Deserialization error: {}", e)))?;
 
         Ok(DbLogEntry(LogEntry {
-            key: key_tuple,
+            key: log_key,
             value: log_value,
         }))
     }
@@ -69,6 +148,8 @@ pub enum DBErrors {
     },
     #[error("Conversion error: {0}")]
     ConversionError(String),
+    #[error("Unknown network: {0}")]
+    UnknownNetwork(String),
 }
 
 fn convert_slice_to_u64(slice: fjall::Slice) -> u64 {
@@ -77,12 +158,45 @@ fn convert_slice_to_u64(slice: fjall::Slice) -> u64 {
     u64::from_be_bytes(bytes)
 }
 
-fn convert_slice_to_tuple(slice: &[u8]) -> Result<(u64, u8), DBErrors> {
-    if slice.len() != 10 || slice[8] != 0 {
-        return Err(DBErrors::ConversionError(
-            "Key conversion error: Invalid slice format".to_string(),
-        ));
+fn convert_slice_to_u128(slice: fjall::Slice) -> u128 {
+    let mut bytes = [0; 16];
+    bytes.copy_from_slice(&slice);
+    u128::from_be_bytes(bytes)
+}
+
+// timestamp\0status, the whole key of the first entry logged in a second
+fn log_key_prefix(timestamp: u64, status: u8) -> Vec<u8> {
+    let timestamp_bytes = timestamp.to_be_bytes();
+    let status_bytes = status.to_be_bytes();
+    format_bytes!(b"{}\0{}", timestamp_bytes, status_bytes)
+}
+
+// later entries of the same second get timestamp\0status\0seq
+fn log_key_bytes(key: LogKey) -> Vec<u8> {
+    let prefix = log_key_prefix(key.timestamp, key.status);
+    match key.seq {
+        0 => prefix,
+        seq => {
+            let seq_bytes = seq.to_be_bytes();
+            format_bytes!(b"{}\0{}", prefix, seq_bytes)
+        }
     }
+}
+
+fn convert_slice_to_log_key(slice: &[u8]) -> Result<LogKey, DBErrors> {
+    let seq = match slice.len() {
+        10 if slice[8] == 0 => 0,
+        19 if slice[8] == 0 && slice[10] == 0 => {
+            let mut seq_bytes = [0u8; 8];
+            seq_bytes.copy_from_slice(&slice[11..]);
+            u64::from_be_bytes(seq_bytes)
+        }
+        _ => {
+            return Err(DBErrors::ConversionError(
+                "Key conversion error: Invalid slice format".to_string(),
+            ));
+        }
+    };
 
     let mut timestamp_bytes = [0u8; 8];
     timestamp_bytes.copy_from_slice(&slice[..8]);
@@ -90,11 +204,53 @@ fn convert_slice_to_tuple(slice: &[u8]) -> Result<(u64, u8), DBErrors> {
 
     let status_byte = slice[9];
 
-    Ok((timestamp, status_byte))
+    Ok(LogKey {
+        timestamp,
+        status: status_byte,
+        seq,
+    })
+}
+
+// token payouts don't count, only successful native claims. errors are kept to be returned
+fn is_native_payout(res: &Result<(LogKey, UserValue), DBErrors>) -> bool {
+    match res {
+        Ok((key, v)) if key.status == 1 => {
+            rmp_serde::from_slice::<LogValue>(v).is_ok_and(|log| log.asset.is_empty())
+        }
+        Ok(_) => false,
+        Err(_) => true,
+    }
+}
+
+fn is_pending(log: &LogValue) -> bool {
+    matches!(log.tx_status, Some(TxStatus::Queued | TxStatus::Pending))
+}
+
+fn quota_key(network: &str, quota: &str, timestamp: u64) -> String {
+    format!(
+        "{}:{}:{}",
+        network,
+        quota,
+        timestamp / BudgetPeriod::Day.secs()
+    )
+}
+
+// expiry first, expired challenges are a range
+fn challenge_key(id: &str, expires_at: u64) -> Vec<u8> {
+    let mut key = expires_at.to_be_bytes().to_vec();
+    key.extend_from_slice(id.as_bytes());
+    key
+}
+
+fn partition_name(kind: &str, network: &str) -> String {
+    match network {
+        LEGACY_NETWORK => kind.to_string(),
+        _ => format!("{}_{}", kind, network),
+    }
 }
 
 impl DB {
-    pub fn new(path: &str, limit: u64) -> Result<Self, DBErrors> {
+    pub fn new(path: &str, limit: u64, networks: &[&str]) -> Result<Self, DBErrors> {
         let keyspace = Config::new(path)
             .max_write_buffer_size(1_024 * 1_024)
             .open()
@@ -103,41 +259,112 @@ impl DB {
                 source: e,
             })?;
 
-        let registry = keyspace
-            .open_partition(
-                "claim",
-                PartitionCreateOptions::default().compaction_strategy(
-                    fjall::compaction::Strategy::Fifo(fjall::compaction::Fifo::new(limit, None)),
-                ),
-            )
-            .db_error_with_context("init claim partition")?;
-
-        let logs = keyspace
-            .open_partition(
-                "logs",
-                PartitionCreateOptions::default().compaction_strategy(
-                    fjall::compaction::Strategy::Fifo(fjall::compaction::Fifo::new(limit, None)),
-                ),
-            )
-            .db_error_with_context("init logs partition")?;
+        let open_fifo = |name: String, context: &'static str| {
+            keyspace
+                .open_partition(
+                    &name,
+                    PartitionCreateOptions::default().compaction_strategy(
+                        fjall::compaction::Strategy::Fifo(fjall::compaction::Fifo::new(
+                            limit, None,
+                        )),
+                    ),
+                )
+                .db_error_with_context(context)
+        };
+
+        let mut partitions = HashMap::new();
+        let mut queue_seq = 0;
+        for network in networks {
+            let registry = open_fifo(partition_name("claim", network), "init claim partition")?;
+            let logs = open_fifo(partition_name("logs", network), "init logs partition")?;
+            let queue = keyspace
+                .open_partition(
+                    &partition_name("queue", network),
+                    PartitionCreateOptions::default(),
+                )
+                .db_error_with_context("init queue partition")?;
+            if let Some((last, _)) = queue.last_key_value().db_error_with_context("init queue")? {
+                queue_seq = queue_seq.max(convert_slice_to_u64(last) + 1);
+            }
+            // logs written before the index are indexed once, when it's created
+            let pending_name = partition_name("pending", network);
+            let indexed = keyspace.partition_exists(&pending_name);
+            let pending = keyspace
+                .open_partition(&pending_name, PartitionCreateOptions::default())
+                .db_error_with_context("init pending partition")?;
+            if !indexed {
+                for item in logs.iter() {
+                    let (key, value) = item.db_error_with_context("index pending logs")?;
+                    if rmp_serde::from_slice::<LogValue>(&value).is_ok_and(|log| is_pending(&log)) {
+                        pending
+                            .insert(key, [])
+                            .db_error_with_context("index pending logs")?;
+                    }
+                }
+            }
+            partitions.insert(
+                network.to_string(),
+                NetworkPartitions {
+                    registry,
+                    logs,
+                    queue,
+                    pending,
+                },
+            );
+        }
+
+        let nonces = keyspace
+            .open_partition("nonces", PartitionCreateOptions::default())
+            .db_error_with_context("init nonces partition")?;
+        let spending = keyspace
+            .open_partition("spending", PartitionCreateOptions::default())
+            .db_error_with_context("init spending partition")?;
+        let quotas = keyspace
+            .open_partition("quotas", PartitionCreateOptions::default())
+            .db_error_with_context("init quotas partition")?;
+        let allowlist = keyspace
+            .open_partition("allowlist", PartitionCreateOptions::default())
+            .db_error_with_context("init allowlist partition")?;
+        let denylist = keyspace
+            .open_partition("denylist", PartitionCreateOptions::default())
+            .db_error_with_context("init denylist partition")?;
+        let challenges = keyspace
+            .open_partition("challenges", PartitionCreateOptions::default())
+            .db_error_with_context("init challenges partition")?;
 
         Ok(Self {
-            partition_registry: registry,
-            partition_logs: logs,
-            keyspace: keyspace,
+            networks: Arc::new(partitions),
+            nonces,
+            spending,
+            quotas,
+            allowlist,
+            denylist,
+            challenges,
+            keyspace,
             size_limit: limit,
+            claim_lock: Arc::new(Mutex::new(())),
+            log_lock: Arc::new(Mutex::new(())),
+            queue_seq: Arc::new(AtomicU64::new(queue_seq)),
         })
     }
 
-    pub fn insert_k_v_claim(&self, key: &str, value: u64) -> Result<(), DBErrors> {
-        self.partition_registry
+    fn network(&self, network: &str) -> Result<&NetworkPartitions, DBErrors> {
+        self.networks
+            .get(network)
+            .ok_or_else(|| DBErrors::UnknownNetwork(network.to_string()))
+    }
+
+    pub fn insert_k_v_claim(&self, network: &str, key: &str, value: u64) -> Result<(), DBErrors> {
+        self.network(network)?
+            .registry
             .insert(key, value.to_be_bytes())
             .db_error_with_context("insert")
     }
 
-    pub fn get_value_claim(&self, key: &str) -> Result<Option<u64>, DBErrors> {
+    pub fn get_value_claim(&self, network: &str, key: &str) -> Result<Option<u64>, DBErrors> {
         match self
-            .partition_registry
+            .network(network)?
+            .registry
             .get(key)
             .db_error_with_context("get")?
         {
@@ -146,6 +373,274 @@ impl DB {
         }
     }
 
+    // key is the network and wallet, e.g. "sepolia:0xf0e5...", see zeroxname_ethereum::nonce
+    pub fn get_nonce(&self, key: &str) -> Result<Option<u64>, DBErrors> {
+        Ok(self
+            .nonces
+            .get(key)
+            .db_error_with_context("get nonce")?
+            .map(convert_slice_to_u64))
+    }
+
+    pub fn set_nonce(&self, key: &str, nonce: u64) -> Result<(), DBErrors> {
+        self.nonces
+            .insert(key, nonce.to_be_bytes())
+            .db_error_with_context("set nonce")
+    }
+
+    // compare-and-set on the claim partition: check the cooldown of every key and write the new
+    // timestamp under one lock, so concurrent claims sharing any key can't both pass the check.
+    // keys are (claim key, cooldown window in seconds), all of them are reserved or none.
+    pub fn reserve_claim(
+        &self,
+        network: &str,
+        keys: &[(String, u64)],
+        timestamp: u64,
+    ) -> Result<ReserveOutcome, DBErrors> {
+        let _guard = self.claim_lock.lock().unwrap_or_else(|e| e.into_inner());
+        let mut slots = Vec::with_capacity(keys.len());
+        for (index, (key, cooldown_sec)) in keys.iter().enumerate() {
+            let previous = self.get_value_claim(network, key)?;
+            if let Some(last_claim) = previous
+                && timestamp.saturating_sub(last_claim) < *cooldown_sec
+            {
+                return Ok(ReserveOutcome::InCooldown {
+                    index,
+                    ends_at: last_claim + cooldown_sec,
+                });
+            }
+            slots.push((key.clone(), previous));
+        }
+        for (key, _) in &slots {
+            self.insert_k_v_claim(network, key, timestamp)?;
+        }
+        Ok(ReserveOutcome::Reserved(ClaimReservation {
+            network: network.to_string(),
+            timestamp,
+            slots,
+        }))
+    }
+
+    // wei paid out on a network in the day or hour of `timestamp`
+    pub fn spent(
+        &self,
+        network: &str,
+        period: BudgetPeriod,
+        timestamp: u64,
+    ) -> Result<u128, DBErrors> {
+        Ok(self
+            .spending
+            .get(period.key(network, timestamp))
+            .db_error_with_context("get spent")?
+            .map(convert_slice_to_u128)
+            .unwrap_or(0))
+    }
+
+    fn set_spent(
+        &self,
+        network: &str,
+        period: BudgetPeriod,
+        timestamp: u64,
+        spent: u128,
+    ) -> Result<(), DBErrors> {
+        self.spending
+            .insert(period.key(network, timestamp), spent.to_be_bytes())
+            .db_error_with_context("set spent")
+    }
+
+    // compare-and-set like reserve_claim: adds `amount` to the totals of every period, or to
+    // none of them if one would go over its limit. limits are (period, wei)
+    pub fn reserve_spend(
+        &self,
+        network: &str,
+        limits: &[(BudgetPeriod, u128)],
+        timestamp: u64,
+        amount: u128,
+    ) -> Result<SpendOutcome, DBErrors> {
+        let _guard = self.claim_lock.lock().unwrap_or_else(|e| e.into_inner());
+        let mut totals = Vec::with_capacity(limits.len());
+        for (period, limit) in limits {
+            let total = self
+                .spent(network, *period, timestamp)?
+                .saturating_add(amount);
+            if total > *limit {
+                return Ok(SpendOutcome::OverBudget(*period));
+            }
+            totals.push((*period, total));
+        }
+        for (period, total) in totals {
+            self.set_spent(network, period, timestamp, total)?;
+        }
+        Ok(SpendOutcome::Reserved)
+    }
+
+    // gives back what reserve_spend took for a payout that wasn't sent
+    pub fn release_spend(
+        &self,
+        network: &str,
+        periods: &[BudgetPeriod],
+        timestamp: u64,
+        amount: u128,
+    ) -> Result<(), DBErrors> {
+        let _guard = self.claim_lock.lock().unwrap_or_else(|e| e.into_inner());
+        for period in periods {
+            let spent = self.spent(network, *period, timestamp)?;
+            self.set_spent(network, *period, timestamp, spent.saturating_sub(amount))?;
+        }
+        Ok(())
+    }
+
+    // claims counted against `quota`, e.g. "community:eth", on the UTC day of `timestamp`
+    pub fn quota_used(&self, network: &str, quota: &str, timestamp: u64) -> Result<u64, DBErrors> {
+        Ok(self
+            .quotas
+            .get(quota_key(network, quota, timestamp))
+            .db_error_with_context("get quota")?
+            .map(convert_slice_to_u64)
+            .unwrap_or(0))
+    }
+
+    // counts one claim against a daily quota under the claim lock, false once `limit` claims
+    // were counted that day
+    pub fn reserve_quota(
+        &self,
+        network: &str,
+        quota: &str,
+        timestamp: u64,
+        limit: u64,
+    ) -> Result<bool, DBErrors> {
+        let _guard = self.claim_lock.lock().unwrap_or_else(|e| e.into_inner());
+        let used = self.quota_used(network, quota, timestamp)?;
+        if used >= limit {
+            return Ok(false);
+        }
+        self.quotas
+            .insert(
+                quota_key(network, quota, timestamp),
+                (used + 1).to_be_bytes(),
+            )
+            .db_error_with_context("reserve quota")?;
+        Ok(true)
+    }
+
+    // gives back what reserve_quota took for a claim that wasn't paid
+    pub fn release_quota(
+        &self,
+        network: &str,
+        quota: &str,
+        timestamp: u64,
+    ) -> Result<(), DBErrors> {
+        let _guard = self.claim_lock.lock().unwrap_or_else(|e| e.into_inner());
+        let used = self.quota_used(network, quota, timestamp)?;
+        self.quotas
+            .insert(
+                quota_key(network, quota, timestamp),
+                used.saturating_sub(1).to_be_bytes(),
+            )
+            .db_error_with_context("release quota")
+    }
+
+    fn access_list(&self, list: AccessList) -> &PartitionHandle {
+        match list {
+            AccessList::Allow => &self.allowlist,
+            AccessList::Deny => &self.denylist,
+        }
+    }
+
+    // reason `key` was listed with, None if it isn't listed
+    pub fn access_reason(&self, list: AccessList, key: &str) -> Result<Option<String>, DBErrors> {
+        Ok(self
+            .access_list(list)
+            .get(key)
+            .db_error_with_context("get access entry")?
+            .map(|reason| String::from_utf8_lossy(&reason).into_owned()))
+    }
+
+    // replaces the reason if `key` is already listed
+    pub fn add_access(&self, list: AccessList, key: &str, reason: &str) -> Result<(), DBErrors> {
+        self.access_list(list)
+            .insert(key, reason)
+            .db_error_with_context("add access entry")
+    }
+
+    // false if `key` wasn't listed
+    pub fn remove_access(&self, list: AccessList, key: &str) -> Result<bool, DBErrors> {
+        let partition = self.access_list(list);
+        if !partition
+            .contains_key(key)
+            .db_error_with_context("get access entry")?
+        {
+            return Ok(false);
+        }
+        partition
+            .remove(key)
+            .db_error_with_context("remove access entry")?;
+        Ok(true)
+    }
+
+    pub fn access_entries(&self, list: AccessList) -> Result<Vec<AccessEntry>, DBErrors> {
+        self.access_list(list)
+            .iter()
+            .map(|item| {
+                let (key, reason) = item.db_error_with_context("iter access entries")?;
+                Ok(AccessEntry {
+                    key: String::from_utf8_lossy(&key).into_owned(),
+                    reason: String::from_utf8_lossy(&reason).into_owned(),
+                })
+            })
+            .collect()
+    }
+
+    // takes a proof of work challenge once, false if it was taken before. expired challenges are
+    // dropped, their solutions are refused anyway
+    pub fn use_challenge(&self, id: &str, expires_at: u64, now: u64) -> Result<bool, DBErrors> {
+        let _guard = self.claim_lock.lock().unwrap_or_else(|e| e.into_inner());
+        let expired = self
+            .challenges
+            .range(..now.to_be_bytes())
+            .map(|item| item.map(|(key, _)| key))
+            .collect::<Result<Vec<_>, _>>()
+            .db_error_with_context("iter challenges")?;
+        for key in expired {
+            self.challenges
+                .remove(key)
+                .db_error_with_context("remove challenge")?;
+        }
+        let key = challenge_key(id, expires_at);
+        if self
+            .challenges
+            .contains_key(&key)
+            .db_error_with_context("get challenge")?
+        {
+            return Ok(false);
+        }
+        self.challenges
+            .insert(key, [])
+            .db_error_with_context("use challenge")?;
+        Ok(true)
+    }
+
+    // roll back a reservation after a failed payout.
+    // a key is only restored if nobody else has written it since it was reserved.
+    pub fn release_claim(&self, reservation: ClaimReservation) -> Result<(), DBErrors> {
+        let _guard = self.claim_lock.lock().unwrap_or_else(|e| e.into_inner());
+        let network = reservation.network.as_str();
+        for (key, previous) in reservation.slots {
+            if self.get_value_claim(network, &key)? != Some(reservation.timestamp) {
+                continue;
+            }
+            match previous {
+                Some(previous) => self.insert_k_v_claim(network, &key, previous)?,
+                None => self
+                    .network(network)?
+                    .registry
+                    .remove(key)
+                    .db_error_with_context("remove claim")?,
+            }
+        }
+        Ok(())
+    }
+
     // return 0 if no previous claims
     // reverse since we need largest timestamp
     // finds first element that satisfies "true for status"
@@ -153,65 +648,109 @@ impl DB {
 
     pub fn get_last_claim_timestamp(
         &self,
+        network: &str,
         range_low: u64,
         range_high: u64,
     ) -> Result<u64, DBErrors> {
-        self.partition_logs
+        self.network(network)?
+            .logs
             .range(range_low.to_be_bytes()..(range_high + 1).to_be_bytes())
             .rev()
             .map(|res| {
-                let (k, _) = res.db_error_with_context("get log keys from range")?;
-                convert_slice_to_tuple(&k)
+                let (k, v) = res.db_error_with_context("get log keys from range")?;
+                Ok((convert_slice_to_log_key(&k)?, v))
             })
-            .find(|res| matches!(res, Ok((_, s)) if *s == 1))
+            .find(is_native_payout)
             .transpose()
-            .map(|opt| opt.map(|(ts, _)| ts).unwrap_or(0))
+            .map(|opt| opt.map(|(key, _)| key.timestamp).unwrap_or(0))
     }
 
-    pub fn get_db_meta(&self) -> Result<DBMeta, DBErrors> {
+    // successful native claims logged between range_low and range_high, inclusive
+    pub fn count_claims(
+        &self,
+        network: &str,
+        range_low: u64,
+        range_high: u64,
+    ) -> Result<u64, DBErrors> {
+        self.network(network)?
+            .logs
+            .range(range_low.to_be_bytes()..(range_high + 1).to_be_bytes())
+            .map(|res| {
+                let (k, v) = res.db_error_with_context("get log keys from range")?;
+                Ok((convert_slice_to_log_key(&k)?, v))
+            })
+            .filter(is_native_payout)
+            .try_fold(0, |count, res| res.map(|_| count + 1))
+    }
+
+    pub fn get_db_meta(&self, network: &str) -> Result<DBMeta, DBErrors> {
+        let partitions = self.network(network)?;
         Ok(DBMeta {
             journal_disk_space: self.keyspace.disk_space(),
             partition_count: self.keyspace.partition_count(),
             partition_size_limit: self.size_limit,
-            log_entries: self
-                .partition_logs
+            log_entries: partitions
+                .logs
                 .len()
                 .db_error_with_context("get log entries")?,
-            log_disk_space: self.partition_logs.disk_space(),
-            log_segments: self.partition_logs.segment_count(),
-            claim_entries: self
-                .partition_registry
+            log_disk_space: partitions.logs.disk_space(),
+            log_segments: partitions.logs.segment_count(),
+            claim_entries: partitions
+                .registry
                 .len()
                 .db_error_with_context("get claim entries")?,
-            claim_disk_space: self.partition_registry.disk_space(),
-            claim_segments: self.partition_registry.segment_count(),
+            claim_disk_space: partitions.registry.disk_space(),
+            claim_segments: partitions.registry.segment_count(),
         })
     }
 
+    // claims logged in the same second never share an entry, returns the key it was logged under
     pub fn insert_k_v_logs(
         &self,
+        network: &str,
         timestamp: u64,
         status: bool,
-        input: String,
-        result: String,
-    ) -> Result<(), DBErrors> {
-        let timestamp_bytes = timestamp.to_be_bytes();
-        let status_bytes = (status as u8).to_be_bytes();
-        let key = format_bytes!(b"{}\0{}", timestamp_bytes, status_bytes);
-        let log_struct = LogValue { input, result };
-        let serialized = log_struct.to_bytes();
-        self.partition_logs
-            .insert(key, serialized)
-            .db_error_with_context("insert log")
-    }
-
-    pub fn get_value_log(&self, key: (u64, u8)) -> Result<Option<LogValue>, DBErrors> {
-        let timestamp_bytes = key.0.to_be_bytes();
-        let status_bytes = key.1.to_be_bytes();
-        let key = format_bytes!(b"{}\0{}", timestamp_bytes, status_bytes);
+        log_struct: LogValue,
+    ) -> Result<LogKey, DBErrors> {
+        let logs = &self.network(network)?.logs;
+        let _guard = self.log_lock.lock().unwrap_or_else(|e| e.into_inner());
+        let status = status as u8;
+        let seq = match logs
+            .prefix(log_key_prefix(timestamp, status))
+            .next_back()
+            .transpose()
+            .db_error_with_context("get last log key")?
+        {
+            Some((last, _)) => convert_slice_to_log_key(&last)?.seq + 1,
+            None => 0,
+        };
+        let key = LogKey {
+            timestamp,
+            status,
+            seq,
+        };
+        self.write_log(network, key, &log_struct)?;
+        Ok(key)
+    }
+
+    // the entry and its place in the pending index are written together
+    fn write_log(&self, network: &str, key: LogKey, value: &LogValue) -> Result<(), DBErrors> {
+        let partitions = self.network(network)?;
+        let key = log_key_bytes(key);
+        let mut batch = self.keyspace.batch();
+        batch.insert(&partitions.logs, key.clone(), value.to_bytes());
+        match is_pending(value) {
+            true => batch.insert(&partitions.pending, key, []),
+            false => batch.remove(&partitions.pending, key),
+        }
+        batch.commit().db_error_with_context("insert log")
+    }
+
+    pub fn get_value_log(&self, network: &str, key: LogKey) -> Result<Option<LogValue>, DBErrors> {
         match self
-            .partition_logs
-            .get(&key)
+            .network(network)?
+            .logs
+            .get(log_key_bytes(key))
             .db_error_with_context("get log value")?
         {
             Some(v) => Ok(Some(rmp_serde::from_slice(&v).expect("should deserialize"))),
@@ -219,8 +758,119 @@ impl DB {
         }
     }
 
-    pub fn iter_logs(&self) -> impl Iterator<Item = Result<LogEntry, DBErrors>> + '_ {
-        self.partition_logs.iter().map(|item| {
+    // appends to the network's batch queue, returns the queue key
+    pub fn enqueue_payout(&self, network: &str, payout: &QueuedPayout) -> Result<u64, DBErrors> {
+        let seq = self.queue_seq.fetch_add(1, Ordering::SeqCst);
+        self.requeue_payout(network, seq, payout)?;
+        Ok(seq)
+    }
+
+    // rewrites a queued payout in place, keeping its place in the queue
+    pub fn requeue_payout(
+        &self,
+        network: &str,
+        key: u64,
+        payout: &QueuedPayout,
+    ) -> Result<(), DBErrors> {
+        let value = rmp_serde::to_vec(payout)
+            .map_err(|e| DBErrors::ConversionError(format!("Serialization error: {}", e)))?;
+        self.network(network)?
+            .queue
+            .insert(key.to_be_bytes(), value)
+            .db_error_with_context("enqueue payout")
+    }
+
+    // oldest first, at most `limit`
+    pub fn queued_payouts(
+        &self,
+        network: &str,
+        limit: usize,
+    ) -> Result<Vec<(u64, QueuedPayout)>, DBErrors> {
+        self.network(network)?
+            .queue
+            .iter()
+            .take(limit)
+            .map(|item| {
+                let (key, value) = item.db_error_with_context("iter queue")?;
+                let payout = rmp_serde::from_slice(&value).map_err(|e| {
+                    DBErrors::ConversionError(format!("Deserialization error: {}", e))
+                })?;
+                Ok((convert_slice_to_u64(key), payout))
+            })
+            .collect()
+    }
+
+    pub fn queue_len(&self, network: &str) -> Result<usize, DBErrors> {
+        self.network(network)?
+            .queue
+            .len()
+            .db_error_with_context("get queue length")
+    }
+
+    pub fn remove_queued(&self, network: &str, keys: &[u64]) -> Result<(), DBErrors> {
+        let queue = &self.network(network)?.queue;
+        for key in keys {
+            queue
+                .remove(key.to_be_bytes())
+                .db_error_with_context("remove queued payout")?;
+        }
+        Ok(())
+    }
+
+    // rewrites a logged claim in place, under the log lock so concurrent updates of the same
+    // entry don't lose each other's changes.
+    // false if the entry is gone, the FIFO partition may have dropped it
+    pub fn update_log(
+        &self,
+        network: &str,
+        key: LogKey,
+        update: impl FnOnce(&mut LogValue),
+    ) -> Result<bool, DBErrors> {
+        let _guard = self.log_lock.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(mut value) = self.get_value_log(network, key)? else {
+            return Ok(false);
+        };
+        update(&mut value);
+        self.write_log(network, key, &value)?;
+        Ok(true)
+    }
+
+    pub fn set_log_tx_status(
+        &self,
+        network: &str,
+        key: LogKey,
+        tx_status: TxStatus,
+    ) -> Result<bool, DBErrors> {
+        self.update_log(network, key, |value| value.tx_status = Some(tx_status))
+    }
+
+    // logged claims still queued or pending, oldest first, without going through the whole log.
+    // entries the FIFO logs partition dropped are taken off the index
+    pub fn pending_logs(&self, network: &str) -> Result<Vec<LogEntry>, DBErrors> {
+        let partitions = self.network(network)?;
+        let mut entries = Vec::new();
+        for item in partitions.pending.iter() {
+            let (key, _) = item.db_error_with_context("iter pending logs")?;
+            let log_key = convert_slice_to_log_key(&key)?;
+            match self.get_value_log(network, log_key)? {
+                Some(value) => entries.push(LogEntry {
+                    key: log_key,
+                    value,
+                }),
+                None => partitions
+                    .pending
+                    .remove(key)
+                    .db_error_with_context("remove pending log")?,
+            }
+        }
+        Ok(entries)
+    }
+
+    pub fn iter_logs(
+        &self,
+        network: &str,
+    ) -> Result<impl Iterator<Item = Result<LogEntry, DBErrors>> + '_, DBErrors> {
+        Ok(self.network(network)?.logs.iter().map(|item| {
             {
                 item.map_err(|e| DBErrors::DBError {
                     context: "iter_logs",
@@ -228,6 +878,6 @@ impl DB {
                 })
             }
             .and_then(|res| Ok(DbLogEntry::try_from(res)?.0))
-        })
+        }))
     }
 }
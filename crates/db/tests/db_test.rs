@@ -2,9 +2,22 @@
 
 use db::*;
 use dotenv::dotenv;
+use shared_types::{AccessEntry, AccessList, LogKey, LogValue, QueuedPayout, TxStatus};
 use std::env;
 use tempfile::tempdir;
 
+fn log_value(input: &str, result: &str, asset: &str) -> LogValue {
+    LogValue {
+        input: input.to_string(),
+        result: result.to_string(),
+        asset: asset.to_string(),
+        tx_status: None,
+        replaced: Vec::new(),
+        wallet: String::new(),
+        prevented: None,
+    }
+}
+
 #[test]
 fn test_claim_insert_and_retrieve() {
     dotenv().ok();
@@ -16,17 +29,17 @@ fn test_claim_insert_and_retrieve() {
     // Setup - create temp database
     let dir = tempdir().expect("should create temp dir");
     let db_path = dir.path().to_str().expect("valid path");
-    let db = DB::new(db_path, partition_size_limit).expect("should create DB");
+    let db = DB::new(db_path, partition_size_limit, &["sepolia"]).expect("should create DB");
 
     // Test data
     let test_key = "0xf0E5D3Cc05206987a125afC404b719e54Fa942a8";
     let test_value = 1234567890u64;
 
-    db.insert_k_v_claim(test_key, test_value)
+    db.insert_k_v_claim("sepolia", test_key, test_value)
         .expect("should insert claim");
 
     let retrieved = db
-        .get_value_claim(test_key)
+        .get_value_claim("sepolia", test_key)
         .expect("should retrieve claim")
         .expect("value should exist");
     dbg!(
@@ -52,7 +65,7 @@ fn test_claim_inserts_and_last_claim() {
     // Setup - create temp database
     let dir = tempdir().expect("should create temp dir");
     let db_path = dir.path().to_str().expect("valid path");
-    let db = DB::new(db_path, partition_size_limit).expect("should create DB");
+    let db = DB::new(db_path, partition_size_limit, &["sepolia"]).expect("should create DB");
 
     // Test data
     let input = "beast@beast".to_string();
@@ -61,15 +74,30 @@ fn test_claim_inserts_and_last_claim() {
     let test_time_mid = 1755965100u64;
     let test_time_last = 1755965200u64;
 
-    db.insert_k_v_logs(test_time_old, true, input.clone(), result.clone())
-        .expect("should insert log");
-    db.insert_k_v_logs(test_time_mid, true, input.clone(), result.clone())
-        .expect("should insert log");
-    db.insert_k_v_logs(test_time_last, true, input.clone(), result.clone())
-        .expect("should insert log");
+    db.insert_k_v_logs(
+        "sepolia",
+        test_time_old,
+        true,
+        log_value(&input, &result, ""),
+    )
+    .expect("should insert log");
+    db.insert_k_v_logs(
+        "sepolia",
+        test_time_mid,
+        true,
+        log_value(&input, &result, ""),
+    )
+    .expect("should insert log");
+    db.insert_k_v_logs(
+        "sepolia",
+        test_time_last,
+        true,
+        log_value(&input, &result, ""),
+    )
+    .expect("should insert log");
 
     let last_claim_timestamp = db
-        .get_last_claim_timestamp(0, 1755965900u64)
+        .get_last_claim_timestamp("sepolia", 0, 1755965900u64)
         .expect("should retrieve timestamp");
 
     dbg!(
@@ -94,18 +122,19 @@ fn test_log_insert_and_retrieve() {
     // Setup
     let dir = tempdir().expect("should create temp dir");
     let db_path = dir.path().to_str().expect("valid path");
-    let db = DB::new(db_path, partition_size_limit).expect("should create DB");
+    let db = DB::new(db_path, partition_size_limit, &["sepolia"]).expect("should create DB");
 
     // Test data
     let timestamp = 1234567890u64;
     let input = "beast@beast".to_string();
     let result = "test_result".to_string();
 
-    db.insert_k_v_logs(timestamp, true, input.clone(), result.clone())
+    let key = db
+        .insert_k_v_logs("sepolia", timestamp, true, log_value(&input, &result, ""))
         .expect("should insert log");
 
     let retrieved = db
-        .get_value_log((timestamp, true as u8))
+        .get_value_log("sepolia", key)
         .expect("should retrieve log")
         .expect("log should exist");
 
@@ -113,6 +142,357 @@ fn test_log_insert_and_retrieve() {
     assert_eq!(retrieved.result, result);
 }
 
+#[test]
+fn test_log_tx_status_update() {
+    dotenv().ok();
+    let partition_size_limit: u64 = env::var("PARTITION_SIZE_LIMIT")
+        .expect("should get env var for partition size limit")
+        .parse()
+        .expect("should parse u64");
+    let dir = tempdir().expect("should create temp dir");
+    let db_path = dir.path().to_str().expect("valid path");
+    let db = DB::new(db_path, partition_size_limit, &["sepolia"]).expect("should create DB");
+
+    let timestamp = 1755965000u64;
+    let mut value = log_value("beast@beast", "0x1", "");
+    value.tx_status = Some(TxStatus::Pending);
+    let key = db
+        .insert_k_v_logs("sepolia", timestamp, true, value)
+        .expect("should insert log");
+
+    assert!(
+        db.set_log_tx_status("sepolia", key, TxStatus::Confirmed)
+            .expect("should update")
+    );
+    let retrieved = db
+        .get_value_log("sepolia", key)
+        .expect("should retrieve log")
+        .expect("log should exist");
+    assert_eq!(retrieved.tx_status, Some(TxStatus::Confirmed));
+    assert_eq!(retrieved.result, "0x1");
+
+    assert!(
+        !db.set_log_tx_status(
+            "sepolia",
+            LogKey {
+                timestamp: timestamp + 1,
+                ..key
+            },
+            TxStatus::Failed
+        )
+        .expect("should not fail"),
+        "Missing entries aren't created"
+    );
+    assert_eq!(db.iter_logs("sepolia").expect("known network").count(), 1);
+}
+
+#[test]
+fn test_pending_logs() {
+    dotenv().ok();
+    let partition_size_limit: u64 = env::var("PARTITION_SIZE_LIMIT")
+        .expect("should get env var for partition size limit")
+        .parse()
+        .expect("should parse u64");
+    let dir = tempdir().expect("should create temp dir");
+    let db_path = dir.path().to_str().expect("valid path");
+    let timestamp = 1755965000u64;
+    let (queued, pending) = {
+        let db = DB::new(db_path, partition_size_limit, &["sepolia", "hoodi"])
+            .expect("should create DB");
+        let mut value = log_value("beast@beast", "", "");
+        value.tx_status = Some(TxStatus::Queued);
+        let queued = db
+            .insert_k_v_logs("sepolia", timestamp, true, value)
+            .expect("should insert log");
+        let mut value = log_value("beast@beast", "0x1", "");
+        value.tx_status = Some(TxStatus::Pending);
+        let pending = db
+            .insert_k_v_logs("sepolia", timestamp, true, value)
+            .expect("should insert log");
+        db.insert_k_v_logs(
+            "sepolia",
+            timestamp,
+            false,
+            log_value("beast@beast", "No", ""),
+        )
+        .expect("should insert log");
+        (queued, pending)
+    };
+
+    let db =
+        DB::new(db_path, partition_size_limit, &["sepolia", "hoodi"]).expect("should reopen DB");
+    let keys = |db: &DB| -> Vec<LogKey> {
+        db.pending_logs("sepolia")
+            .expect("known network")
+            .into_iter()
+            .map(|entry| entry.key)
+            .collect()
+    };
+    assert_eq!(keys(&db), vec![queued, pending]);
+    assert!(db.pending_logs("hoodi").expect("known network").is_empty());
+
+    // settled entries leave the index, the log keeps them
+    db.set_log_tx_status("sepolia", pending, TxStatus::Confirmed)
+        .expect("should update");
+    assert_eq!(keys(&db), vec![queued]);
+    db.update_log("sepolia", queued, |value| {
+        value.result = "0x2".to_string();
+        value.tx_status = Some(TxStatus::Pending);
+    })
+    .expect("should update");
+    let entries = db.pending_logs("sepolia").expect("known network");
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].value.result, "0x2");
+    db.set_log_tx_status("sepolia", queued, TxStatus::Failed)
+        .expect("should update");
+    assert!(keys(&db).is_empty());
+    assert_eq!(db.iter_logs("sepolia").expect("known network").count(), 3);
+}
+
+#[test]
+fn test_token_claims_skip_last_claim() {
+    dotenv().ok();
+    let partition_size_limit: u64 = env::var("PARTITION_SIZE_LIMIT")
+        .expect("should get env var for partition size limit")
+        .parse()
+        .expect("should parse u64");
+    let dir = tempdir().expect("should create temp dir");
+    let db_path = dir.path().to_str().expect("valid path");
+    let db = DB::new(db_path, partition_size_limit, &["sepolia"]).expect("should create DB");
+
+    let eth_time = 1755965000u64;
+    let token_time = 1755965100u64;
+    db.insert_k_v_logs(
+        "sepolia",
+        eth_time,
+        true,
+        log_value("beast@beast", "0x1", ""),
+    )
+    .expect("should insert log");
+    let token_key = db
+        .insert_k_v_logs(
+            "sepolia",
+            token_time,
+            true,
+            log_value("beast@beast", "0x2", "usdc"),
+        )
+        .expect("should insert log");
+
+    assert_eq!(
+        db.get_last_claim_timestamp("sepolia", 0, token_time)
+            .expect("should get"),
+        eth_time,
+        "Token payouts shouldn't reset the ETH payout curve"
+    );
+    let token_log = db
+        .get_value_log("sepolia", token_key)
+        .expect("should retrieve log")
+        .expect("log should exist");
+    assert_eq!(token_log.asset, "usdc");
+}
+
+#[test]
+fn test_count_claims() {
+    let dir = tempdir().expect("should create temp dir");
+    let db_path = dir.path().to_str().expect("valid path");
+    let db = DB::new(db_path, 1024 * 1024, &["sepolia"]).expect("should create DB");
+
+    let start = 1755965000u64;
+    for (offset, status, asset) in [
+        (0, true, ""),
+        (10, true, ""),
+        (20, false, ""),
+        (30, true, "usdc"),
+        (40, true, ""),
+    ] {
+        db.insert_k_v_logs(
+            "sepolia",
+            start + offset,
+            status,
+            log_value("beast@beast", "0x1", asset),
+        )
+        .expect("should insert log");
+    }
+
+    // refused claims and token payouts don't count
+    assert_eq!(db.count_claims("sepolia", 0, start + 40).expect("count"), 3);
+    assert_eq!(
+        db.count_claims("sepolia", start + 10, start + 39)
+            .expect("count"),
+        1
+    );
+    assert_eq!(
+        db.count_claims("sepolia", start + 41, start + 100)
+            .expect("count"),
+        0
+    );
+}
+
+#[test]
+fn test_spending_budget() {
+    let dir = tempdir().expect("should create temp dir");
+    let db_path = dir.path().to_str().expect("valid path");
+    let db = DB::new(db_path, 1024 * 1024, &["sepolia", "hoodi"]).expect("should create DB");
+    let limits = [(BudgetPeriod::Day, 100), (BudgetPeriod::Hour, 40)];
+    // 00:00 UTC
+    let day = 1755907200u64;
+
+    for _ in 0..2 {
+        assert_eq!(
+            db.reserve_spend("sepolia", &limits, day + 60, 20)
+                .expect("should reserve"),
+            SpendOutcome::Reserved
+        );
+    }
+    // the hour is full, nothing is taken from the day
+    assert_eq!(
+        db.reserve_spend("sepolia", &limits, day + 120, 1)
+            .expect("should check"),
+        SpendOutcome::OverBudget(BudgetPeriod::Hour)
+    );
+    assert_eq!(
+        db.spent("sepolia", BudgetPeriod::Day, day).expect("spent"),
+        40
+    );
+    // other networks keep their own totals
+    assert_eq!(db.spent("hoodi", BudgetPeriod::Day, day).expect("spent"), 0);
+
+    // the next hour starts over, the day doesn't
+    assert_eq!(
+        db.reserve_spend("sepolia", &limits, day + 3600, 40)
+            .expect("should reserve"),
+        SpendOutcome::Reserved
+    );
+    assert_eq!(
+        db.reserve_spend("sepolia", &limits, day + 7200, 40)
+            .expect("should check"),
+        SpendOutcome::OverBudget(BudgetPeriod::Day)
+    );
+    db.release_spend(
+        "sepolia",
+        &[BudgetPeriod::Day, BudgetPeriod::Hour],
+        day + 3600,
+        40,
+    )
+    .expect("should release");
+    assert_eq!(
+        db.spent("sepolia", BudgetPeriod::Day, day).expect("spent"),
+        40
+    );
+    assert_eq!(
+        db.spent("sepolia", BudgetPeriod::Day, day + 86400)
+            .expect("spent"),
+        0
+    );
+}
+
+#[test]
+fn test_daily_quota() {
+    let dir = tempdir().expect("should create temp dir");
+    let db_path = dir.path().to_str().expect("valid path");
+    let db = DB::new(db_path, 1024 * 1024, &["sepolia"]).expect("should create DB");
+    // 00:00 UTC
+    let day = 1755907200u64;
+
+    for _ in 0..2 {
+        assert!(
+            db.reserve_quota("sepolia", "community:dao", day + 10, 2)
+                .expect("should reserve")
+        );
+    }
+    assert!(
+        !db.reserve_quota("sepolia", "community:dao", day + 20, 2)
+            .expect("should check")
+    );
+    // quotas are counted apart
+    assert!(
+        db.reserve_quota("sepolia", "community:eth", day + 20, 2)
+            .expect("should reserve")
+    );
+
+    db.release_quota("sepolia", "community:dao", day + 10)
+        .expect("should release");
+    assert_eq!(
+        db.quota_used("sepolia", "community:dao", day)
+            .expect("should get"),
+        1
+    );
+    // a new day starts over
+    assert_eq!(
+        db.quota_used("sepolia", "community:dao", day + 86400)
+            .expect("should get"),
+        0
+    );
+}
+
+#[test]
+fn test_access_lists() {
+    let dir = tempdir().expect("should create temp dir");
+    let db_path = dir.path().to_str().expect("valid path");
+    let db = DB::new(db_path, 1024 * 1024, &["sepolia"]).expect("should create DB");
+    let address = "0xf0e5d3cc05206987a125afc404b719e54fa942a8";
+
+    db.add_access(AccessList::Deny, address, "drained the faucet")
+        .expect("should add");
+    db.add_access(AccessList::Allow, "alice@eth", "hackathon")
+        .expect("should add");
+    assert_eq!(
+        db.access_reason(AccessList::Deny, address)
+            .expect("should get"),
+        Some("drained the faucet".to_string())
+    );
+    // the lists are kept apart
+    assert_eq!(
+        db.access_reason(AccessList::Allow, address)
+            .expect("should get"),
+        None
+    );
+    assert_eq!(
+        db.access_entries(AccessList::Allow).expect("should list"),
+        vec![AccessEntry {
+            key: "alice@eth".to_string(),
+            reason: "hackathon".to_string(),
+        }]
+    );
+
+    assert!(
+        db.remove_access(AccessList::Deny, address)
+            .expect("should remove")
+    );
+    assert!(
+        !db.remove_access(AccessList::Deny, address)
+            .expect("should check")
+    );
+    assert!(
+        db.access_entries(AccessList::Deny)
+            .expect("should list")
+            .is_empty()
+    );
+}
+
+#[test]
+fn test_challenges_are_used_once() {
+    let dir = tempdir().expect("should create temp dir");
+    let db_path = dir.path().to_str().expect("valid path");
+    let db = DB::new(db_path, 1024 * 1024, &["sepolia"]).expect("should create DB");
+    let now = 1755907200u64;
+
+    assert!(db.use_challenge("a1", now + 300, now).expect("should use"));
+    assert!(
+        !db.use_challenge("a1", now + 300, now + 10)
+            .expect("should check")
+    );
+    assert!(
+        db.use_challenge("b2", now + 300, now + 10)
+            .expect("should use")
+    );
+    // dropped once expired
+    assert!(
+        db.use_challenge("a1", now + 300, now + 301)
+            .expect("should use")
+    );
+}
+
 #[test]
 pub fn insert_many_k_v_test_fifo() {
     dotenv().ok();
@@ -122,10 +502,10 @@ pub fn insert_many_k_v_test_fifo() {
         .expect("should parse u64");
     let dir = tempdir().expect("./fjall_data_test");
     let db_path = dir.path().to_str().expect("valid path");
-    let db = DB::new(db_path, partition_size_limit).expect("should create DB");
+    let db = DB::new(db_path, partition_size_limit, &["sepolia"]).expect("should create DB");
     for x in 0u64..1_000_000 {
         if x % 100_000 == 0 {
-            let db_meta = db.get_db_meta().unwrap();
+            let db_meta = db.get_db_meta("sepolia").unwrap();
 
             println!(
                 "Keyspace DISK SPACE : {} KB",
@@ -150,15 +530,454 @@ pub fn insert_many_k_v_test_fifo() {
         }
 
         db.insert_k_v_logs(
+            "sepolia",
             x,
             false,
-            format!("user{}", x),
-            "Unable to resolve the name: No name found".to_string(),
+            log_value(
+                &format!("user{}", x),
+                "Unable to resolve the name: No name found",
+                "",
+            ),
         )
         .unwrap();
 
-        let base: i128 = 0x3983cD648de4d0509f56; //just 10 bytes of real address
+        let base: i128 = 0x3983cd648de4d0509f56; //just 10 bytes of real address
         let claim_key = &format!("0x{:016x}{:020x}", base, x); //preparing address like 0x3983cd648de4d0509f560000000000000000a87a
-        db.insert_k_v_claim(claim_key, 1234567890u64).unwrap();
+        db.insert_k_v_claim("sepolia", claim_key, 1234567890u64)
+            .unwrap();
+    }
+}
+
+#[test]
+fn test_reserve_claim_cooldown_and_release() {
+    dotenv().ok();
+    let partition_size_limit: u64 = env::var("PARTITION_SIZE_LIMIT")
+        .expect("should get env var for partition size limit")
+        .parse()
+        .expect("should parse u64");
+    let dir = tempdir().expect("should create temp dir");
+    let db_path = dir.path().to_str().expect("valid path");
+    let db = DB::new(db_path, partition_size_limit, &["sepolia"]).expect("should create DB");
+
+    let test_key = "0xf0E5D3Cc05206987a125afC404b719e54Fa942a8";
+    let cooldown_sec = 86400u64;
+    let keys = vec![(test_key.to_string(), cooldown_sec)];
+    let first_claim = 1755965000u64;
+
+    let ReserveOutcome::Reserved(reservation) = db
+        .reserve_claim("sepolia", &keys, first_claim)
+        .expect("should reserve")
+    else {
+        panic!("first reservation should be granted");
+    };
+    assert!(
+        matches!(
+            db.reserve_claim("sepolia", &keys, first_claim + 10)
+                .expect("should reserve"),
+            ReserveOutcome::InCooldown { index: 0, ends_at } if ends_at == first_claim + cooldown_sec
+        ),
+        "Second reservation inside cooldown should be refused"
+    );
+
+    // failed payout: slot is given back and the key has no claim again
+    db.release_claim(reservation).expect("should release");
+    assert_eq!(
+        db.get_value_claim("sepolia", test_key).expect("should get"),
+        None
+    );
+
+    let ReserveOutcome::Reserved(reservation) = db
+        .reserve_claim("sepolia", &keys, first_claim + 20)
+        .expect("should reserve")
+    else {
+        panic!("reservation after release should be granted");
+    };
+    assert_eq!(reservation.timestamp, first_claim + 20);
+    assert!(
+        matches!(
+            db.reserve_claim("sepolia", &keys, first_claim + 20 + cooldown_sec)
+                .expect("should reserve"),
+            ReserveOutcome::Reserved(_)
+        ),
+        "Reservation after cooldown should be granted"
+    );
+}
+
+#[test]
+fn test_reserve_claim_multiple_identities() {
+    dotenv().ok();
+    let partition_size_limit: u64 = env::var("PARTITION_SIZE_LIMIT")
+        .expect("should get env var for partition size limit")
+        .parse()
+        .expect("should parse u64");
+    let dir = tempdir().expect("should create temp dir");
+    let db_path = dir.path().to_str().expect("valid path");
+    let db = DB::new(db_path, partition_size_limit, &["sepolia"]).expect("should create DB");
+
+    let address = "0xf0E5D3Cc05206987a125afC404b719e54Fa942a8".to_string();
+    let timestamp = 1755965000u64;
+
+    // alice@eth claims: address, token id and community windows are all taken
+    let alice = vec![
+        (address.clone(), 86400),
+        ("token:1".to_string(), 43200),
+        ("tln:eth".to_string(), 60),
+    ];
+    assert!(matches!(
+        db.reserve_claim("sepolia", &alice, timestamp)
+            .expect("should reserve"),
+        ReserveOutcome::Reserved(_)
+    ));
+
+    // bob@eth points to the same wallet: blocked by the address window
+    let bob = vec![
+        (address.clone(), 86400),
+        ("token:2".to_string(), 43200),
+        ("tln:eth".to_string(), 60),
+    ];
+    assert!(matches!(
+        db.reserve_claim("sepolia", &bob, timestamp + 120)
+            .expect("should reserve"),
+        ReserveOutcome::InCooldown { index: 0, .. }
+    ));
+    // nothing of a refused reservation is written
+    assert_eq!(
+        db.get_value_claim("sepolia", "token:2")
+            .expect("should get"),
+        None
+    );
+
+    // alice@eth re-pointed to a new wallet: blocked by the token id window
+    let alice_new_wallet = vec![
+        (
+            "0x3983cd648de4d0509f560000000000000000a87a".to_string(),
+            86400,
+        ),
+        ("token:1".to_string(), 43200),
+        ("tln:eth".to_string(), 60),
+    ];
+    assert!(matches!(
+        db.reserve_claim("sepolia", &alice_new_wallet, timestamp + 120)
+            .expect("should reserve"),
+        ReserveOutcome::InCooldown { index: 1, .. }
+    ));
+}
+
+#[test]
+fn test_parallel_claims_pay_once() {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::thread;
+
+    dotenv().ok();
+    let partition_size_limit: u64 = env::var("PARTITION_SIZE_LIMIT")
+        .expect("should get env var for partition size limit")
+        .parse()
+        .expect("should parse u64");
+    let dir = tempdir().expect("should create temp dir");
+    let db_path = dir.path().to_str().expect("valid path");
+    let db = DB::new(db_path, partition_size_limit, &["sepolia"]).expect("should create DB");
+
+    let test_key = "0xf0E5D3Cc05206987a125afC404b719e54Fa942a8";
+    let timestamp = 1755965000u64;
+    let payouts = Arc::new(AtomicUsize::new(0));
+
+    // every thread runs the claim_server sequence: reserve -> send (stub) -> keep or release
+    let handles: Vec<_> = (0..64)
+        .map(|_| {
+            let db = db.clone();
+            let payouts = payouts.clone();
+            thread::spawn(move || {
+                let keys = vec![(test_key.to_string(), 86400)];
+                if let ReserveOutcome::Reserved(_) = db
+                    .reserve_claim("sepolia", &keys, timestamp)
+                    .expect("should reserve")
+                {
+                    thread::sleep(std::time::Duration::from_millis(5));
+                    payouts.fetch_add(1, Ordering::SeqCst);
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().expect("thread should finish");
+    }
+
+    assert_eq!(
+        payouts.load(Ordering::SeqCst),
+        1,
+        "Only one parallel claim should be paid"
+    );
+    assert_eq!(
+        db.get_value_claim("sepolia", test_key).expect("should get"),
+        Some(timestamp)
+    );
+}
+
+#[test]
+fn test_networks_are_separated() {
+    dotenv().ok();
+    let partition_size_limit: u64 = env::var("PARTITION_SIZE_LIMIT")
+        .expect("should get env var for partition size limit")
+        .parse()
+        .expect("should parse u64");
+    let dir = tempdir().expect("should create temp dir");
+    let db_path = dir.path().to_str().expect("valid path");
+    let db =
+        DB::new(db_path, partition_size_limit, &["sepolia", "hoodi"]).expect("should create DB");
+
+    let keys = vec![(
+        "0xf0E5D3Cc05206987a125afC404b719e54Fa942a8".to_string(),
+        86400,
+    )];
+    let timestamp = 1755965000u64;
+
+    // same address can claim once on every network
+    for network in ["sepolia", "hoodi"] {
+        assert!(matches!(
+            db.reserve_claim(network, &keys, timestamp)
+                .expect("should reserve"),
+            ReserveOutcome::Reserved(_)
+        ));
+    }
+    db.insert_k_v_logs(
+        "hoodi",
+        timestamp,
+        true,
+        log_value("beast@beast", "0x1", ""),
+    )
+    .expect("should insert log");
+
+    assert_eq!(db.iter_logs("sepolia").expect("known network").count(), 0);
+    assert_eq!(db.iter_logs("hoodi").expect("known network").count(), 1);
+    assert_eq!(
+        db.get_last_claim_timestamp("sepolia", 0, timestamp)
+            .expect("should get"),
+        0
+    );
+    assert!(matches!(
+        db.iter_logs("holesky"),
+        Err(DBErrors::UnknownNetwork(_))
+    ));
+}
+
+#[test]
+fn test_nonces_persist() {
+    dotenv().ok();
+    let partition_size_limit: u64 = env::var("PARTITION_SIZE_LIMIT")
+        .expect("should get env var for partition size limit")
+        .parse()
+        .expect("should parse u64");
+    let dir = tempdir().expect("should create temp dir");
+    let db_path = dir.path().to_str().expect("valid path");
+
+    {
+        let db = DB::new(db_path, partition_size_limit, &["sepolia"]).expect("should create DB");
+        assert_eq!(db.get_nonce("sepolia:0x1").expect("should get"), None);
+        db.set_nonce("sepolia:0x1", 42).expect("should set");
+        db.set_nonce("hoodi:0x1", 7).expect("should set");
     }
+
+    let db = DB::new(db_path, partition_size_limit, &["sepolia"]).expect("should reopen DB");
+    assert_eq!(db.get_nonce("sepolia:0x1").expect("should get"), Some(42));
+    assert_eq!(db.get_nonce("hoodi:0x1").expect("should get"), Some(7));
+}
+
+#[test]
+fn test_batch_queue() {
+    dotenv().ok();
+    let partition_size_limit: u64 = env::var("PARTITION_SIZE_LIMIT")
+        .expect("should get env var for partition size limit")
+        .parse()
+        .expect("should parse u64");
+    let dir = tempdir().expect("should create temp dir");
+    let db_path = dir.path().to_str().expect("valid path");
+    let payout = |n: u64| QueuedPayout {
+        receiver: format!("0x{:040x}", n),
+        amount: "50000000000000000".to_string(),
+        log_key: LogKey {
+            timestamp: 1755965000 + n,
+            status: 1,
+            seq: 0,
+        },
+        queued_at: 1755965000 + n,
+        claim_slots: vec![(format!("address:0x{:040x}", n), None)],
+        quota: None,
+        attempts: 0,
+    };
+
+    let last = {
+        let db = DB::new(db_path, partition_size_limit, &["sepolia", "hoodi"])
+            .expect("should create DB");
+        for n in 0..3 {
+            db.enqueue_payout("sepolia", &payout(n))
+                .expect("should enqueue");
+        }
+        db.enqueue_payout("hoodi", &payout(9))
+            .expect("should enqueue")
+    };
+
+    // keys keep growing after a restart, the queue keeps its order
+    let db =
+        DB::new(db_path, partition_size_limit, &["sepolia", "hoodi"]).expect("should reopen DB");
+    let key = db
+        .enqueue_payout("sepolia", &payout(3))
+        .expect("should enqueue");
+    assert!(key > last);
+    assert_eq!(db.queue_len("sepolia").expect("should get"), 4);
+    assert_eq!(db.queue_len("hoodi").expect("should get"), 1);
+
+    let queued = db.queued_payouts("sepolia", 2).expect("should get queue");
+    assert_eq!(
+        queued.iter().map(|(_, p)| p.clone()).collect::<Vec<_>>(),
+        vec![payout(0), payout(1)]
+    );
+    let keys: Vec<u64> = queued.iter().map(|(k, _)| *k).collect();
+    db.remove_queued("sepolia", &keys).expect("should remove");
+    let queued = db.queued_payouts("sepolia", 10).expect("should get queue");
+    assert_eq!(
+        queued.iter().map(|(_, p)| p.clone()).collect::<Vec<_>>(),
+        vec![payout(2), payout(3)]
+    );
+
+    // rewritten in place, still first in line
+    let retried = QueuedPayout {
+        attempts: 1,
+        ..payout(2)
+    };
+    db.requeue_payout("sepolia", queued[0].0, &retried)
+        .expect("should requeue");
+    let queued = db.queued_payouts("sepolia", 10).expect("should get queue");
+    assert_eq!(
+        queued.into_iter().map(|(_, p)| p).collect::<Vec<_>>(),
+        vec![retried, payout(3)]
+    );
+}
+
+#[test]
+fn test_logs_in_same_second() {
+    let dir = tempdir().expect("should create temp dir");
+    let db_path = dir.path().to_str().expect("valid path");
+    let db = DB::new(db_path, 1024 * 1024, &["sepolia"]).expect("should create DB");
+
+    let timestamp = 1755965000u64;
+    let keys: Vec<LogKey> = (0..3)
+        .map(|n| {
+            db.insert_k_v_logs(
+                "sepolia",
+                timestamp,
+                true,
+                log_value(&format!("beast{}@beast", n), "0x1", ""),
+            )
+            .expect("should insert log")
+        })
+        .collect();
+    db.insert_k_v_logs("sepolia", timestamp, false, log_value("x@beast", "err", ""))
+        .expect("should insert log");
+
+    assert_eq!(
+        keys.iter().map(|k| k.seq).collect::<Vec<_>>(),
+        vec![0, 1, 2]
+    );
+    assert!(
+        keys.iter()
+            .all(|k| k.timestamp == timestamp && k.status == 1)
+    );
+    assert_eq!(db.iter_logs("sepolia").expect("known network").count(), 4);
+    assert_eq!(
+        db.count_claims("sepolia", timestamp, timestamp)
+            .expect("should count"),
+        3
+    );
+
+    // every entry is updated on its own
+    assert!(
+        db.set_log_tx_status("sepolia", keys[1], TxStatus::Confirmed)
+            .expect("should update")
+    );
+    for (n, key) in keys.iter().enumerate() {
+        let value = db
+            .get_value_log("sepolia", *key)
+            .expect("should retrieve log")
+            .expect("log should exist");
+        assert_eq!(value.input, format!("beast{}@beast", n));
+        assert_eq!(
+            value.tx_status,
+            (n == 1).then_some(TxStatus::Confirmed),
+            "{}",
+            n
+        );
+    }
+    let logged: Vec<LogKey> = db
+        .iter_logs("sepolia")
+        .expect("known network")
+        .map(|entry| entry.expect("should read").key)
+        .filter(|key| key.status == 1)
+        .collect();
+    assert_eq!(logged, keys);
+}
+
+#[test]
+fn test_concurrent_log_updates() {
+    let dir = tempdir().expect("should create temp dir");
+    let db_path = dir.path().to_str().expect("valid path");
+    let db = DB::new(db_path, 1024 * 1024, &["sepolia"]).expect("should create DB");
+
+    let key = db
+        .insert_k_v_logs(
+            "sepolia",
+            1755965000,
+            true,
+            log_value("beast@beast", "", ""),
+        )
+        .expect("should insert log");
+    let handles: Vec<_> = (0..8)
+        .map(|n| {
+            let db = db.clone();
+            std::thread::spawn(move || {
+                db.update_log("sepolia", key, |value| {
+                    value.result.push_str(&n.to_string());
+                })
+                .expect("should update")
+            })
+        })
+        .collect();
+    for handle in handles {
+        assert!(handle.join().expect("should join"));
+    }
+
+    let value = db
+        .get_value_log("sepolia", key)
+        .expect("should retrieve log")
+        .expect("log should exist");
+    assert_eq!(value.result.len(), 8, "no update is lost");
+}
+
+#[test]
+fn test_queued_payout_before_log_seq() {
+    // queued before log keys had a seq, as (timestamp, status)
+    #[derive(serde::Serialize)]
+    struct OldPayout {
+        receiver: String,
+        amount: String,
+        log_key: (u64, u8),
+        queued_at: u64,
+    }
+    let old = rmp_serde::to_vec(&OldPayout {
+        receiver: "0x1".to_string(),
+        amount: "1".to_string(),
+        log_key: (1755965000, 1),
+        queued_at: 1755965000,
+    })
+    .expect("should serialize");
+
+    let payout: QueuedPayout = rmp_serde::from_slice(&old).expect("should deserialize");
+    assert_eq!(
+        payout.log_key,
+        LogKey {
+            timestamp: 1755965000,
+            status: 1,
+            seq: 0,
+        }
+    );
 }
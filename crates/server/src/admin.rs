@@ -0,0 +1,229 @@
+// recipient allow and deny lists: the claim check, the admin functions behind ADMIN_TOKEN and
+// the command line client that calls them on a running faucet
+use crate::server::{ClaimError, prevented};
+use crate::state::{AccessMode, AppState};
+use dioxus::prelude::*;
+use serde::de::DeserializeOwned;
+use shared_types::{AccessEntry, AccessList, ClaimTarget, Prevention};
+use std::env;
+use zeroxname_ethereum::Address;
+
+// the running faucet holds the db, the cli goes through its admin functions
+const DEFAULT_FAUCET_URL: &str = "http://127.0.0.1:8080";
+const USAGE: &str =
+    "usage: --admin <allow|deny> list | add <address or name> [reason] | remove <address or name>";
+
+fn address_key(address: &Address) -> String {
+    format!("{:#x}", address)
+}
+
+// list key of an admin entry, parsed like a claim: lower case hex for addresses, lower case
+// names like alice@eth or alice.eth
+pub fn access_key(entry: &str) -> Result<String, ServerFnError> {
+    match entry.parse::<ClaimTarget>() {
+        Ok(ClaimTarget::Address(address)) => Ok(address_key(&Address::from(address))),
+        Ok(target) => Ok(target.to_string().to_lowercase()),
+        Err(_) => Err(ServerFnError::ServerError(format!(
+            "{} is neither an address nor a name like alice@eth or alice.eth",
+            entry.trim()
+        ))),
+    }
+}
+
+// refuses denylisted names and addresses with the listed reason, and in allowlist mode claims
+// where neither is allowlisted. `address` is None until the name is resolved
+pub fn check_access(
+    app_state: &AppState,
+    input: &str,
+    address: Option<&Address>,
+) -> Result<(), ClaimError> {
+    let mut keys = vec![input.trim().to_lowercase()];
+    keys.extend(address.map(address_key));
+    for key in &keys {
+        if let Some(reason) = app_state.db.access_reason(AccessList::Deny, key)? {
+            return Err(prevented(
+                Prevention::Denylisted,
+                match reason.as_str() {
+                    "" => format!("{} is blocked from this faucet!", input),
+                    reason => format!("{} is blocked from this faucet: {}", input, reason),
+                },
+            ));
+        }
+    }
+    if address.is_none() || app_state.access_mode == AccessMode::Open {
+        return Ok(());
+    }
+    for key in &keys {
+        if app_state
+            .db
+            .access_reason(AccessList::Allow, key)?
+            .is_some()
+        {
+            return Ok(());
+        }
+    }
+    Err(prevented(
+        Prevention::NotAllowlisted,
+        format!("{} is not on this faucet's allowlist!", input),
+    ))
+}
+
+// admin functions are refused while ADMIN_TOKEN is unset
+pub fn authorize(app_state: &AppState, token: &str) -> Result<(), ServerFnError> {
+    let Some(expected) = &app_state.admin_token else {
+        return Err(ServerFnError::ServerError(
+            "Admin functions are disabled, ADMIN_TOKEN is not set".into(),
+        ));
+    };
+    // constant time, only the length can leak
+    let matches = expected.len() == token.len()
+        && expected
+            .bytes()
+            .zip(token.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0;
+    match matches {
+        true => Ok(()),
+        false => Err(ServerFnError::ServerError("Invalid admin token".into())),
+    }
+}
+
+// replaces the reason of an entry that's already listed
+pub fn add_access_entry(
+    app_state: &AppState,
+    token: &str,
+    list: AccessList,
+    entry: &str,
+    reason: &str,
+) -> Result<AccessEntry, ServerFnError> {
+    authorize(app_state, token)?;
+    let key = access_key(entry)?;
+    let reason = reason.trim();
+    app_state.db.add_access(list, &key, reason)?;
+    Ok(AccessEntry {
+        key,
+        reason: reason.to_string(),
+    })
+}
+
+// false if the entry wasn't listed
+pub fn remove_access_entry(
+    app_state: &AppState,
+    token: &str,
+    list: AccessList,
+    entry: &str,
+) -> Result<bool, ServerFnError> {
+    authorize(app_state, token)?;
+    Ok(app_state.db.remove_access(list, &access_key(entry)?)?)
+}
+
+pub fn access_entries(
+    app_state: &AppState,
+    token: &str,
+    list: AccessList,
+) -> Result<Vec<AccessEntry>, ServerFnError> {
+    authorize(app_state, token)?;
+    Ok(app_state.db.access_entries(list)?)
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum AdminCliError {
+    #[error("{0}\n{USAGE}")]
+    Usage(String),
+    #[error("ADMIN_TOKEN is not set")]
+    NoToken,
+    #[error("Request error: {0}")]
+    Request(#[from] reqwest::Error),
+    #[error("Faucet error: {0}")]
+    Faucet(String),
+    #[error("Runtime error: {0}")]
+    Runtime(#[from] std::io::Error),
+}
+
+// `args` follow --admin. the faucet is at FAUCET_URL, the token is ADMIN_TOKEN, both from the
+// env or .env. returns what to print
+pub fn run_cli(args: &[String]) -> Result<String, AdminCliError> {
+    dotenv::dotenv().ok();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    let (list, command) = match args.as_slice() {
+        [list, command @ ..] => (
+            list.parse::<AccessList>().map_err(AdminCliError::Usage)?,
+            command,
+        ),
+        [] => return Err(AdminCliError::Usage("missing list".into())),
+    };
+    let token = env::var("ADMIN_TOKEN").map_err(|_| AdminCliError::NoToken)?;
+    let url = env::var("FAUCET_URL").unwrap_or_else(|_| DEFAULT_FAUCET_URL.to_string());
+    let client = AdminClient { url, token };
+    let name = format!("{}list", list.as_str());
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+    runtime.block_on(async {
+        match command {
+            ["list"] => {
+                let entries: Vec<AccessEntry> = client.call("list", list, &[]).await?;
+                Ok(match entries.is_empty() {
+                    true => format!("the {} is empty", name),
+                    false => entries
+                        .iter()
+                        .map(|entry| format!("{}\t{}", entry.key, entry.reason))
+                        .collect::<Vec<_>>()
+                        .join("\n"),
+                })
+            }
+            ["add", entry, reason @ ..] => {
+                let reason = reason.join(" ");
+                let added: AccessEntry = client
+                    .call("add", list, &[("entry", entry), ("reason", &reason)])
+                    .await?;
+                Ok(format!("added {} to the {}", added.key, name))
+            }
+            ["remove", entry] => {
+                let removed: bool = client.call("remove", list, &[("entry", entry)]).await?;
+                Ok(match removed {
+                    true => format!("removed {} from the {}", entry, name),
+                    false => format!("{} is not on the {}", entry, name),
+                })
+            }
+            _ => Err(AdminCliError::Usage(format!(
+                "unknown command '{}'",
+                command.join(" ")
+            ))),
+        }
+    })
+}
+
+struct AdminClient {
+    url: String,
+    token: String,
+}
+
+impl AdminClient {
+    // posts the form the admin server function takes, see shared::admin_*
+    async fn call<T: DeserializeOwned>(
+        &self,
+        endpoint: &str,
+        list: AccessList,
+        fields: &[(&str, &str)],
+    ) -> Result<T, AdminCliError> {
+        let mut form = vec![("token", self.token.as_str()), ("list", list.as_str())];
+        form.extend_from_slice(fields);
+        let response = reqwest::Client::new()
+            .post(format!(
+                "{}/api/admin/{}",
+                self.url.trim_end_matches('/'),
+                endpoint
+            ))
+            .form(&form)
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            // server function errors come as "<kind>|<message>"
+            let body = response.text().await?;
+            let message = body.split_once('|').map_or(body.as_str(), |(_, m)| m);
+            return Err(AdminCliError::Faucet(message.to_string()));
+        }
+        Ok(response.json().await?)
+    }
+}
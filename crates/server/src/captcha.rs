@@ -0,0 +1,262 @@
+// captcha check before a claim. hCaptcha and Turnstile share the siteverify protocol: the token
+// the widget gave the claimer is posted with the faucet's secret, the provider says if it's good
+use crate::state::AppState;
+use async_trait::async_trait;
+use dioxus::prelude::*;
+use serde::Deserialize;
+use shared_types::{CaptchaProvider, CaptchaWidget};
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+pub const HCAPTCHA_VERIFY_URL: &str = "https://api.hcaptcha.com/siteverify";
+pub const TURNSTILE_VERIFY_URL: &str = "https://challenges.cloudflare.com/turnstile/v0/siteverify";
+
+const VERIFY_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, thiserror::Error)]
+pub enum CaptchaError {
+    #[error("Captcha request error: {0}")]
+    Request(#[from] reqwest::Error),
+    // the provider refused the faucet's own setup, like a wrong secret
+    #[error("Captcha provider error: {0}")]
+    Provider(String),
+}
+
+#[async_trait]
+pub trait CaptchaVerifier: Send + Sync {
+    // false for tokens that are wrong, expired or used before
+    async fn verify(&self, token: &str) -> Result<bool, CaptchaError>;
+}
+
+#[derive(Debug, Deserialize)]
+#[cfg_attr(feature = "mock", derive(serde::Serialize))]
+struct SiteVerifyResponse {
+    success: bool,
+    #[serde(rename = "error-codes", default)]
+    error_codes: Vec<String>,
+}
+
+// error codes about the token are a failed check, any other is the faucet's or provider's fault
+fn is_token_error(code: &str) -> bool {
+    matches!(
+        code,
+        "missing-input-response"
+            | "invalid-input-response"
+            | "timeout-or-duplicate"
+            | "invalid-or-already-seen-response"
+            | "already-seen-response"
+            | "expired-input-response"
+    )
+}
+
+async fn siteverify(
+    client: &reqwest::Client,
+    url: &str,
+    form: &[(&str, &str)],
+) -> Result<bool, CaptchaError> {
+    let response: SiteVerifyResponse = client
+        .post(url)
+        .form(form)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    match response
+        .error_codes
+        .iter()
+        .find(|code| !is_token_error(code))
+    {
+        Some(code) if !response.success => Err(CaptchaError::Provider(code.clone())),
+        _ => Ok(response.success),
+    }
+}
+
+fn client() -> reqwest::Client {
+    reqwest::Client::builder()
+        .timeout(VERIFY_TIMEOUT)
+        .build()
+        .unwrap_or_default()
+}
+
+pub struct HCaptcha {
+    secret: String,
+    site_key: String,
+    verify_url: String,
+    client: reqwest::Client,
+}
+
+impl HCaptcha {
+    pub fn new(secret: &str, site_key: &str, verify_url: Option<&str>) -> Self {
+        Self {
+            secret: secret.to_string(),
+            site_key: site_key.to_string(),
+            verify_url: verify_url.unwrap_or(HCAPTCHA_VERIFY_URL).to_string(),
+            client: client(),
+        }
+    }
+}
+
+#[async_trait]
+impl CaptchaVerifier for HCaptcha {
+    async fn verify(&self, token: &str) -> Result<bool, CaptchaError> {
+        // the site key makes hCaptcha refuse tokens solved for another site
+        let form = [
+            ("secret", self.secret.as_str()),
+            ("response", token),
+            ("sitekey", self.site_key.as_str()),
+        ];
+        siteverify(&self.client, &self.verify_url, &form).await
+    }
+}
+
+pub struct Turnstile {
+    secret: String,
+    verify_url: String,
+    client: reqwest::Client,
+}
+
+impl Turnstile {
+    pub fn new(secret: &str, verify_url: Option<&str>) -> Self {
+        Self {
+            secret: secret.to_string(),
+            verify_url: verify_url.unwrap_or(TURNSTILE_VERIFY_URL).to_string(),
+            client: client(),
+        }
+    }
+}
+
+#[async_trait]
+impl CaptchaVerifier for Turnstile {
+    async fn verify(&self, token: &str) -> Result<bool, CaptchaError> {
+        let form = [("secret", self.secret.as_str()), ("response", token)];
+        siteverify(&self.client, &self.verify_url, &form).await
+    }
+}
+
+#[derive(Clone)]
+pub struct CaptchaConfig {
+    pub provider: CaptchaProvider,
+    pub secret: String,
+    pub site_key: String,
+    // None is the provider's own endpoint
+    pub verify_url: Option<String>,
+}
+
+impl fmt::Debug for CaptchaConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CaptchaConfig")
+            .field("provider", &self.provider)
+            .field("site_key", &self.site_key)
+            .field("verify_url", &self.verify_url)
+            .finish_non_exhaustive()
+    }
+}
+
+impl CaptchaConfig {
+    pub fn captcha(&self) -> Captcha {
+        let verify_url = self.verify_url.as_deref();
+        let verifier: Arc<dyn CaptchaVerifier> = match self.provider {
+            CaptchaProvider::HCaptcha => {
+                Arc::new(HCaptcha::new(&self.secret, &self.site_key, verify_url))
+            }
+            CaptchaProvider::Turnstile => Arc::new(Turnstile::new(&self.secret, verify_url)),
+        };
+        Captcha {
+            widget: CaptchaWidget {
+                provider: self.provider,
+                site_key: self.site_key.clone(),
+            },
+            verifier,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Captcha {
+    // what the claim form renders
+    pub widget: CaptchaWidget,
+    pub verifier: Arc<dyn CaptchaVerifier>,
+}
+
+// refuses claims without a solved captcha while one is configured
+pub async fn verify_captcha(
+    app_state: &AppState,
+    token: Option<&str>,
+) -> Result<(), ServerFnError> {
+    let Some(captcha) = &app_state.captcha else {
+        return Ok(());
+    };
+    let Some(token) = token.map(str::trim).filter(|token| !token.is_empty()) else {
+        return Err(ServerFnError::ServerError(
+            "Please solve the captcha first".into(),
+        ));
+    };
+    match captcha.verifier.verify(token).await {
+        Ok(true) => Ok(()),
+        Ok(false) => Err(ServerFnError::ServerError(
+            "Captcha check failed, try again!".into(),
+        )),
+        Err(e) => Err(ServerFnError::ServerError(e.to_string())),
+    }
+}
+
+// a local siteverify endpoint in place of the provider's, for tests. it knows one secret and
+// takes each of its tokens once, like the real ones. only built with the mock feature
+#[cfg(feature = "mock")]
+pub mod mock {
+    use super::SiteVerifyResponse;
+    use axum::extract::{Form, State};
+    use axum::routing::post;
+    use axum::{Json, Router};
+    use std::collections::{HashMap, HashSet};
+    use std::sync::{Arc, Mutex};
+    use tokio::net::TcpListener;
+
+    #[derive(Clone)]
+    struct MockProvider {
+        secret: String,
+        tokens: Arc<Mutex<HashSet<String>>>,
+    }
+
+    async fn siteverify(
+        State(provider): State<MockProvider>,
+        Form(form): Form<HashMap<String, String>>,
+    ) -> Json<SiteVerifyResponse> {
+        let field = |name: &str| form.get(name).map(String::as_str).unwrap_or_default();
+        let error = if field("secret") != provider.secret {
+            Some("invalid-input-secret")
+        } else if field("response").is_empty() {
+            Some("missing-input-response")
+        } else if !provider
+            .tokens
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(field("response"))
+        {
+            Some("timeout-or-duplicate")
+        } else {
+            None
+        };
+        Json(SiteVerifyResponse {
+            success: error.is_none(),
+            error_codes: error.into_iter().map(String::from).collect(),
+        })
+    }
+
+    // serves on a free local port, returns the verify url to configure
+    pub async fn serve(secret: &str, tokens: &[&str]) -> std::io::Result<String> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let url = format!("http://{}/siteverify", listener.local_addr()?);
+        let provider = MockProvider {
+            secret: secret.to_string(),
+            tokens: Arc::new(Mutex::new(tokens.iter().map(|t| t.to_string()).collect())),
+        };
+        let router = Router::new()
+            .route("/siteverify", post(siteverify))
+            .with_state(provider);
+        tokio::spawn(async move { axum::serve(listener, router).await });
+        Ok(url)
+    }
+}
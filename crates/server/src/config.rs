@@ -0,0 +1,1073 @@
+use crate::captcha::CaptchaConfig;
+use crate::curve::{CurveShape, PayoutCurve, PayoutMode};
+use crate::pow::{PowConfig, random_bytes};
+use crate::ratelimit::{RateLimit, RateLimitConfig};
+use crate::state::{
+    AccessMode, Asset, AssetKind, BatchConfig, BudgetConfig, CommunityPolicy, Cooldown,
+    CooldownIdentity, EligibilityConfig, FundsConfig, NameChecks, NetworkSettings, SourcePolicy,
+};
+use dotenv::dotenv;
+use shared_types::{CaptchaProvider, ClaimSource};
+use std::collections::HashMap;
+use std::env;
+use std::net::IpAddr;
+use std::path::PathBuf;
+use toml_edit::{DocumentMut, Item, Value};
+use zeroxname_ethereum::pool::WalletStrategy;
+use zeroxname_ethereum::signer::{RemoteSignerApi, SignerConfig};
+use zeroxname_ethereum::{Address, NetworkConfig, U256};
+
+// the file is optional unless CONFIG_FILE names one
+const DEFAULT_CONFIG_FILE: &str = "faucet.toml";
+
+// top level keys, lower case in the file, upper case as env vars
+const KEYS: [&str; 66] = [
+    "RPC_MAINNET",
+    "DB_PATH",
+    "PARTITION_SIZE_LIMIT",
+    "NETWORKS",
+    "FAUCET_LIMIT",
+    "FEE_THRESHOLD",
+    "PAYOUT_ADJUSTMENT",
+    "PAYOUT_CURVE",
+    "PAYOUT_PERIOD_SEC",
+    "PAYOUT_STEP_SEC",
+    "PAYOUT_CURVE_RATE",
+    "PAYOUT_STEPS",
+    "PAYOUT_MODE",
+    "PAYOUT_BUDGET_WEI",
+    "COOLDOWN_SEC",
+    "COOLDOWN_TOKEN_ID_SEC",
+    "COOLDOWN_COMMUNITY_SEC",
+    "RECEIPT_TIMEOUT_SEC",
+    "REBROADCAST_AFTER_SEC",
+    "BATCH_INTERVAL_SEC",
+    "BATCH_MAX_CLAIMS",
+    "LOW_BALANCE_WEI",
+    "MIN_BALANCE_WEI",
+    "BUDGET_DAILY_WEI",
+    "BUDGET_HOURLY_WEI",
+    "COMMUNITIES",
+    "CLAIM_SOURCES",
+    "ZEROXNAME_MULTIPLIER",
+    "ENS_MULTIPLIER",
+    "ADDRESS_MULTIPLIER",
+    "ACCESS_MODE",
+    "ADMIN_TOKEN",
+    "MIN_MAINNET_BALANCE_WEI",
+    "MIN_MAINNET_NONCE",
+    "REQUIRE_UNEXPIRED_NAME",
+    "REQUIRE_PRIMARY_NAME",
+    "RATE_LIMIT_CLAIM_PER_MIN",
+    "RATE_LIMIT_CLAIM_BURST",
+    "RATE_LIMIT_PAYOUT_PER_MIN",
+    "RATE_LIMIT_PAYOUT_BURST",
+    "RATE_LIMIT_LOGS_PER_MIN",
+    "RATE_LIMIT_LOGS_BURST",
+    "RATE_LIMIT_STATUS_PER_MIN",
+    "RATE_LIMIT_STATUS_BURST",
+    "RATE_LIMIT_CHALLENGE_PER_MIN",
+    "RATE_LIMIT_CHALLENGE_BURST",
+    "RATE_LIMIT_ADMIN_PER_MIN",
+    "RATE_LIMIT_ADMIN_BURST",
+    "TRUSTED_PROXIES",
+    "POW_DIFFICULTY",
+    "POW_MAX_DIFFICULTY",
+    "POW_DEMAND_CLAIMS",
+    "POW_TTL_SEC",
+    "POW_SECRET",
+    "CAPTCHA_PROVIDER",
+    "CAPTCHA_SECRET",
+    "CAPTCHA_SITE_KEY",
+    "CAPTCHA_VERIFY_URL",
+    "SIGNER",
+    "PRIVATE_KEY",
+    "KEYSTORE_PATH",
+    "KEYSTORE_PASSWORD_FILE",
+    "REMOTE_SIGNER_URL",
+    "REMOTE_SIGNER_ADDRESS",
+    "REMOTE_SIGNER_API",
+    "WALLET_STRATEGY",
+];
+
+// [networks.<id>] keys and the env var prefix they stand for, suffixed with the network
+const NETWORK_KEYS: [(&str, &str); 7] = [
+    ("name", "NETWORK_NAME"),
+    ("rpc", "RPC"),
+    ("chain_id", "CHAIN_ID"),
+    ("faucet_limit", "FAUCET_LIMIT"),
+    ("fee_threshold", "FEE_THRESHOLD"),
+    ("explorer_url", "EXPLORER_URL"),
+    ("disperse_address", "DISPERSE_ADDRESS"),
+];
+
+// [networks.<id>.assets.<asset>] keys, suffixed with the network and the asset
+const ASSET_KEYS: [(&str, &str); 4] = [
+    ("address", "TOKEN_ADDRESS"),
+    ("amount", "TOKEN_AMOUNT"),
+    ("symbol", "TOKEN_SYMBOL"),
+    ("cooldown_sec", "TOKEN_COOLDOWN_SEC"),
+];
+
+// [communities.<id>] keys, suffixed with the community
+const COMMUNITY_KEYS: [(&str, &str); 5] = [
+    ("multiplier", "COMMUNITY_MULTIPLIER"),
+    ("daily_quota", "COMMUNITY_DAILY_QUOTA"),
+    ("cooldown_sec", "COMMUNITY_COOLDOWN_SEC"),
+    ("allow", "COMMUNITY_ALLOW"),
+    ("deny", "COMMUNITY_DENY"),
+];
+
+// (id, chain id, name, explorer) defaults, unknown networks set CHAIN_ID_<ID> and EXPLORER_URL_<ID>
+const KNOWN_NETWORKS: [(&str, u64, &str, &str); 5] = [
+    (
+        "sepolia",
+        11155111,
+        "Sepolia",
+        "https://sepolia.etherscan.io",
+    ),
+    ("holesky", 17000, "Holesky", "https://holesky.etherscan.io"),
+    ("hoodi", 560048, "Hoodi", "https://hoodi.etherscan.io"),
+    (
+        "base-sepolia",
+        84532,
+        "Base Sepolia",
+        "https://sepolia.basescan.org",
+    ),
+    (
+        "op-sepolia",
+        11155420,
+        "OP Sepolia",
+        "https://sepolia-optimism.etherscan.io",
+    ),
+];
+
+// names the env var (or file key) at fault
+#[derive(Debug, PartialEq, thiserror::Error)]
+#[error("Config error in {key}: {message}")]
+pub struct ConfigError {
+    pub key: String,
+    pub message: String,
+}
+
+impl ConfigError {
+    pub fn new(key: &str, message: impl Into<String>) -> Self {
+        Self {
+            key: key.to_string(),
+            message: message.into(),
+        }
+    }
+}
+
+// a value a config key parses into
+pub trait ConfigValue: Sized {
+    const EXPECTED: &'static str;
+
+    fn parse_value(value: &str) -> Option<Self>;
+}
+
+impl ConfigValue for String {
+    const EXPECTED: &'static str = "a string";
+
+    fn parse_value(value: &str) -> Option<Self> {
+        Some(value.to_string())
+    }
+}
+
+impl ConfigValue for bool {
+    const EXPECTED: &'static str = "true or false";
+
+    fn parse_value(value: &str) -> Option<Self> {
+        value.parse().ok()
+    }
+}
+
+impl ConfigValue for u64 {
+    const EXPECTED: &'static str = "a non-negative integer";
+
+    fn parse_value(value: &str) -> Option<Self> {
+        value.parse().ok()
+    }
+}
+
+impl ConfigValue for u32 {
+    const EXPECTED: &'static str = "a non-negative integer";
+
+    fn parse_value(value: &str) -> Option<Self> {
+        value.parse().ok()
+    }
+}
+
+impl ConfigValue for usize {
+    const EXPECTED: &'static str = "a non-negative integer";
+
+    fn parse_value(value: &str) -> Option<Self> {
+        value.parse().ok()
+    }
+}
+
+impl ConfigValue for f64 {
+    const EXPECTED: &'static str = "a number";
+
+    fn parse_value(value: &str) -> Option<Self> {
+        value.parse().ok().filter(|v: &f64| v.is_finite())
+    }
+}
+
+impl ConfigValue for U256 {
+    const EXPECTED: &'static str = "an amount in base units";
+
+    fn parse_value(value: &str) -> Option<Self> {
+        value.parse().ok()
+    }
+}
+
+impl ConfigValue for Address {
+    const EXPECTED: &'static str = "an address";
+
+    fn parse_value(value: &str) -> Option<Self> {
+        value.parse().ok()
+    }
+}
+
+impl ConfigValue for IpAddr {
+    const EXPECTED: &'static str = "an IP address";
+
+    fn parse_value(value: &str) -> Option<Self> {
+        value.parse().ok()
+    }
+}
+
+impl ConfigValue for AccessMode {
+    const EXPECTED: &'static str = "open or allowlist";
+
+    fn parse_value(value: &str) -> Option<Self> {
+        match value {
+            "open" => Some(Self::Open),
+            "allowlist" => Some(Self::Allowlist),
+            _ => None,
+        }
+    }
+}
+
+impl ConfigValue for CaptchaProvider {
+    const EXPECTED: &'static str = "hcaptcha or turnstile";
+
+    fn parse_value(value: &str) -> Option<Self> {
+        match value {
+            "hcaptcha" => Some(Self::HCaptcha),
+            "turnstile" => Some(Self::Turnstile),
+            _ => None,
+        }
+    }
+}
+
+impl ConfigValue for WalletStrategy {
+    const EXPECTED: &'static str = "round-robin, most-funded or least-pending";
+
+    fn parse_value(value: &str) -> Option<Self> {
+        value.parse().ok()
+    }
+}
+
+impl ConfigValue for RemoteSignerApi {
+    const EXPECTED: &'static str = "web3signer or clef";
+
+    fn parse_value(value: &str) -> Option<Self> {
+        value.parse().ok()
+    }
+}
+
+// config values by env var name: env vars first, then the file
+#[derive(Debug, Default)]
+pub struct ConfigSource {
+    file: HashMap<String, String>,
+    env: HashMap<String, String>,
+}
+
+impl ConfigSource {
+    // `toml` is the config file, its keys are mapped to env var names
+    pub fn new(toml: &str, env: HashMap<String, String>) -> Result<Self, ConfigError> {
+        let doc: DocumentMut = toml
+            .parse()
+            .map_err(|e| ConfigError::new("config file", format!("{}", e)))?;
+        let mut file = HashMap::new();
+        for (key, item) in doc.iter() {
+            if key == "networks" && item.is_table_like() {
+                flatten_networks(item, &mut file)?;
+                continue;
+            }
+            if key == "communities" && item.is_table_like() {
+                flatten_communities(item, &mut file)?;
+                continue;
+            }
+            let name = key.to_uppercase();
+            if !KEYS.contains(&name.as_str()) {
+                return Err(ConfigError::new(key, "unknown key"));
+            }
+            file.insert(name, scalar(key, item)?);
+        }
+        Ok(Self { file, env })
+    }
+
+    // empty values count as unset, .env lists every key
+    pub fn get(&self, key: &str) -> Option<&str> {
+        [self.env.get(key), self.file.get(key)]
+            .into_iter()
+            .flatten()
+            .map(|value| value.trim())
+            .find(|value| !value.is_empty())
+    }
+
+    pub fn optional<T: ConfigValue>(&self, key: &str) -> Result<Option<T>, ConfigError> {
+        self.get(key)
+            .map(|value| {
+                T::parse_value(value).ok_or_else(|| {
+                    ConfigError::new(key, format!("should be {}, got '{}'", T::EXPECTED, value))
+                })
+            })
+            .transpose()
+    }
+
+    pub fn required<T: ConfigValue>(&self, key: &str) -> Result<T, ConfigError> {
+        self.optional(key)?
+            .ok_or_else(|| ConfigError::new(key, "is required"))
+    }
+
+    pub fn or<T: ConfigValue>(&self, key: &str, default: T) -> Result<T, ConfigError> {
+        Ok(self.optional(key)?.unwrap_or(default))
+    }
+
+    // comma separated, empty items dropped
+    pub fn list(&self, key: &str) -> Vec<String> {
+        self.get(key)
+            .unwrap_or_default()
+            .split(',')
+            .map(|item| item.trim().to_string())
+            .filter(|item| !item.is_empty())
+            .collect()
+    }
+}
+
+// [networks.<id>] tables, in file order they make the default NETWORKS
+fn flatten_networks(item: &Item, file: &mut HashMap<String, String>) -> Result<(), ConfigError> {
+    let networks = item
+        .as_table_like()
+        .ok_or_else(|| ConfigError::new("networks", "should be a table"))?;
+    let mut ids = Vec::new();
+    for (id, network) in networks.iter() {
+        let path = format!("networks.{}", id);
+        let network = network
+            .as_table_like()
+            .ok_or_else(|| ConfigError::new(&path, "should be a table"))?;
+        let suffix = env_suffix(id);
+        for (key, item) in network.iter() {
+            if key == "assets" {
+                flatten_assets(id, item, file)?;
+                continue;
+            }
+            let key_path = format!("{}.{}", path, key);
+            let (_, prefix) = NETWORK_KEYS
+                .iter()
+                .find(|(name, _)| *name == key)
+                .ok_or_else(|| ConfigError::new(&key_path, "unknown key"))?;
+            file.insert(format!("{}_{}", prefix, suffix), scalar(&key_path, item)?);
+        }
+        ids.push(id.to_string());
+    }
+    file.entry("NETWORKS".to_string())
+        .or_insert_with(|| ids.join(","));
+    Ok(())
+}
+
+// [communities.<id>] tables, they make the default COMMUNITIES
+fn flatten_communities(item: &Item, file: &mut HashMap<String, String>) -> Result<(), ConfigError> {
+    let communities = item
+        .as_table_like()
+        .ok_or_else(|| ConfigError::new("communities", "should be a table"))?;
+    let mut ids = Vec::new();
+    for (id, community) in communities.iter() {
+        let path = format!("communities.{}", id);
+        let community = community
+            .as_table_like()
+            .ok_or_else(|| ConfigError::new(&path, "should be a table"))?;
+        for (key, item) in community.iter() {
+            let key_path = format!("{}.{}", path, key);
+            let (_, prefix) = COMMUNITY_KEYS
+                .iter()
+                .find(|(name, _)| *name == key)
+                .ok_or_else(|| ConfigError::new(&key_path, "unknown key"))?;
+            file.insert(
+                format!("{}_{}", prefix, env_suffix(id)),
+                scalar(&key_path, item)?,
+            );
+        }
+        ids.push(id.to_string());
+    }
+    file.entry("COMMUNITIES".to_string())
+        .or_insert_with(|| ids.join(","));
+    Ok(())
+}
+
+fn flatten_assets(
+    network: &str,
+    item: &Item,
+    file: &mut HashMap<String, String>,
+) -> Result<(), ConfigError> {
+    let path = format!("networks.{}.assets", network);
+    let assets = item
+        .as_table_like()
+        .ok_or_else(|| ConfigError::new(&path, "should be a table"))?;
+    let mut ids = Vec::new();
+    for (id, asset) in assets.iter() {
+        let asset_path = format!("{}.{}", path, id);
+        let asset = asset
+            .as_table_like()
+            .ok_or_else(|| ConfigError::new(&asset_path, "should be a table"))?;
+        let suffix = format!("{}_{}", env_suffix(network), env_suffix(id));
+        for (key, item) in asset.iter() {
+            let key_path = format!("{}.{}", asset_path, key);
+            let (_, prefix) = ASSET_KEYS
+                .iter()
+                .find(|(name, _)| *name == key)
+                .ok_or_else(|| ConfigError::new(&key_path, "unknown key"))?;
+            file.insert(format!("{}_{}", prefix, suffix), scalar(&key_path, item)?);
+        }
+        ids.push(id.to_string());
+    }
+    file.insert(format!("ASSETS_{}", env_suffix(network)), ids.join(","));
+    Ok(())
+}
+
+// a file value as the env var would hold it, arrays comma separated
+fn scalar(key: &str, item: &Item) -> Result<String, ConfigError> {
+    let value = item
+        .as_value()
+        .ok_or_else(|| ConfigError::new(key, "should be a value, not a table"))?;
+    match value {
+        Value::Array(items) => Ok(items
+            .iter()
+            .map(|item| value_string(key, item))
+            .collect::<Result<Vec<_>, _>>()?
+            .join(",")),
+        value => value_string(key, value),
+    }
+}
+
+fn value_string(key: &str, value: &Value) -> Result<String, ConfigError> {
+    match value {
+        Value::String(s) => Ok(s.value().clone()),
+        Value::Integer(i) => Ok(i.value().to_string()),
+        Value::Float(f) => Ok(f.value().to_string()),
+        Value::Boolean(b) => Ok(b.value().to_string()),
+        _ => Err(ConfigError::new(
+            key,
+            "should be a string, a number or a list of them",
+        )),
+    }
+}
+
+fn check(key: &str, ok: bool, message: &str) -> Result<(), ConfigError> {
+    match ok {
+        true => Ok(()),
+        false => Err(ConfigError::new(key, message)),
+    }
+}
+
+fn check_url(key: &str, value: &str) -> Result<(), ConfigError> {
+    url::Url::parse(value)
+        .map(|_| ())
+        .map_err(|e| ConfigError::new(key, format!("should be a URL: {}", e)))
+}
+
+fn check_fee_threshold(key: &str, fee_threshold: f64) -> Result<(), ConfigError> {
+    check(
+        key,
+        fee_threshold > 0.0 && fee_threshold <= 1.0,
+        "should be in (0, 1]",
+    )
+}
+
+fn is_valid_id(id: &str) -> bool {
+    !id.is_empty()
+        && id
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+}
+
+fn env_suffix(id: &str) -> String {
+    id.to_uppercase().replace('-', "_")
+}
+
+#[derive(Clone, Debug)]
+pub struct Config {
+    pub rpc_mainnet: String,
+    pub db_path: String,
+    pub partition_size_limit: u64,
+    // in NETWORKS order, the first one is the default
+    pub networks: Vec<NetworkSettings>,
+    pub curve: PayoutCurve,
+    pub cooldowns: Vec<Cooldown>,
+    pub receipt_timeout_sec: u64,
+    pub rebroadcast_after_sec: u64,
+    pub batch: Option<BatchConfig>,
+    pub funds: FundsConfig,
+    pub budget: BudgetConfig,
+    pub communities: Vec<CommunityPolicy>,
+    pub sources: Vec<SourcePolicy>,
+    pub access_mode: AccessMode,
+    pub eligibility: EligibilityConfig,
+    pub name_checks: NameChecks,
+    pub admin_token: Option<String>,
+    pub rate_limits: RateLimitConfig,
+    pub pow: Option<PowConfig>,
+    pub captcha: Option<CaptchaConfig>,
+    pub signers: Vec<SignerConfig>,
+    pub wallet_strategy: WalletStrategy,
+}
+
+impl Config {
+    // CONFIG_FILE (default faucet.toml, if present), overridden by env vars and .env
+    pub fn load() -> Result<Self, ConfigError> {
+        dotenv().ok();
+        let toml = match env::var("CONFIG_FILE") {
+            Ok(path) => std::fs::read_to_string(&path)
+                .map_err(|e| ConfigError::new("CONFIG_FILE", format!("{}: {}", path, e)))?,
+            Err(_) => std::fs::read_to_string(DEFAULT_CONFIG_FILE).unwrap_or_default(),
+        };
+        Self::parse(&toml, env::vars().collect())
+    }
+
+    pub fn parse(toml: &str, env: HashMap<String, String>) -> Result<Self, ConfigError> {
+        Self::from_source(&ConfigSource::new(toml, env)?)
+    }
+
+    pub fn from_source(source: &ConfigSource) -> Result<Self, ConfigError> {
+        let rpc_mainnet: String = source.required("RPC_MAINNET")?;
+        check_url("RPC_MAINNET", &rpc_mainnet)?;
+        let partition_size_limit = source.or("PARTITION_SIZE_LIMIT", 1024 * 1024)?;
+        check(
+            "PARTITION_SIZE_LIMIT",
+            partition_size_limit > 0,
+            "should be above 0",
+        )?;
+
+        // FAUCET_LIMIT and FEE_THRESHOLD are the defaults for networks that don't override them
+        let faucet_limit: u64 = source.required("FAUCET_LIMIT")?;
+        check("FAUCET_LIMIT", faucet_limit > 0, "should be above 0")?;
+        let fee_threshold = source.or("FEE_THRESHOLD", 0.1)?;
+        check_fee_threshold("FEE_THRESHOLD", fee_threshold)?;
+        let curve = payout_curve(source)?;
+
+        // 0 disables the identity
+        let cooldowns = [
+            (CooldownIdentity::Address, source.or("COOLDOWN_SEC", 86400)?),
+            (
+                CooldownIdentity::TokenId,
+                source.or("COOLDOWN_TOKEN_ID_SEC", 0)?,
+            ),
+            (
+                CooldownIdentity::Community,
+                source.or("COOLDOWN_COMMUNITY_SEC", 0)?,
+            ),
+        ]
+        .into_iter()
+        .filter(|(_, window_sec)| *window_sec > 0)
+        .map(|(identity, window_sec)| Cooldown {
+            identity,
+            window_sec,
+        })
+        .collect();
+
+        // 0 disables batching
+        let batch_interval_sec = source.or("BATCH_INTERVAL_SEC", 0)?;
+        let batch_max_claims = source.or("BATCH_MAX_CLAIMS", 100)?;
+        check(
+            "BATCH_MAX_CLAIMS",
+            batch_interval_sec == 0 || batch_max_claims > 0,
+            "should be above 0 when BATCH_INTERVAL_SEC is set",
+        )?;
+        let batch = (batch_interval_sec > 0).then_some(BatchConfig {
+            interval_sec: batch_interval_sec,
+            max_claims: batch_max_claims,
+        });
+
+        // 0 disables the mark
+        let funds = FundsConfig {
+            low_water_wei: source.or("LOW_BALANCE_WEI", U256::ZERO)?,
+            floor_wei: source.or("MIN_BALANCE_WEI", U256::ZERO)?,
+        };
+        check(
+            "LOW_BALANCE_WEI",
+            funds.low_water_wei.is_zero() || funds.low_water_wei > funds.floor_wei,
+            "should be above MIN_BALANCE_WEI",
+        )?;
+
+        // per network, 0 disables the limit
+        let budget = BudgetConfig {
+            daily_wei: source.or("BUDGET_DAILY_WEI", U256::ZERO)?,
+            hourly_wei: source.or("BUDGET_HOURLY_WEI", U256::ZERO)?,
+        };
+
+        // checked on mainnet, 0 disables the threshold
+        let eligibility = EligibilityConfig {
+            min_balance_wei: source.or("MIN_MAINNET_BALANCE_WEI", U256::ZERO)?,
+            min_nonce: source.or("MIN_MAINNET_NONCE", 0)?,
+        };
+
+        // read from the NamesRegistry, 0xNAMEs only
+        let name_checks = NameChecks {
+            unexpired: source.or("REQUIRE_UNEXPIRED_NAME", false)?,
+            primary: source.or("REQUIRE_PRIMARY_NAME", false)?,
+        };
+
+        // unset disables the admin functions
+        let admin_token: Option<String> = source.optional("ADMIN_TOKEN")?;
+        check(
+            "ADMIN_TOKEN",
+            admin_token.as_ref().is_none_or(|token| token.len() >= 16),
+            "should be at least 16 characters",
+        )?;
+
+        let mut ids = source.list("NETWORKS");
+        if ids.is_empty() {
+            ids.push("sepolia".to_string());
+        }
+        let mut networks: Vec<NetworkSettings> = Vec::new();
+        for id in ids {
+            if networks.iter().any(|n| n.config.id == id) {
+                return Err(ConfigError::new("NETWORKS", format!("lists {} twice", id)));
+            }
+            networks.push(NetworkSettings {
+                config: network_config(source, &id, faucet_limit, fee_threshold)?,
+                assets: network_assets(source, &id)?,
+            });
+        }
+
+        Ok(Self {
+            rpc_mainnet,
+            db_path: source.required("DB_PATH")?,
+            partition_size_limit,
+            networks,
+            curve,
+            cooldowns,
+            receipt_timeout_sec: source.or("RECEIPT_TIMEOUT_SEC", 600)?,
+            rebroadcast_after_sec: source.or("REBROADCAST_AFTER_SEC", 180)?,
+            batch,
+            funds,
+            budget,
+            communities: community_policies(source)?,
+            sources: source_policies(source)?,
+            access_mode: source.or("ACCESS_MODE", AccessMode::Open)?,
+            eligibility,
+            name_checks,
+            admin_token,
+            rate_limits: rate_limits(source)?,
+            pow: pow_config(source)?,
+            captcha: captcha_config(source)?,
+            signers: signer_configs(source)?,
+            wallet_strategy: source.or("WALLET_STRATEGY", WalletStrategy::RoundRobin)?,
+        })
+    }
+}
+
+// PAYOUT_CURVE picks the shape: linear (default), exponential with PAYOUT_CURVE_RATE,
+// or steps with PAYOUT_STEPS, the comma separated coefficient per elapsed step.
+// PAYOUT_MODE picks what moves along it: since-last-claim (default), or demand, which spreads
+// PAYOUT_BUDGET_WEI over the claims of the trailing PAYOUT_PERIOD_SEC
+fn payout_curve(source: &ConfigSource) -> Result<PayoutCurve, ConfigError> {
+    let default = PayoutCurve::default();
+    let step_sec = source.or("PAYOUT_STEP_SEC", default.step_sec)?;
+    check("PAYOUT_STEP_SEC", step_sec > 0, "should be above 0")?;
+    let period_sec = source.or("PAYOUT_PERIOD_SEC", default.period_sec)?;
+    check(
+        "PAYOUT_PERIOD_SEC",
+        period_sec >= step_sec,
+        "should be at least PAYOUT_STEP_SEC",
+    )?;
+    let adjustment = source.or("PAYOUT_ADJUSTMENT", default.adjustment)?;
+    check(
+        "PAYOUT_ADJUSTMENT",
+        adjustment >= 0.0,
+        "should not be negative",
+    )?;
+
+    let curve: String = source.or("PAYOUT_CURVE", "linear".to_string())?;
+    let shape = match curve.as_str() {
+        "linear" => CurveShape::Linear,
+        "exponential" => {
+            let rate = source.or("PAYOUT_CURVE_RATE", 0.2)?;
+            check(
+                "PAYOUT_CURVE_RATE",
+                rate > 0.0 && rate < 1.0,
+                "should be in (0, 1)",
+            )?;
+            CurveShape::Exponential { rate }
+        }
+        "steps" => {
+            let table = source
+                .list("PAYOUT_STEPS")
+                .iter()
+                .map(|step| {
+                    f64::parse_value(step).ok_or_else(|| {
+                        ConfigError::new(
+                            "PAYOUT_STEPS",
+                            format!("should list numbers, got '{}'", step),
+                        )
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            check("PAYOUT_STEPS", !table.is_empty(), "is required")?;
+            // the max is where the curve ends
+            check(
+                "PAYOUT_STEPS",
+                table[0] >= 0.0 && table.windows(2).all(|w| w[0] <= w[1]),
+                "should not be negative or decrease",
+            )?;
+            CurveShape::Steps(table)
+        }
+        _ => {
+            return Err(ConfigError::new(
+                "PAYOUT_CURVE",
+                format!("should be linear, exponential or steps, got '{}'", curve),
+            ));
+        }
+    };
+    let mode: String = source.or("PAYOUT_MODE", "since-last-claim".to_string())?;
+    let mode = match mode.as_str() {
+        "since-last-claim" => PayoutMode::SinceLastClaim,
+        "demand" => {
+            let budget_wei: U256 = source.required("PAYOUT_BUDGET_WEI")?;
+            check(
+                "PAYOUT_BUDGET_WEI",
+                !budget_wei.is_zero(),
+                "should be above 0",
+            )?;
+            PayoutMode::Demand { budget_wei }
+        }
+        _ => {
+            return Err(ConfigError::new(
+                "PAYOUT_MODE",
+                format!("should be since-last-claim or demand, got '{}'", mode),
+            ));
+        }
+    };
+    Ok(PayoutCurve {
+        period_sec,
+        step_sec,
+        shape,
+        adjustment,
+        mode,
+    })
+}
+
+// per network keys are suffixed with the upper-cased id, e.g. RPC_BASE_SEPOLIA for base-sepolia
+fn network_config(
+    source: &ConfigSource,
+    id: &str,
+    faucet_limit: u64,
+    fee_threshold: f64,
+) -> Result<NetworkConfig, ConfigError> {
+    if !is_valid_id(id) {
+        return Err(ConfigError::new(
+            "NETWORKS",
+            format!(
+                "invalid network id '{}', use lowercase letters, digits and '-'",
+                id
+            ),
+        ));
+    }
+    let suffix = env_suffix(id);
+    let key = |key: &str| format!("{}_{}", key, suffix);
+    let known = KNOWN_NETWORKS.iter().find(|(known_id, ..)| *known_id == id);
+
+    let rpc_url: String = source.required(&key("RPC"))?;
+    check_url(&key("RPC"), &rpc_url)?;
+    let fee_threshold = source.or(&key("FEE_THRESHOLD"), fee_threshold)?;
+    check_fee_threshold(&key("FEE_THRESHOLD"), fee_threshold)?;
+    let faucet_limit = source.or(&key("FAUCET_LIMIT"), faucet_limit)?;
+    check(&key("FAUCET_LIMIT"), faucet_limit > 0, "should be above 0")?;
+
+    Ok(NetworkConfig {
+        id: id.to_string(),
+        name: match (source.optional(&key("NETWORK_NAME"))?, known) {
+            (Some(name), _) => name,
+            (None, Some((_, _, name, _))) => name.to_string(),
+            (None, None) => id.to_string(),
+        },
+        rpc_url,
+        chain_id: match (source.optional(&key("CHAIN_ID"))?, known) {
+            (Some(chain_id), _) => chain_id,
+            (None, Some((_, chain_id, _, _))) => *chain_id,
+            (None, None) => source.required(&key("CHAIN_ID"))?,
+        },
+        faucet_limit,
+        fee_threshold,
+        explorer_url: match (source.optional(&key("EXPLORER_URL"))?, known) {
+            (Some(url), _) => url,
+            (None, Some((_, _, _, url))) => url.to_string(),
+            (None, None) => source.required(&key("EXPLORER_URL"))?,
+        },
+        disperse_address: source.optional(&key("DISPERSE_ADDRESS"))?,
+    })
+}
+
+// ASSETS_<ID> lists the tokens of a network, e.g. ASSETS_SEPOLIA=usdc,dai.
+// every token is configured with keys suffixed by <ID>_<ASSET>:
+// TOKEN_ADDRESS and TOKEN_AMOUNT (base units) are required,
+// TOKEN_SYMBOL (default: upper-cased asset id) and TOKEN_COOLDOWN_SEC are optional.
+fn network_assets(source: &ConfigSource, network: &str) -> Result<Vec<Asset>, ConfigError> {
+    let assets_key = format!("ASSETS_{}", env_suffix(network));
+    let mut assets = vec![Asset::native()];
+    for id in source.list(&assets_key) {
+        if !is_valid_id(&id) || assets.iter().any(|a| a.id == id) {
+            return Err(ConfigError::new(
+                &assets_key,
+                format!("invalid or duplicate asset id '{}'", id),
+            ));
+        }
+        let suffix = format!("{}_{}", env_suffix(network), env_suffix(&id));
+        let key = |key: &str| format!("{}_{}", key, suffix);
+
+        assets.push(Asset {
+            symbol: source.or(&key("TOKEN_SYMBOL"), id.to_uppercase())?,
+            kind: AssetKind::Erc20 {
+                token: source.required(&key("TOKEN_ADDRESS"))?,
+                amount: source.required(&key("TOKEN_AMOUNT"))?,
+            },
+            cooldown_sec: source.optional(&key("TOKEN_COOLDOWN_SEC"))?,
+            id,
+        });
+    }
+    Ok(assets)
+}
+
+// COMMUNITIES lists the communities with their own terms, e.g. COMMUNITIES=eth,dao.
+// every community is configured with keys suffixed by its id, all optional:
+// COMMUNITY_MULTIPLIER (default 1), COMMUNITY_DAILY_QUOTA (claims, 0 is unlimited),
+// COMMUNITY_COOLDOWN_SEC, and COMMUNITY_ALLOW / COMMUNITY_DENY name lists
+fn community_policies(source: &ConfigSource) -> Result<Vec<CommunityPolicy>, ConfigError> {
+    let mut communities: Vec<CommunityPolicy> = Vec::new();
+    for id in source.list("COMMUNITIES") {
+        if !is_valid_id(&id) || communities.iter().any(|c| c.id == id) {
+            return Err(ConfigError::new(
+                "COMMUNITIES",
+                format!("invalid or duplicate community id '{}'", id),
+            ));
+        }
+        let key = |key: &str| format!("{}_{}", key, env_suffix(&id));
+        let multiplier = source.or(&key("COMMUNITY_MULTIPLIER"), 1.0)?;
+        check(
+            &key("COMMUNITY_MULTIPLIER"),
+            multiplier >= 0.0,
+            "should not be negative",
+        )?;
+        communities.push(CommunityPolicy {
+            multiplier,
+            daily_quota: source.or(&key("COMMUNITY_DAILY_QUOTA"), 0)?,
+            cooldown_sec: source.optional(&key("COMMUNITY_COOLDOWN_SEC"))?,
+            allow: source.list(&key("COMMUNITY_ALLOW")),
+            deny: source.list(&key("COMMUNITY_DENY")),
+            id,
+        });
+    }
+    Ok(communities)
+}
+
+// CLAIM_SOURCES lists what claims may be sent to: 0xname (default), ens and address, each
+// with a <SOURCE>_MULTIPLIER on its native payouts
+fn source_policies(source: &ConfigSource) -> Result<Vec<SourcePolicy>, ConfigError> {
+    let mut names = source.list("CLAIM_SOURCES");
+    if names.is_empty() {
+        names.push(ClaimSource::ZeroxName.as_str().to_string());
+    }
+    let mut policies: Vec<SourcePolicy> = Vec::new();
+    for name in names {
+        let claim_source = ClaimSource::ALL
+            .into_iter()
+            .find(|s| s.as_str() == name)
+            .filter(|s| policies.iter().all(|p| p.source != *s))
+            .ok_or_else(|| {
+                ConfigError::new(
+                    "CLAIM_SOURCES",
+                    format!(
+                        "unknown or duplicate source '{}', use 0xname, ens or address",
+                        name
+                    ),
+                )
+            })?;
+        let key = match claim_source {
+            ClaimSource::ZeroxName => "ZEROXNAME_MULTIPLIER",
+            ClaimSource::Ens => "ENS_MULTIPLIER",
+            ClaimSource::Address => "ADDRESS_MULTIPLIER",
+        };
+        let multiplier = source.or(key, 1.0)?;
+        check(key, multiplier >= 0.0, "should not be negative")?;
+        policies.push(SourcePolicy {
+            source: claim_source,
+            multiplier,
+        });
+    }
+    Ok(policies)
+}
+
+// RATE_LIMIT_<FN>_PER_MIN and _BURST per client IP, a per-minute rate of 0 disables the limit
+fn rate_limits(source: &ConfigSource) -> Result<RateLimitConfig, ConfigError> {
+    let limit = |name: &str, per_min: u64, burst: u64| {
+        let per_min = source.or(&format!("RATE_LIMIT_{}_PER_MIN", name), per_min)?;
+        let burst_key = format!("RATE_LIMIT_{}_BURST", name);
+        let burst = source.or(&burst_key, burst)?;
+        check(&burst_key, burst > 0, "should be above 0")?;
+        Ok::<_, ConfigError>((per_min > 0).then_some(RateLimit { per_min, burst }))
+    };
+    let trusted_proxies = source
+        .list("TRUSTED_PROXIES")
+        .iter()
+        .map(|proxy| {
+            IpAddr::parse_value(proxy).ok_or_else(|| {
+                ConfigError::new(
+                    "TRUSTED_PROXIES",
+                    format!("should list IP addresses, got '{}'", proxy),
+                )
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(RateLimitConfig {
+        claim: limit("CLAIM", 6, 3)?,
+        payout: limit("PAYOUT", 60, 30)?,
+        logs: limit("LOGS", 20, 10)?,
+        // each status request may wait a minute on the receipt
+        status: limit("STATUS", 6, 3)?,
+        challenge: limit("CHALLENGE", 12, 6)?,
+        // slows down guessing ADMIN_TOKEN
+        admin: limit("ADMIN", 10, 5)?,
+        trusted_proxies,
+    })
+}
+
+// POW_DIFFICULTY in leading zero bits, 0 disables proof of work. without POW_SECRET challenges
+// are signed with a random key and don't outlive a restart
+fn pow_config(source: &ConfigSource) -> Result<Option<PowConfig>, ConfigError> {
+    let difficulty: u32 = source.or("POW_DIFFICULTY", 0)?;
+    if difficulty == 0 {
+        return Ok(None);
+    }
+    // leading zero bits past 64 take more hashes than any client can try
+    check("POW_DIFFICULTY", difficulty <= 64, "should be at most 64")?;
+    let max_difficulty = source.or("POW_MAX_DIFFICULTY", difficulty.saturating_add(8).min(64))?;
+    check(
+        "POW_MAX_DIFFICULTY",
+        (difficulty..=64).contains(&max_difficulty),
+        "should be between POW_DIFFICULTY and 64",
+    )?;
+    let demand_claims = source.or("POW_DEMAND_CLAIMS", 10)?;
+    check("POW_DEMAND_CLAIMS", demand_claims > 0, "should be above 0")?;
+    let ttl_sec = source.or("POW_TTL_SEC", 300)?;
+    check("POW_TTL_SEC", ttl_sec > 0, "should be above 0")?;
+    let secret = match source.optional::<String>("POW_SECRET")? {
+        Some(secret) => {
+            check(
+                "POW_SECRET",
+                secret.len() >= 16,
+                "should be at least 16 characters",
+            )?;
+            secret.into_bytes()
+        }
+        None => random_bytes(32)
+            .map_err(|e| ConfigError::new("POW_SECRET", format!("random key error: {}", e)))?,
+    };
+    Ok(Some(PowConfig {
+        difficulty,
+        max_difficulty,
+        demand_claims,
+        ttl_sec,
+        secret,
+    }))
+}
+
+// CAPTCHA_PROVIDER (hcaptcha or turnstile) turns the captcha on, with the site's CAPTCHA_SECRET
+// and CAPTCHA_SITE_KEY. CAPTCHA_VERIFY_URL replaces the provider's siteverify endpoint
+fn captcha_config(source: &ConfigSource) -> Result<Option<CaptchaConfig>, ConfigError> {
+    let Some(provider) = source.optional("CAPTCHA_PROVIDER")? else {
+        return Ok(None);
+    };
+    let verify_url: Option<String> = source.optional("CAPTCHA_VERIFY_URL")?;
+    if let Some(url) = &verify_url {
+        check_url("CAPTCHA_VERIFY_URL", url)?;
+    }
+    Ok(Some(CaptchaConfig {
+        provider,
+        secret: source.required("CAPTCHA_SECRET")?,
+        site_key: source.required("CAPTCHA_SITE_KEY")?,
+        verify_url,
+    }))
+}
+
+// SIGNER picks where the faucet keys come from, every source takes comma separated lists:
+// private-key (default, for dev): PRIVATE_KEY
+// keystore: KEYSTORE_PATH, and KEYSTORE_PASSWORD_FILE, one for all keystores or one each
+// remote: REMOTE_SIGNER_URL, REMOTE_SIGNER_ADDRESS and REMOTE_SIGNER_API (web3signer or clef)
+fn signer_configs(source: &ConfigSource) -> Result<Vec<SignerConfig>, ConfigError> {
+    let signer: String = source.or("SIGNER", "private-key".to_string())?;
+    let (key, signers) = match signer.as_str() {
+        "private-key" => (
+            "PRIVATE_KEY",
+            source
+                .list("PRIVATE_KEY")
+                .into_iter()
+                .map(SignerConfig::PrivateKey)
+                .collect::<Vec<_>>(),
+        ),
+        "keystore" => {
+            let paths = source.list("KEYSTORE_PATH");
+            let password_files = source.list("KEYSTORE_PASSWORD_FILE");
+            check(
+                "KEYSTORE_PASSWORD_FILE",
+                password_files.len() == 1 || password_files.len() == paths.len(),
+                "should list one file, or one per KEYSTORE_PATH",
+            )?;
+            let signers = paths
+                .into_iter()
+                .enumerate()
+                .map(|(i, path)| SignerConfig::Keystore {
+                    path: PathBuf::from(path),
+                    password_file: PathBuf::from(&password_files[i.min(password_files.len() - 1)]),
+                })
+                .collect();
+            ("KEYSTORE_PATH", signers)
+        }
+        "remote" => {
+            let url: String = source.required("REMOTE_SIGNER_URL")?;
+            check_url("REMOTE_SIGNER_URL", &url)?;
+            let api = source.or("REMOTE_SIGNER_API", RemoteSignerApi::Web3Signer)?;
+            let signers = source
+                .list("REMOTE_SIGNER_ADDRESS")
+                .into_iter()
+                .map(|address| {
+                    Ok(SignerConfig::Remote {
+                        url: url.clone(),
+                        address: Address::parse_value(&address).ok_or_else(|| {
+                            ConfigError::new(
+                                "REMOTE_SIGNER_ADDRESS",
+                                format!("should list addresses, got '{}'", address),
+                            )
+                        })?,
+                        api,
+                    })
+                })
+                .collect::<Result<Vec<_>, ConfigError>>()?;
+            ("REMOTE_SIGNER_ADDRESS", signers)
+        }
+        _ => {
+            return Err(ConfigError::new(
+                "SIGNER",
+                format!(
+                    "should be private-key, keystore or remote, got '{}'",
+                    signer
+                ),
+            ));
+        }
+    };
+    check(key, !signers.is_empty(), "is required")?;
+    Ok(signers)
+}
@@ -0,0 +1,97 @@
+// payout curve: the coefficient a native payout gets on top of its base amount, growing with
+// the time since the last claim. payout is base * (1 + coefficient)
+use crate::state::wei_to_f64;
+use zeroxname_ethereum::U256;
+
+// what the coefficient follows
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum PayoutMode {
+    // time since the last claim, along the curve
+    #[default]
+    SinceLastClaim,
+    // claims in the trailing period: each claim gets an even share of `budget_wei` over the
+    // claims so far plus itself, within the curve's min and max. per network
+    Demand {
+        budget_wei: U256,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum CurveShape {
+    // the coefficient grows by the adjustment every step
+    Linear,
+    // early steps add the most, each step adds `rate` less than the one before (0 < rate < 1),
+    // ends at the linear curve's max
+    Exponential { rate: f64 },
+    // coefficient per elapsed step, times the adjustment. the last entry holds for later steps
+    Steps(Vec<f64>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PayoutCurve {
+    // claims older than this don't count, the curve is at its max
+    pub period_sec: u64,
+    pub step_sec: u64,
+    pub shape: CurveShape,
+    // scales the whole curve, 0 pays the base amount only
+    pub adjustment: f64,
+    pub mode: PayoutMode,
+}
+
+impl Default for PayoutCurve {
+    // one step per hour over a day
+    fn default() -> Self {
+        Self {
+            period_sec: 86400,
+            step_sec: 3600,
+            shape: CurveShape::Linear,
+            adjustment: 1.0,
+            mode: PayoutMode::default(),
+        }
+    }
+}
+
+impl PayoutCurve {
+    pub fn steps(&self) -> u64 {
+        self.period_sec / self.step_sec.max(1)
+    }
+
+    pub fn coefficient(&self, secs_elapsed: u64) -> f64 {
+        let steps = self.steps();
+        let elapsed = (secs_elapsed / self.step_sec.max(1)).min(steps);
+        let coefficient = match &self.shape {
+            CurveShape::Linear => elapsed as f64,
+            CurveShape::Exponential { rate } => {
+                // sum of a geometric series, normalized to `steps` at the end
+                let added = |n: u64| 1.0 - (1.0 - rate).powf(n as f64);
+                match added(steps) {
+                    total if total > 0.0 => steps as f64 * added(elapsed) / total,
+                    _ => 0.0,
+                }
+            }
+            CurveShape::Steps(table) => table
+                .get(elapsed as usize)
+                .or(table.last())
+                .copied()
+                .unwrap_or_default(),
+        };
+        coefficient * self.adjustment
+    }
+
+    // `claims` paid in the trailing period, `base_wei` is the network's faucet limit
+    pub fn demand_coefficient(&self, claims: u64, base_wei: u64, budget_wei: U256) -> f64 {
+        let share = wei_to_f64(budget_wei) / (claims + 1) as f64;
+        // min <= max, config keeps the steps table non-decreasing
+        (share / base_wei.max(1) as f64 - 1.0).clamp(self.min(), self.max())
+    }
+
+    // right after a claim
+    pub fn min(&self) -> f64 {
+        self.coefficient(0)
+    }
+
+    // a full period since the last claim
+    pub fn max(&self) -> f64 {
+        self.coefficient(self.period_sec)
+    }
+}
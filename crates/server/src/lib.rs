@@ -1,5 +1,17 @@
 #[cfg(feature = "server")]
-mod server;
+pub mod admin;
+#[cfg(feature = "server")]
+pub mod captcha;
+#[cfg(feature = "server")]
+pub mod config;
+#[cfg(feature = "server")]
+pub mod curve;
+#[cfg(feature = "server")]
+pub mod pow;
+#[cfg(feature = "server")]
+pub mod ratelimit;
+#[cfg(feature = "server")]
+pub mod server;
 pub mod shared;
 #[cfg(feature = "server")]
 pub mod state;
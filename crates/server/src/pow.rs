@@ -0,0 +1,162 @@
+// proof of work before a claim: the server signs expiring challenges, harder the more claims the
+// network paid in the payout period, and takes every solution once
+use crate::state::{AppState, Network};
+use dioxus::prelude::*;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use shared_types::{PowChallenge, PowSolution, pow_zero_bits};
+use std::fmt;
+
+type HmacSha256 = Hmac<Sha256>;
+
+#[derive(Clone)]
+pub struct PowConfig {
+    // asked for with no claims in the payout period
+    pub difficulty: u32,
+    pub max_difficulty: u32,
+    // every doubling of this many claims in the payout period adds a bit
+    pub demand_claims: u64,
+    pub ttl_sec: u64,
+    // signs the challenges
+    pub secret: Vec<u8>,
+}
+
+impl fmt::Debug for PowConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PowConfig")
+            .field("difficulty", &self.difficulty)
+            .field("max_difficulty", &self.max_difficulty)
+            .field("demand_claims", &self.demand_claims)
+            .field("ttl_sec", &self.ttl_sec)
+            .finish_non_exhaustive()
+    }
+}
+
+impl PowConfig {
+    // each extra bit doubles the work, as each doubling of the claims adds one
+    pub fn difficulty(&self, claims: u64) -> u32 {
+        let extra = (claims / self.demand_claims.max(1) + 1).ilog2();
+        (self.difficulty + extra).min(self.max_difficulty)
+    }
+
+    fn sign(&self, payload: &str) -> HmacSha256 {
+        let mut mac = HmacSha256::new_from_slice(&self.secret).expect("any key size");
+        mac.update(payload.as_bytes());
+        mac
+    }
+
+    // "<id>.<expires_at>.<difficulty>.<signature>", ids are random hex
+    pub fn challenge(&self, id: &str, difficulty: u32, now: u64) -> PowChallenge {
+        let expires_at = now + self.ttl_sec;
+        let payload = format!("{}.{}.{}", id, expires_at, difficulty);
+        let signature = to_hex(&self.sign(&payload).finalize().into_bytes());
+        PowChallenge {
+            challenge: format!("{}.{}", payload, signature),
+            difficulty,
+            expires_at,
+        }
+    }
+
+    // the id and expiry of a challenge this server signed, if the solution meets its difficulty
+    pub fn verify(
+        &self,
+        input: &str,
+        solution: &PowSolution,
+        now: u64,
+    ) -> Result<(String, u64), ServerFnError> {
+        let invalid = || ServerFnError::ServerError("Invalid proof of work challenge".into());
+        let (payload, signature) = solution.challenge.rsplit_once('.').ok_or_else(invalid)?;
+        let signature = from_hex(signature).ok_or_else(invalid)?;
+        self.sign(payload)
+            .verify_slice(&signature)
+            .map_err(|_| invalid())?;
+        let mut parts = payload.split('.');
+        let (Some(id), Some(expires_at), Some(difficulty), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(invalid());
+        };
+        let expires_at: u64 = expires_at.parse().map_err(|_| invalid())?;
+        let difficulty: u32 = difficulty.parse().map_err(|_| invalid())?;
+        if now > expires_at {
+            return Err(ServerFnError::ServerError(
+                "The proof of work challenge expired, try again!".into(),
+            ));
+        }
+        if pow_zero_bits(&solution.challenge, input, solution.nonce) < difficulty {
+            return Err(ServerFnError::ServerError(
+                "The proof of work doesn't solve the challenge".into(),
+            ));
+        }
+        Ok((id.to_string(), expires_at))
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+pub fn random_bytes(len: usize) -> Result<Vec<u8>, getrandom::Error> {
+    let mut bytes = vec![0; len];
+    getrandom::getrandom(&mut bytes)?;
+    Ok(bytes)
+}
+
+// None while proof of work is off
+pub fn issue_challenge(
+    app_state: &AppState,
+    network: &Network,
+    now: u64,
+) -> Result<Option<PowChallenge>, ServerFnError> {
+    let Some(pow) = &app_state.pow else {
+        return Ok(None);
+    };
+    let claims = app_state.db.count_claims(
+        &network.config.id,
+        now.saturating_sub(app_state.curve.period_sec),
+        now,
+    )?;
+    let id = random_bytes(16).map_err(|e| {
+        ServerFnError::<std::io::Error>::ServerError(format!("Challenge id error: {}", e))
+    })?;
+    Ok(Some(pow.challenge(
+        &to_hex(&id),
+        pow.difficulty(claims),
+        now,
+    )))
+}
+
+// refuses claims without a solution while proof of work is on, and solutions used before.
+// the solution is taken here, a claim refused later doesn't get it back
+pub fn verify_pow(
+    app_state: &AppState,
+    input: &str,
+    solution: Option<&PowSolution>,
+    now: u64,
+) -> Result<(), ServerFnError> {
+    let Some(pow) = &app_state.pow else {
+        return Ok(());
+    };
+    let Some(solution) = solution else {
+        return Err(ServerFnError::ServerError(
+            "A proof of work is required to claim".into(),
+        ));
+    };
+    let (id, expires_at) = pow.verify(input, solution, now)?;
+    match app_state.db.use_challenge(&id, expires_at, now)? {
+        true => Ok(()),
+        false => Err(ServerFnError::ServerError(
+            "The proof of work was already used".into(),
+        )),
+    }
+}
@@ -0,0 +1,184 @@
+// token buckets per client IP in front of the server functions that reach the chain or the db,
+// so one client can't hammer name resolution and fee checks, or guess the admin token
+use crate::server::now_timestamp;
+use axum::extract::{ConnectInfo, Request, State};
+use axum::http::StatusCode;
+use axum::http::header::RETRY_AFTER;
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use dioxus::prelude::server_fn::error::ServerFnErrorSerde;
+use dioxus::prelude::*;
+use shared_types::RateLimited;
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex};
+
+// past this many tracked clients the full buckets are dropped
+const PRUNE_AT: usize = 10_000;
+
+// the limited server functions, by their fixed endpoints in shared.rs
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Limited {
+    Claim,
+    Payout,
+    Logs,
+    Status,
+    Challenge,
+    // every admin function shares one bucket
+    Admin,
+}
+
+impl Limited {
+    pub fn from_path(path: &str) -> Option<Self> {
+        match path {
+            "/api/claim" => Some(Self::Claim),
+            "/api/payout" => Some(Self::Payout),
+            "/api/logs" => Some(Self::Logs),
+            "/api/claim_status" => Some(Self::Status),
+            "/api/challenge" => Some(Self::Challenge),
+            _ if path.starts_with("/api/admin/") => Some(Self::Admin),
+            _ => None,
+        }
+    }
+}
+
+// a client may send `burst` requests at once, then `per_min` a minute
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RateLimit {
+    pub per_min: u64,
+    pub burst: u64,
+}
+
+// None leaves a function unlimited
+#[derive(Clone, Debug, Default)]
+pub struct RateLimitConfig {
+    pub claim: Option<RateLimit>,
+    pub payout: Option<RateLimit>,
+    pub logs: Option<RateLimit>,
+    pub status: Option<RateLimit>,
+    pub challenge: Option<RateLimit>,
+    pub admin: Option<RateLimit>,
+    // X-Forwarded-For is only read from these peers, the faucet's own reverse proxies
+    pub trusted_proxies: Vec<IpAddr>,
+}
+
+impl RateLimitConfig {
+    fn limit(&self, limited: Limited) -> Option<RateLimit> {
+        match limited {
+            Limited::Claim => self.claim,
+            Limited::Payout => self.payout,
+            Limited::Logs => self.logs,
+            Limited::Status => self.status,
+            Limited::Challenge => self.challenge,
+            Limited::Admin => self.admin,
+        }
+    }
+}
+
+struct Bucket {
+    tokens: f64,
+    updated: u64,
+}
+
+impl Bucket {
+    fn refill(&mut self, limit: RateLimit, now: u64) {
+        let elapsed = now.saturating_sub(self.updated) as f64;
+        self.tokens = (self.tokens + elapsed * limit.per_min as f64 / 60.0).min(limit.burst as f64);
+        self.updated = now.max(self.updated);
+    }
+}
+
+#[derive(Clone)]
+pub struct RateLimiter {
+    config: Arc<RateLimitConfig>,
+    buckets: Arc<Mutex<HashMap<(Limited, IpAddr), Bucket>>>,
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self {
+            config: Arc::new(config),
+            buckets: Arc::default(),
+        }
+    }
+
+    // the peer itself, or the nearest address in X-Forwarded-For that isn't a trusted proxy
+    pub fn client_ip(&self, peer: IpAddr, forwarded_for: Option<&str>) -> IpAddr {
+        let trusted = |ip: &IpAddr| self.config.trusted_proxies.contains(ip);
+        if !trusted(&peer) {
+            return peer;
+        }
+        // proxies append the address they got the request from, the client is on the left
+        let mut client = peer;
+        for hop in forwarded_for.unwrap_or_default().rsplit(',') {
+            let Ok(ip) = hop.trim().parse::<IpAddr>() else {
+                break;
+            };
+            client = ip;
+            if !trusted(&ip) {
+                break;
+            }
+        }
+        client
+    }
+
+    // takes a token from the client's bucket for the function at `path`, unlimited paths pass
+    pub fn check(&self, path: &str, client: IpAddr, now: u64) -> Result<(), RateLimited> {
+        let Some(limited) = Limited::from_path(path) else {
+            return Ok(());
+        };
+        let Some(limit) = self.config.limit(limited) else {
+            return Ok(());
+        };
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        if buckets.len() >= PRUNE_AT {
+            buckets.retain(|(limited, _), bucket| match self.config.limit(*limited) {
+                Some(limit) => {
+                    bucket.refill(limit, now);
+                    bucket.tokens < limit.burst as f64
+                }
+                None => false,
+            });
+        }
+        let bucket = buckets.entry((limited, client)).or_insert(Bucket {
+            tokens: limit.burst as f64,
+            updated: now,
+        });
+        bucket.refill(limit, now);
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            return Ok(());
+        }
+        let missing = 1.0 - bucket.tokens;
+        Err(RateLimited {
+            retry_after_sec: (missing * 60.0 / limit.per_min.max(1) as f64).ceil() as u64,
+        })
+    }
+}
+
+// axum middleware, the server has to be served with connect info for the peer address
+pub async fn rate_limit(
+    State(limiter): State<RateLimiter>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let forwarded_for = request
+        .headers()
+        .get("x-forwarded-for")
+        .and_then(|value| value.to_str().ok());
+    let client = limiter.client_ip(peer.ip(), forwarded_for);
+    let now = now_timestamp().unwrap_or_default();
+    match limiter.check(request.uri().path(), client, now) {
+        Ok(()) => next.run(request).await,
+        // the limited functions' custom error, the client gets it back typed
+        Err(limited) => (
+            StatusCode::TOO_MANY_REQUESTS,
+            [(RETRY_AFTER, limited.retry_after_sec.to_string())],
+            ServerFnError::WrappedServerError(limited)
+                .ser()
+                .unwrap_or_default(),
+        )
+            .into_response(),
+    }
+}
@@ -1,44 +1,653 @@
-use crate::state;
+use crate::admin::check_access;
+use crate::captcha::verify_captcha;
+use crate::curve::PayoutMode;
+use crate::pow::{issue_challenge, verify_pow};
+use crate::state::{AppState, Asset, AssetKind, CommunityPolicy, CooldownIdentity, Network};
+use db::{BudgetPeriod, ClaimReservation, ReserveOutcome, SpendOutcome};
 use dioxus::prelude::*;
-use shared_types::{LogEntry, LogValue, PayoutRange};
-use std::time::{SystemTime, SystemTimeError};
-use zeroxname_ethereum::Address;
-use zeroxname_ethereum::U256;
+use shared_types::{
+    AssetInfo, BudgetRemaining, CaptchaWidget, ClaimProof, ClaimResult, ClaimTarget,
+    CommunityTerms, LogEntry, LogKey, LogValue, NetworkInfo, PayoutRange, PowChallenge, Prevention,
+    QueuedPayout, TxReplacement, TxStatus,
+};
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime, SystemTimeError};
 use zeroxname_ethereum::format_units;
+use zeroxname_ethereum::hash;
+use zeroxname_ethereum::{Address, EthErrors, SentTx, U256};
 
-//replace to config vars
-const PERIOD_SEC: u64 = 86400; // 86400 secs = 24 hours
-const STEP_SEC: u64 = 3600; //3600 secs = 1 hour
-const STEPS_AMOUNT: u64 = PERIOD_SEC / STEP_SEC; //24
+// how long one watcher check waits for a receipt
+const RECEIPT_POLL: Duration = Duration::from_secs(2);
+const RECEIPT_WATCH_INTERVAL: Duration = Duration::from_secs(15);
+// how long claim_status holds the claimer's request before answering Pending
+const CLAIM_RECEIPT_WAIT: Duration = Duration::from_secs(60);
+const BATCH_FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+// how long a queued claim waits for its batch past the batch interval
+const BATCH_WAIT_GRACE_SEC: u64 = 30;
+// failed sends of a batch before its claims are marked failed
+const BATCH_MAX_ATTEMPTS: u32 = 5;
 
-pub async fn resolve_name(input: String) -> Result<Address, ServerFnError> {
-    let FromContext(app_state): dioxus::prelude::FromContext<state::AppState> = extract().await?;
-    let (name, community) = input.split_once("@").unwrap_or(("", ""));
+// a payout sent right away, or queued for the next batch under its log entry
+enum Payout {
+    Sent(SentTx),
+    Queued(LogKey),
+}
+
+// why a claim wasn't paid. `prevented` when the faucet's rules refused it, it's logged as such
+#[derive(Debug)]
+pub struct ClaimError {
+    pub error: ServerFnError,
+    pub prevented: Option<Prevention>,
+}
+
+// anything that converts to a ServerFnError is a plain failure
+impl<E> From<E> for ClaimError
+where
+    ServerFnError: From<E>,
+{
+    fn from(error: E) -> Self {
+        Self {
+            error: error.into(),
+            prevented: None,
+        }
+    }
+}
+
+fn failed(message: String) -> ClaimError {
+    ClaimError {
+        error: ServerFnError::ServerError(message),
+        prevented: None,
+    }
+}
+
+pub fn prevented(prevention: Prevention, message: String) -> ClaimError {
+    ClaimError {
+        error: ServerFnError::ServerError(message),
+        prevented: Some(prevention),
+    }
+}
+
+// whole claim flow, server functions only extract the state and call this
+pub async fn claim(
+    app_state: &AppState,
+    input: String,
+    network: String,
+    asset: String,
+    proof: ClaimProof,
+) -> Result<ClaimResult, ServerFnError> {
+    let network = get_network(app_state, &network)?;
+    let asset = get_asset(network, &asset)?;
+    let time = now_timestamp()?;
+    // the same parser as the claim form's
+    let target = match input.parse::<ClaimTarget>() {
+        Ok(target) => target,
+        Err(e) => return Err(ServerFnError::ServerError(e.to_string())),
+    };
+    let Some(source) = app_state.source(target.source()) else {
+        return Err(ServerFnError::ServerError(format!(
+            "This faucet doesn't pay out to {}, use {}",
+            target.source().label(),
+            app_state
+                .sources
+                .iter()
+                .map(|p| p.source.label())
+                .collect::<Vec<_>>()
+                .join(" or ")
+        )));
+    };
+    // before anything reaches the chain
+    verify_pow(app_state, &input, proof.pow.as_ref(), time)?;
+    verify_captcha(app_state, proof.captcha.as_deref()).await?;
+    let fees_ok = is_network_fees_ok(network, asset).await?;
+    let balance = faucet_balance(network).await?;
+    let batched = is_batched(app_state, network, asset);
+    let (name, community) = target.name_and_community();
+    let policy = app_state.community(community);
+    let result = async {
+        // tokens need gas too, nothing is paid out below the floor
+        if app_state.funds.is_paused(balance) {
+            return Err(prevented(
+                Prevention::Paused,
+                format!(
+                    "Faucet is running dry, {} claims are paused until it's refilled!",
+                    network.config.name
+                ),
+            ));
+        }
+        if let Some(policy) = policy
+            && !policy.admits(name)
+        {
+            return Err(prevented(
+                Prevention::Community,
+                format!("{} is not allowed to claim from this faucet!", input),
+            ));
+        }
+        check_access(app_state, &input, None)?;
+        let addr = resolve_name(app_state, &target).await?;
+        check_access(app_state, &input, Some(&addr))?;
+        check_name_record(app_state, &input, &target, addr, time).await?;
+        check_eligibility(app_state, &input, addr).await?;
+        let reservation = db_reserve_claim(app_state, network, asset, &addr, &target, time)?;
+        if let Err(e) = db_reserve_quota(app_state, network, policy, time) {
+            db_release_claim(app_state, reservation).ok();
+            return Err(e.into());
+        }
+        let paid = async {
+            if !fees_ok {
+                return Err(ServerFnError::ServerError(
+                    "Network Fee is too high!".into(),
+                ));
+            }
+            let coefficient = match &asset.kind {
+                AssetKind::Native => calculate_current_coefficient(app_state, network, balance)?,
+                AssetKind::Erc20 { token, amount } => {
+                    return send_token(network, asset, *token, *amount, addr)
+                        .await
+                        .map(Payout::Sent);
+                }
+            };
+            // the community's and source's multipliers apply to the whole payout
+            let multiplier = policy.map_or(1.0, |p| p.multiplier) * source.multiplier;
+            let coefficient = (1.0 + coefficient) * multiplier - 1.0;
+            // counted against the budget before it's sent, given back if it isn't
+            let amount = network.sender.get_claim_amount(coefficient);
+            db_reserve_spend(app_state, network, time, amount)?;
+            let paid = match batched {
+                true => queue_eth(
+                    app_state,
+                    network,
+                    addr,
+                    &input,
+                    amount,
+                    &reservation,
+                    policy,
+                )
+                .map(Payout::Queued),
+                false => send_eth(network, addr, coefficient).await.map(Payout::Sent),
+            };
+            if paid.is_err() {
+                db_release_spend(app_state, network, time, amount).ok();
+            }
+            paid
+        }
+        .await;
+        if paid.is_err() {
+            db_release_quota(app_state, network, policy, time).ok();
+            db_release_claim(app_state, reservation).ok();
+        }
+        Ok(paid?)
+    }
+    .await;
+    let (tx_hash, log_key) = match result {
+        // logged when queued, the batch fills in the hash
+        Ok(Payout::Queued(log_key)) => (
+            wait_for_batch(app_state, network, log_key)
+                .await?
+                .unwrap_or_default(),
+            Some(log_key),
+        ),
+        Ok(Payout::Sent(sent)) => {
+            let log_key = insert_log(app_state, network, asset, time, input, Ok(&sent)).ok();
+            (sent.tx_hash, log_key)
+        }
+        Err(e) => {
+            insert_log(app_state, network, asset, time, input, Err(&e)).ok();
+            return Err(e.error);
+        }
+    };
+    Ok(ClaimResult {
+        tx_hash,
+        log_key,
+        community: policy
+            .map(|policy| community_terms(app_state, network, policy, time))
+            .transpose()?,
+    })
+}
+
+fn community_terms(
+    app_state: &AppState,
+    network: &Network,
+    policy: &CommunityPolicy,
+    timestamp: u64,
+) -> Result<CommunityTerms, ServerFnError> {
+    let claims_left_today = match policy.daily_quota {
+        0 => None,
+        quota => {
+            let used = app_state
+                .db
+                .quota_used(&network.config.id, &policy.quota(), timestamp)?;
+            Some(quota.saturating_sub(used))
+        }
+    };
+    Ok(CommunityTerms {
+        id: policy.id.clone(),
+        multiplier: policy.multiplier,
+        claims_left_today,
+    })
+}
 
+// native payouts are batched when batching is on and the network has a disperse contract
+pub fn is_batched(app_state: &AppState, network: &Network, asset: &Asset) -> bool {
+    app_state.batch.is_some()
+        && network.config.disperse_address.is_some()
+        && asset.kind == AssetKind::Native
+}
+
+// the claim is logged before it's queued, a flush right after must find the log entry.
+// returns the key it was logged under
+pub fn queue_eth(
+    app_state: &AppState,
+    network: &Network,
+    addr: Address,
+    input: &str,
+    amount: U256,
+    // taken at the claim's time, released with the spend if the batch is never sent
+    reservation: &ClaimReservation,
+    policy: Option<&CommunityPolicy>,
+) -> Result<LogKey, ServerFnError> {
+    let id = &network.config.id;
+    let timestamp = reservation.timestamp;
+    let log_key = app_state.db.insert_k_v_logs(
+        id,
+        timestamp,
+        true,
+        LogValue {
+            input: input.to_string(),
+            result: String::new(),
+            asset: String::new(),
+            tx_status: Some(TxStatus::Queued),
+            replaced: Vec::new(),
+            wallet: String::new(),
+            prevented: None,
+        },
+    )?;
+    let queued = app_state.db.enqueue_payout(
+        id,
+        &QueuedPayout {
+            receiver: addr.to_string(),
+            amount: amount.to_string(),
+            log_key,
+            queued_at: timestamp,
+            claim_slots: reservation.slots.clone(),
+            quota: policy.filter(|p| p.daily_quota > 0).map(|p| p.quota()),
+            attempts: 0,
+        },
+    );
+    if let Err(e) = queued {
+        app_state
+            .db
+            .set_log_tx_status(id, log_key, TxStatus::Failed)
+            .ok();
+        return Err(ServerFnError::ServerError(format!(
+            "Queue claim in DB error: {}",
+            e
+        )));
+    }
+    Ok(log_key)
+}
+
+// holds the claimer until the batch with their payout is sent, returns the batch tx hash.
+// None if it's still queued by then, the claim stays queued and is paid with a later batch
+async fn wait_for_batch(
+    app_state: &AppState,
+    network: &Network,
+    log_key: LogKey,
+) -> Result<Option<String>, ServerFnError> {
+    let interval_sec = app_state.batch.as_ref().map_or(0, |b| b.interval_sec);
+    let deadline = log_key.timestamp + interval_sec + BATCH_WAIT_GRACE_SEC;
+    while now_timestamp()? <= deadline {
+        if let Some(value) = app_state.db.get_value_log(&network.config.id, log_key)? {
+            match value.tx_status {
+                Some(TxStatus::Queued) => {}
+                Some(TxStatus::Failed) => {
+                    return Err(ServerFnError::ServerError(
+                        "The batch with your payout couldn't be sent, try again later!".into(),
+                    ));
+                }
+                _ => return Ok(Some(value.result)),
+            }
+        }
+        tokio::time::sleep(BATCH_FLUSH_INTERVAL).await;
+    }
+    Ok(None)
+}
+
+// sends one batch from a network's queue once it's due: max_claims are queued or the oldest
+// claim waited interval_sec. None when nothing was sent
+pub async fn flush_batch(
+    app_state: &AppState,
+    network: &Network,
+    now: u64,
+) -> Result<Option<String>, ServerFnError> {
+    let Some(batch) = &app_state.batch else {
+        return Ok(None);
+    };
+    let id = &network.config.id;
+    let queued = app_state.db.queued_payouts(id, batch.max_claims)?;
+    let Some((_, oldest)) = queued.first() else {
+        return Ok(None);
+    };
+    if queued.len() < batch.max_claims && now.saturating_sub(oldest.queued_at) < batch.interval_sec
+    {
+        return Ok(None);
+    }
+
+    let sent = async {
+        let payouts = queued
+            .iter()
+            .map(|(_, payout)| -> Result<(Address, U256), ServerFnError> {
+                Ok((payout.receiver.parse()?, payout.amount.parse()?))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        send_batch(network, &payouts).await
+    }
+    .await;
+    let sent = match sent {
+        Ok(sent) => sent,
+        Err(e) => {
+            retry_batch(app_state, network, &queued, &e)?;
+            return Err(e);
+        }
+    };
+
+    // off the queue first, a failure below must not pay the batch twice
+    let keys: Vec<u64> = queued.iter().map(|(key, _)| *key).collect();
+    app_state.db.remove_queued(id, &keys)?;
+    for (_, payout) in &queued {
+        app_state.db.update_log(id, payout.log_key, |value| {
+            value.result = sent.tx_hash.clone();
+            value.tx_status = Some(TxStatus::Pending);
+            value.wallet = sent.wallet.to_string();
+        })?;
+    }
+    Ok(Some(sent.tx_hash))
+}
+
+pub async fn send_batch(
+    network: &Network,
+    payouts: &[(Address, U256)],
+) -> Result<SentTx, ServerFnError> {
+    network.sender.send_batch(payouts).await.map_err(|e| {
+        ServerFnError::ServerError(format!(
+            "Unable to send {} batch: {}",
+            network.config.name, e
+        ))
+    })
+}
+
+// counts a failed send against every claim of the batch. claims out of attempts leave the
+// queue as failed and give back their reservations, like a single payout that failed
+fn retry_batch(
+    app_state: &AppState,
+    network: &Network,
+    queued: &[(u64, QueuedPayout)],
+    error: &ServerFnError,
+) -> Result<(), ServerFnError> {
+    let id = &network.config.id;
+    for (key, payout) in queued {
+        let attempts = payout.attempts + 1;
+        if attempts < BATCH_MAX_ATTEMPTS {
+            app_state.db.requeue_payout(
+                id,
+                *key,
+                &QueuedPayout {
+                    attempts,
+                    ..payout.clone()
+                },
+            )?;
+            continue;
+        }
+        app_state.db.remove_queued(id, &[*key])?;
+        app_state.db.update_log(id, payout.log_key, |value| {
+            value.result = error.to_string();
+            value.tx_status = Some(TxStatus::Failed);
+        })?;
+        if let Ok(amount) = payout.amount.parse() {
+            db_release_spend(app_state, network, payout.queued_at, amount).ok();
+        }
+        if let Some(quota) = &payout.quota {
+            app_state.db.release_quota(id, quota, payout.queued_at).ok();
+        }
+        db_release_claim(
+            app_state,
+            ClaimReservation {
+                network: id.clone(),
+                timestamp: payout.queued_at,
+                slots: payout.claim_slots.clone(),
+            },
+        )
+        .ok();
+    }
+    Ok(())
+}
+
+pub async fn flush_batches(app_state: AppState) {
+    loop {
+        if let Ok(now) = now_timestamp() {
+            for network in &app_state.networks {
+                flush_batch(&app_state, network, now).await.ok();
+            }
+        }
+        tokio::time::sleep(BATCH_FLUSH_INTERVAL).await;
+    }
+}
+
+// raw addresses are taken as they are
+pub async fn resolve_name(
+    app_state: &AppState,
+    target: &ClaimTarget,
+) -> Result<Address, ClaimError> {
+    let resolved = match target {
+        ClaimTarget::ZeroxName { name, community } => {
+            app_state.resolver.resolve_address(name, community).await
+        }
+        ClaimTarget::Ens(name) => app_state.resolver.resolve_ens(name).await,
+        ClaimTarget::Address(address) => return Ok(Address::from(*address)),
+    };
+    resolved.map_err(|e| {
+        let message = format!("Unable to resolve the name: {}", e);
+        match e {
+            EthErrors::NameNotFound => prevented(Prevention::Unresolved, message),
+            _ => failed(message),
+        }
+    })
+}
+
+// refuses expired 0xNAMEs, and names that aren't the primary name of the address they resolve to
+pub async fn check_name_record(
+    app_state: &AppState,
+    input: &str,
+    target: &ClaimTarget,
+    addr: Address,
+    now: u64,
+) -> Result<(), ClaimError> {
+    let checks = app_state.name_checks;
+    let ClaimTarget::ZeroxName { name, community } = target else {
+        return Ok(());
+    };
+    if !checks.is_enabled() {
+        return Ok(());
+    }
+    let record = match app_state.resolver.name_record(name, community).await {
+        Ok(record) => record,
+        Err(e) => {
+            return Err(failed(format!("Unable to check the name: {}", e)));
+        }
+    };
+    if checks.unexpired && record.is_expired(now) {
+        return Err(prevented(
+            Prevention::Expired,
+            format!("{} expired, renew it to claim!", input),
+        ));
+    }
+    if !checks.primary {
+        return Ok(());
+    }
+    let primary = match app_state.resolver.primary_token_id(addr).await {
+        Ok(primary) => primary,
+        Err(e) => {
+            return Err(failed(format!("Unable to check the primary name: {}", e)));
+        }
+    };
+    // a reverse record left behind after a transfer doesn't count
+    if record.owner != addr || primary != Some(hash(name, community)?) {
+        return Err(prevented(
+            Prevention::NotPrimary,
+            format!(
+                "{} is not the primary name of {:#x}, set it as primary name to claim!",
+                input, addr
+            ),
+        ));
+    }
+    Ok(())
+}
+
+// refuses recipients without the mainnet balance or nonce the faucet asks for
+pub async fn check_eligibility(
+    app_state: &AppState,
+    input: &str,
+    addr: Address,
+) -> Result<(), ClaimError> {
+    let eligibility = &app_state.eligibility;
+    if !eligibility.is_enabled() {
+        return Ok(());
+    }
+    let activity = match app_state.resolver.mainnet_activity(addr).await {
+        Ok(activity) => activity,
+        Err(e) => {
+            return Err(failed(format!("Unable to check mainnet activity: {}", e)));
+        }
+    };
+    if eligibility.admits(&activity) {
+        return Ok(());
+    }
+    let transactions = |n: u64| match n {
+        1 => "1 transaction".to_string(),
+        n => format!("{} transactions", n),
+    };
+    let mut needs = Vec::new();
+    if !eligibility.min_balance_wei.is_zero() {
+        needs.push(format!("{} ETH", format_eth(eligibility.min_balance_wei)?));
+    }
+    if eligibility.min_nonce > 0 {
+        needs.push(transactions(eligibility.min_nonce));
+    }
+    Err(prevented(
+        Prevention::Ineligible,
+        format!(
+            "{} is not eligible: {:#x} needs at least {} on mainnet, it has {} ETH and {}",
+            input,
+            addr,
+            needs.join(" or "),
+            format_eth(activity.balance)?,
+            transactions(activity.nonce),
+        ),
+    ))
+}
+
+// ETH without trailing zeros
+fn format_eth(wei: U256) -> Result<String, ServerFnError> {
+    format_units(wei, "ether")
+        .map(|eth| eth.trim_end_matches('0').trim_end_matches('.').to_string())
+        .map_err(|e| ServerFnError::ServerError(format!("Failed to format units: {}", e)))
+}
+
+// empty id is the default network
+pub fn get_network<'a>(app_state: &'a AppState, id: &str) -> Result<&'a Network, ServerFnError> {
     app_state
-        .zx
-        .resolve_address(name, community)
-        .await
-        .map_err(|e| ServerFnError::ServerError(format!("Unable to resolve the name: {}", e)))
+        .network(id)
+        .ok_or_else(|| ServerFnError::ServerError(format!("Unknown network: {}", id)))
 }
 
-pub async fn is_network_fees_ok() -> Result<bool, ServerFnError> {
-    let FromContext(app_state): dioxus::prelude::FromContext<state::AppState> = extract().await?;
+// empty id is the native coin
+pub fn get_asset<'a>(network: &'a Network, id: &str) -> Result<&'a Asset, ServerFnError> {
+    network.asset(id).ok_or_else(|| {
+        ServerFnError::ServerError(format!("Unknown asset {} on {}", id, network.config.name))
+    })
+}
+
+pub fn get_networks(app_state: &AppState) -> Vec<NetworkInfo> {
     app_state
-        .zx
-        .is_network_fee_ok()
+        .networks
+        .iter()
+        .map(|n| NetworkInfo {
+            id: n.config.id.clone(),
+            name: n.config.name.clone(),
+            explorer_url: n.config.explorer_url.clone(),
+            assets: n
+                .assets
+                .iter()
+                .map(|a| AssetInfo {
+                    id: a.id.clone(),
+                    symbol: a.symbol.clone(),
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+pub async fn is_network_fees_ok(network: &Network, asset: &Asset) -> Result<bool, ServerFnError> {
+    match asset.kind {
+        AssetKind::Native => network.sender.is_network_fee_ok().await,
+        AssetKind::Erc20 { .. } => network.sender.is_token_fee_ok().await,
+    }
+    .map_err(|e| ServerFnError::ServerError(format!("Unable to check Network Fees: {}", e)))
+}
+
+// faucet wallet native balance
+pub async fn faucet_balance(network: &Network) -> Result<U256, ServerFnError> {
+    network.sender.balance().await.map_err(|e| {
+        ServerFnError::ServerError(format!(
+            "Unable to check {} faucet balance: {}",
+            network.config.name, e
+        ))
+    })
+}
+
+pub async fn send_eth(
+    network: &Network,
+    addr: Address,
+    coefficient: f64,
+) -> Result<SentTx, ServerFnError> {
+    network
+        .sender
+        .send_eth(addr, coefficient)
         .await
-        .map_err(|e| ServerFnError::ServerError(format!("Unable to check Network Fees: {}", e)))
+        .map_err(|e| {
+            ServerFnError::ServerError(format!("Unable to send {} ETH: {}", network.config.name, e))
+        })
 }
 
-pub async fn send_sepolia_eth(addr: Address) -> Result<String, ServerFnError> {
-    let FromContext(app_state): dioxus::prelude::FromContext<state::AppState> = extract().await?;
-    let coefficient = calculate_current_coefficient().await?;
-    app_state
-        .zx
-        .send_sepolia_eth(addr, coefficient)
+// fixed amount, refused up front when the faucet can't cover it
+pub async fn send_token(
+    network: &Network,
+    asset: &Asset,
+    token: Address,
+    amount: U256,
+    addr: Address,
+) -> Result<SentTx, ServerFnError> {
+    let send_error = |e: String| {
+        ServerFnError::ServerError(format!(
+            "Unable to send {} {}: {}",
+            network.config.name, asset.symbol, e
+        ))
+    };
+    let balance = network
+        .sender
+        .token_balance(token)
+        .await
+        .map_err(|e| send_error(e.to_string()))?;
+    if balance < amount {
+        return Err(ServerFnError::ServerError(format!(
+            "Faucet is out of {} {}!",
+            network.config.name, asset.symbol
+        )));
+    }
+    network
+        .sender
+        .send_token(token, addr, amount)
         .await
-        .map_err(|e| ServerFnError::ServerError(format!("Unable to send Sepolia ETH: {}", e)))
+        .map_err(|e| send_error(e.to_string()))
 }
 
 pub fn now_timestamp() -> Result<u64, SystemTimeError> {
@@ -48,93 +657,333 @@ pub fn now_timestamp() -> Result<u64, SystemTimeError> {
     Ok(now)
 }
 
-pub async fn is_in_cooldown(last_record: u64) -> Result<bool, ServerFnError> {
-    let FromContext(app_state): dioxus::prelude::FromContext<state::AppState> = extract().await?;
-    let cur_time = now_timestamp().map_err(|_| {
-        ServerFnError::<std::io::Error>::ServerError("Failed to get current timestamp".to_string())
-    })?;
-    let is_cooldown = (cur_time - last_record) < app_state.cooldown_sec;
-    Ok(is_cooldown)
+// None where the identity doesn't apply to the source, raw addresses only have the address.
+// ENS names count as the name's identity
+fn cooldown_key(
+    identity: CooldownIdentity,
+    addr: &Address,
+    target: &ClaimTarget,
+) -> Result<Option<String>, ServerFnError> {
+    match (identity, target) {
+        (CooldownIdentity::Address, _) => Ok(Some(addr.to_string())),
+        (CooldownIdentity::TokenId, ClaimTarget::ZeroxName { name, community }) => {
+            Ok(Some(format!("token:{}", hash(name, community)?)))
+        }
+        (CooldownIdentity::TokenId, ClaimTarget::Ens(name)) => Ok(Some(format!("ens:{}", name))),
+        (CooldownIdentity::Community, ClaimTarget::ZeroxName { community, .. }) => {
+            Ok(Some(format!("tln:{}", community.to_ascii_lowercase())))
+        }
+        _ => Ok(None),
+    }
+}
+
+// takes the cooldown slots of every configured identity before anything is sent.
+// tokens keep their own slots, prefixed with the asset id, native ETH keeps the bare keys.
+pub fn db_reserve_claim(
+    app_state: &AppState,
+    network: &Network,
+    asset: &Asset,
+    addr: &Address,
+    target: &ClaimTarget,
+    timestamp: u64,
+) -> Result<ClaimReservation, ClaimError> {
+    let (_, community) = target.name_and_community();
+    let mut identities = Vec::new();
+    let mut keys = Vec::new();
+    for c in &app_state.cooldowns {
+        let Some(key) = cooldown_key(c.identity, addr, target)? else {
+            continue;
+        };
+        let key = match asset.kind {
+            AssetKind::Native => key,
+            AssetKind::Erc20 { .. } => format!("asset:{}:{}", asset.id, key),
+        };
+        let window_sec = asset
+            .cooldown_sec
+            .or(app_state.community(community).and_then(|p| p.cooldown_sec))
+            .unwrap_or(c.window_sec);
+        identities.push(c.identity);
+        keys.push((key, window_sec));
+    }
+
+    match app_state
+        .db
+        .reserve_claim(&network.config.id, &keys, timestamp)
+    {
+        Ok(ReserveOutcome::Reserved(reservation)) => Ok(reservation),
+        Ok(ReserveOutcome::InCooldown { index, .. }) => {
+            let identity = match identities[index] {
+                CooldownIdentity::Address => "address",
+                CooldownIdentity::TokenId => "0xNAME",
+                CooldownIdentity::Community => "community",
+            };
+            Err(prevented(
+                Prevention::Cooldown,
+                format!("Cooldown is not ended for this {}!", identity),
+            ))
+        }
+        Err(e) => Err(failed(format!("Reserve claim in DB error: {}", e))),
+    }
+}
+
+pub fn db_release_claim(
+    app_state: &AppState,
+    reservation: ClaimReservation,
+) -> Result<(), ServerFnError> {
+    app_state
+        .db
+        .release_claim(reservation)
+        .map_err(|e| ServerFnError::ServerError(format!("Release claim in DB error: {}", e)))
 }
 
-pub async fn db_get_last_claim(key: &str) -> Result<u64, ServerFnError> {
-    let FromContext(app_state): dioxus::prelude::FromContext<state::AppState> = extract().await?;
-    match app_state.db.get_value_claim(key) {
-        Ok(Some(value)) => Ok(value),
-        Ok(None) => Ok(0),
+fn budget_amount(amount: U256) -> u128 {
+    u128::try_from(amount).unwrap_or(u128::MAX)
+}
+
+// refuses a native payout that would pass the network's daily or hourly budget
+pub fn db_reserve_spend(
+    app_state: &AppState,
+    network: &Network,
+    timestamp: u64,
+    amount: U256,
+) -> Result<(), ServerFnError> {
+    let limits = app_state.budget.limits();
+    if limits.is_empty() {
+        return Ok(());
+    }
+    match app_state.db.reserve_spend(
+        &network.config.id,
+        &limits,
+        timestamp,
+        budget_amount(amount),
+    ) {
+        Ok(SpendOutcome::Reserved) => Ok(()),
+        Ok(SpendOutcome::OverBudget(period)) => {
+            let (period, retry) = match period {
+                BudgetPeriod::Day => ("daily", "tomorrow"),
+                BudgetPeriod::Hour => ("hourly", "in the next hour"),
+            };
+            Err(ServerFnError::ServerError(format!(
+                "The {} {} budget is spent, try again {}!",
+                network.config.name, period, retry
+            )))
+        }
         Err(e) => Err(ServerFnError::ServerError(format!(
-            "Get claim value from DB error: {}",
+            "Reserve budget in DB error: {}",
             e
         ))),
     }
 }
 
-pub async fn insert_timestamp(key: &str, value: u64) -> Result<(), ServerFnError> {
-    let FromContext(app_state): dioxus::prelude::FromContext<state::AppState> = extract().await?;
-    match app_state.db.insert_k_v_claim(key, value) {
-        Ok(_) => return Ok(()),
+pub fn db_release_spend(
+    app_state: &AppState,
+    network: &Network,
+    timestamp: u64,
+    amount: U256,
+) -> Result<(), ServerFnError> {
+    let periods: Vec<BudgetPeriod> = app_state
+        .budget
+        .limits()
+        .into_iter()
+        .map(|(period, _)| period)
+        .collect();
+    app_state
+        .db
+        .release_spend(
+            &network.config.id,
+            &periods,
+            timestamp,
+            budget_amount(amount),
+        )
+        .map_err(|e| ServerFnError::ServerError(format!("Release budget in DB error: {}", e)))
+}
+
+// refuses a claim once the community's daily quota is used up, no policy or quota passes
+pub fn db_reserve_quota(
+    app_state: &AppState,
+    network: &Network,
+    policy: Option<&CommunityPolicy>,
+    timestamp: u64,
+) -> Result<(), ServerFnError> {
+    let Some(policy) = policy.filter(|p| p.daily_quota > 0) else {
+        return Ok(());
+    };
+    match app_state.db.reserve_quota(
+        &network.config.id,
+        &policy.quota(),
+        timestamp,
+        policy.daily_quota,
+    ) {
+        Ok(true) => Ok(()),
+        Ok(false) => Err(ServerFnError::ServerError(format!(
+            "The {} community used its {} daily claims, try again tomorrow!",
+            policy.id, policy.daily_quota
+        ))),
         Err(e) => Err(ServerFnError::ServerError(format!(
-            "Insert claim value to DB error: {}",
+            "Reserve quota in DB error: {}",
             e
         ))),
     }
 }
 
-async fn calculate_current_coefficient() -> Result<f64, ServerFnError> {
+pub fn db_release_quota(
+    app_state: &AppState,
+    network: &Network,
+    policy: Option<&CommunityPolicy>,
+    timestamp: u64,
+) -> Result<(), ServerFnError> {
+    let Some(policy) = policy.filter(|p| p.daily_quota > 0) else {
+        return Ok(());
+    };
+    app_state
+        .db
+        .release_quota(&network.config.id, &policy.quota(), timestamp)
+        .map_err(|e| ServerFnError::ServerError(format!("Release quota in DB error: {}", e)))
+}
+
+// what's left of the budget at `now`, None for disabled limits
+pub fn get_budget_remaining(
+    app_state: &AppState,
+    network: &str,
+) -> Result<BudgetRemaining, ServerFnError> {
+    let network = get_network(app_state, network)?;
+    let now = now_timestamp()?;
+    let mut remaining = BudgetRemaining::default();
+    for (period, limit) in app_state.budget.limits() {
+        let spent = app_state
+            .db
+            .spent(&network.config.id, period, now)
+            .map_err(|e| {
+                ServerFnError::<std::io::Error>::ServerError(format!(
+                    "Get spent budget from DB error: {}",
+                    e
+                ))
+            })?;
+        let left = format_units(U256::from(limit.saturating_sub(spent)), "ether").map_err(|e| {
+            ServerFnError::<std::io::Error>::ServerError(format!("Failed to format units: {}", e))
+        })?;
+        match period {
+            BudgetPeriod::Day => remaining.today = Some(left),
+            BudgetPeriod::Hour => remaining.this_hour = Some(left),
+        }
+    }
+    Ok(remaining)
+}
+
+// grows with the time since the last claim, or shrinks with the claims of the trailing period.
+// shrinks the payout below the low-water mark
+fn calculate_current_coefficient(
+    app_state: &AppState,
+    network: &Network,
+    balance: U256,
+) -> Result<f64, ServerFnError> {
     let now = now_timestamp().map_err(|_| {
         ServerFnError::<std::io::Error>::ServerError("Failed to get current timestamp".to_string())
     })?;
 
-    let range_low = now - PERIOD_SEC;
-    let FromContext(app_state): dioxus::prelude::FromContext<state::AppState> = extract().await?;
+    let curve = &app_state.curve;
+    let range_low = now.saturating_sub(curve.period_sec);
 
-    match app_state.db.get_last_claim_timestamp(range_low, now) {
-        Ok(last_claim_ts) => {
-            let secs_elapsed = now.saturating_sub(last_claim_ts);
-            let steps_elapsed = (secs_elapsed / STEP_SEC).clamp(0, STEPS_AMOUNT);
-            let coefficient = steps_elapsed as f64 * app_state.payout_adjustment;
-            Ok(coefficient)
+    let coefficient = match curve.mode {
+        PayoutMode::SinceLastClaim => {
+            let last_claim_ts = app_state
+                .db
+                .get_last_claim_timestamp(&network.config.id, range_low, now)
+                .map_err(|e| {
+                    ServerFnError::<std::io::Error>::ServerError(format!(
+                        "Getting time elapsed from last claim: {}",
+                        e
+                    ))
+                })?;
+            curve.coefficient(now.saturating_sub(last_claim_ts))
         }
-        Err(e) => Err(ServerFnError::ServerError(format!(
-            "Getting time elapsed from last claim: {}",
-            e
-        ))),
-    }
+        PayoutMode::Demand { budget_wei } => {
+            let claims = app_state
+                .db
+                .count_claims(&network.config.id, range_low, now)
+                .map_err(|e| {
+                    ServerFnError::<std::io::Error>::ServerError(format!(
+                        "Counting claims in DB error: {}",
+                        e
+                    ))
+                })?;
+            curve.demand_coefficient(claims, network.sender.faucet_limit(), budget_wei)
+        }
+    };
+    // payout is base * (1 + coefficient), scale that whole amount
+    let scale = app_state.funds.payout_scale(balance);
+    Ok((1.0 + coefficient) * scale - 1.0)
 }
 
-pub async fn get_payout_range() -> Result<PayoutRange, ServerFnError> {
-    let FromContext(app_state): dioxus::prelude::FromContext<state::AppState> = extract().await?;
-    let current_coefficient = calculate_current_coefficient().await?;
-    let min_coef = 0.0;
+pub async fn get_payout_range(
+    app_state: &AppState,
+    network: &str,
+) -> Result<PayoutRange, ServerFnError> {
+    let network = get_network(app_state, network)?;
+    let balance = faucet_balance(network).await?;
+    let current_coefficient = calculate_current_coefficient(app_state, network, balance)?;
+    let min_coef = app_state.curve.min();
     let cur_coef = current_coefficient;
-    let max_coef = STEPS_AMOUNT as f64 * app_state.payout_adjustment;
+    let max_coef = app_state.curve.max();
 
-    let format_amount = |coef: f64| -> Result<String, ServerFnError> {
-        let wei = app_state.zx.get_claim_amount(coef);
+    let format_wei = |wei: U256| -> Result<String, ServerFnError> {
         format_units(wei, "ether")
             .map_err(|e| ServerFnError::ServerError(format!("Failed to format units: {}", e)))
     };
+    let format_amount = |coef: f64| format_wei(network.sender.get_claim_amount(coef));
+
+    let paused = app_state.funds.is_paused(balance);
+    let current_amount = network.sender.get_claim_amount(cur_coef);
+    let claims_left = match paused || current_amount.is_zero() {
+        true => 0,
+        false => {
+            let spendable = balance.saturating_sub(app_state.funds.floor_wei);
+            u64::try_from(spendable / current_amount).unwrap_or(u64::MAX)
+        }
+    };
 
     Ok(PayoutRange {
         min: format_amount(min_coef)?,
         current: format_amount(cur_coef)?,
         max: format_amount(max_coef)?,
+        balance: format_wei(balance)?,
+        claims_left,
+        paused,
     })
 }
 
-pub async fn insert_log(
+pub fn insert_log(
+    app_state: &AppState,
+    network: &Network,
+    asset: &Asset,
     timestamp: u64,
-    status: bool,
     input: String,
-    result: String,
-) -> Result<(), ServerFnError> {
-    let FromContext(app_state): dioxus::prelude::FromContext<state::AppState> = extract().await?;
-
+    // the sent payout, or why the claim wasn't paid
+    outcome: Result<&SentTx, &ClaimError>,
+) -> Result<LogKey, ServerFnError> {
+    let status = outcome.is_ok();
+    let (result, wallet, prevented) = match outcome {
+        Ok(sent) => (sent.tx_hash.clone(), sent.wallet.to_string(), None),
+        Err(e) => (e.error.to_string(), String::new(), e.prevented),
+    };
+    let value = LogValue {
+        input,
+        result,
+        asset: match asset.kind {
+            AssetKind::Native => String::new(),
+            AssetKind::Erc20 { .. } => asset.id.clone(),
+        },
+        // sent payouts are pending until the watcher sees the receipt
+        tx_status: status.then_some(TxStatus::Pending),
+        replaced: Vec::new(),
+        wallet,
+        prevented,
+    };
     match app_state
         .db
-        .insert_k_v_logs(timestamp, status, input, result)
+        .insert_k_v_logs(&network.config.id, timestamp, status, value)
     {
-        Ok(_) => return Ok(()),
+        Ok(key) => Ok(key),
         Err(e) => Err(ServerFnError::ServerError(format!(
             "Insert log to DB error: {}",
             e
@@ -142,8 +991,201 @@ pub async fn insert_log(
     }
 }
 
-pub async fn get_logs() -> Result<Vec<LogEntry>, ServerFnError> {
-    let FromContext(app_state): dioxus::prelude::FromContext<state::AppState> = extract().await?;
-    let logs: Vec<LogEntry> = app_state.db.iter_logs().collect::<Result<Vec<_>, _>>()?;
+pub fn get_logs(app_state: &AppState, network: &str) -> Result<Vec<LogEntry>, ServerFnError> {
+    let network = get_network(app_state, network)?;
+    let logs: Vec<LogEntry> = app_state
+        .db
+        .iter_logs(&network.config.id)?
+        .collect::<Result<Vec<_>, _>>()?;
     Ok(logs)
 }
+
+// None while claims don't need proof of work
+pub fn get_pow_challenge(
+    app_state: &AppState,
+    network: &str,
+) -> Result<Option<PowChallenge>, ServerFnError> {
+    let network = get_network(app_state, network)?;
+    issue_challenge(app_state, network, now_timestamp()?)
+}
+
+// None while claims don't need a captcha
+pub fn get_captcha_widget(app_state: &AppState) -> Option<CaptchaWidget> {
+    app_state
+        .captcha
+        .as_ref()
+        .map(|captcha| captcha.widget.clone())
+}
+
+// receipt of a sent payout, None while it isn't mined
+async fn check_receipt(
+    network: &Network,
+    tx_hash: &str,
+    wait: Duration,
+) -> Result<Option<TxStatus>, ServerFnError> {
+    match network.sender.wait_for_receipt(tx_hash, wait).await {
+        Ok(true) => Ok(Some(TxStatus::Confirmed)),
+        Ok(false) => Ok(Some(TxStatus::Failed)),
+        Err(e) if e.is_tx_timeout() => Ok(None),
+        Err(e) => Err(ServerFnError::ServerError(format!(
+            "Unable to get the receipt: {}",
+            e
+        ))),
+    }
+}
+
+// the payout and its replacements share a nonce, whichever is mined settles the claim.
+// waits `wait` on the latest transaction, older ones are only polled
+async fn check_claim_receipt(
+    network: &Network,
+    value: &LogValue,
+    wait: Duration,
+) -> Result<Option<(TxStatus, String)>, ServerFnError> {
+    if let Some(status) = check_receipt(network, &value.result, wait).await? {
+        return Ok(Some((status, value.result.clone())));
+    }
+    for replaced in value.replaced.iter().rev() {
+        if let Some(status) = check_receipt(network, &replaced.tx_hash, RECEIPT_POLL).await? {
+            return Ok(Some((status, replaced.tx_hash.clone())));
+        }
+    }
+    Ok(None)
+}
+
+fn settle_claim(
+    app_state: &AppState,
+    network: &Network,
+    key: LogKey,
+    status: TxStatus,
+    tx_hash: String,
+) -> Result<(), ServerFnError> {
+    app_state.db.update_log(&network.config.id, key, |value| {
+        value.tx_status = Some(status);
+        value.result = tx_hash;
+    })?;
+    Ok(())
+}
+
+// one pass of the receipt watcher over the pending payouts of every network.
+// payouts unmined for rebroadcast_after_sec are replaced with bumped fees, payouts not mined
+// within receipt_timeout_sec are marked failed. RPC errors are retried next pass.
+pub async fn check_pending_claims(app_state: &AppState, now: u64) -> Result<(), ServerFnError> {
+    for network in &app_state.networks {
+        let pending = app_state
+            .db
+            .pending_logs(&network.config.id)?
+            .into_iter()
+            .filter(|entry| entry.value.tx_status == Some(TxStatus::Pending));
+
+        // batched claims share a transaction, it's checked and replaced once per pass
+        let mut receipts: HashMap<String, Option<(TxStatus, String)>> = HashMap::new();
+        let mut replacements: HashMap<String, String> = HashMap::new();
+        for entry in pending {
+            let receipt = match receipts.get(&entry.value.result) {
+                Some(receipt) => receipt.clone(),
+                None => {
+                    let Ok(receipt) =
+                        check_claim_receipt(network, &entry.value, RECEIPT_POLL).await
+                    else {
+                        continue;
+                    };
+                    receipts.insert(entry.value.result.clone(), receipt.clone());
+                    receipt
+                }
+            };
+            if let Some((status, tx_hash)) = receipt {
+                settle_claim(app_state, network, entry.key, status, tx_hash)?;
+                continue;
+            }
+
+            let last_sent = entry
+                .value
+                .replaced
+                .last()
+                .map_or(entry.key.timestamp, |r| r.timestamp);
+            if now.saturating_sub(entry.key.timestamp) >= app_state.receipt_timeout_sec {
+                app_state
+                    .db
+                    .set_log_tx_status(&network.config.id, entry.key, TxStatus::Failed)?;
+            } else if app_state.rebroadcast_after_sec > 0
+                && now.saturating_sub(last_sent) >= app_state.rebroadcast_after_sec
+                && let Some(new_hash) = match replacements.get(&entry.value.result) {
+                    Some(new_hash) => Some(new_hash.clone()),
+                    None => network
+                        .sender
+                        .replace_tx(&entry.value.result)
+                        .await
+                        .ok()
+                        .flatten(),
+                }
+            {
+                replacements.insert(entry.value.result.clone(), new_hash.clone());
+                app_state
+                    .db
+                    .update_log(&network.config.id, entry.key, |value| {
+                        let tx_hash = std::mem::replace(&mut value.result, new_hash);
+                        value.replaced.push(TxReplacement {
+                            tx_hash,
+                            timestamp: now,
+                        });
+                    })?;
+            }
+        }
+    }
+    Ok(())
+}
+
+pub async fn watch_receipts(app_state: AppState) {
+    loop {
+        if let Ok(now) = now_timestamp() {
+            check_pending_claims(&app_state, now).await.ok();
+        }
+        tokio::time::sleep(RECEIPT_WATCH_INTERVAL).await;
+    }
+}
+
+// receipt watcher and batch flusher. the state is built before the server runtime starts,
+// they get their own thread and runtime
+pub fn spawn_background_tasks(app_state: AppState) -> std::io::Result<()> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+    std::thread::Builder::new()
+        .name("faucet-background".to_string())
+        .spawn(move || {
+            runtime.spawn(flush_batches(app_state.clone()));
+            runtime.block_on(watch_receipts(app_state))
+        })?;
+    Ok(())
+}
+
+// status of a payout sent by claim, by the log key it returned. waits a while for the receipt
+// if it's still pending. None for payouts logged before receipts were tracked
+pub async fn claim_status(
+    app_state: &AppState,
+    network: &str,
+    log_key: LogKey,
+) -> Result<Option<TxStatus>, ServerFnError> {
+    let network = get_network(app_state, network)?;
+    let value = app_state
+        .db
+        .get_value_log(&network.config.id, log_key)?
+        .filter(|_| log_key.status == 1)
+        .ok_or_else(|| {
+            ServerFnError::<std::io::Error>::ServerError(format!(
+                "Unknown claim: {}-{}",
+                log_key.timestamp, log_key.seq
+            ))
+        })?;
+    if value.tx_status != Some(TxStatus::Pending) {
+        return Ok(value.tx_status);
+    }
+
+    match check_claim_receipt(network, &value, CLAIM_RECEIPT_WAIT).await? {
+        Some((status, mined_hash)) => {
+            settle_claim(app_state, network, log_key, status, mined_hash)?;
+            Ok(Some(status))
+        }
+        None => Ok(Some(TxStatus::Pending)),
+    }
+}
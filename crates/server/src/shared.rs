@@ -4,49 +4,174 @@ use crate::server::*;
 use crate::state;
 use dioxus::prelude::*;
 
-#[server(Claim)]
-pub async fn claim_server(input: String) -> Result<String, ServerFnError> {
-    let time = now_timestamp()?;
-    let fees_ok = is_network_fees_ok().await?;
-    let result = async {
-        let addr = resolve_name(input.clone()).await?;
-        let string_address = addr.to_string();
+// the rate limited functions answer with a RateLimited error when the limiter refuses them,
+// their own errors keep their kind
+#[cfg(feature = "server")]
+fn limited<T>(result: Result<T, ServerFnError>) -> Result<T, ServerFnError<RateLimited>> {
+    result.map_err(|e| match e {
+        ServerFnError::WrappedServerError(e) => ServerFnError::ServerError(e.to_string()),
+        ServerFnError::Registration(e) => ServerFnError::Registration(e),
+        ServerFnError::Request(e) => ServerFnError::Request(e),
+        ServerFnError::Response(e) => ServerFnError::Response(e),
+        ServerFnError::ServerError(e) => ServerFnError::ServerError(e),
+        ServerFnError::Deserialization(e) => ServerFnError::Deserialization(e),
+        ServerFnError::Serialization(e) => ServerFnError::Serialization(e),
+        ServerFnError::Args(e) => ServerFnError::Args(e),
+        ServerFnError::MissingArg(e) => ServerFnError::MissingArg(e),
+    })
+}
 
-        if is_in_cooldown(db_get_last_claim(&string_address).await?).await? {
-            return Err(ServerFnError::ServerError("Cooldown is not ended!".into()));
+// rate limited functions have fixed endpoints, the rate limiter matches them
+// json, the proof doesn't fit a form
+#[server(name = Claim, prefix = "/api", endpoint = "claim", input = server_fn::codec::Json)]
+pub async fn claim_server(
+    input: String,
+    network: String,
+    asset: String,
+    proof: ClaimProof,
+) -> Result<ClaimResult, ServerFnError<RateLimited>> {
+    limited(
+        async move {
+            let FromContext(app_state): dioxus::prelude::FromContext<state::AppState> =
+                extract().await?;
+            claim(&app_state, input, network, asset, proof).await
         }
+        .await,
+    )
+}
 
-        if !fees_ok {
-            return Err(ServerFnError::ServerError(
-                "Network Fee is too high!".into(),
-            ));
+#[server(name = Challenge, prefix = "/api", endpoint = "challenge")]
+pub async fn get_pow_challenge_data(
+    network: String,
+) -> Result<Option<PowChallenge>, ServerFnError<RateLimited>> {
+    limited(
+        async move {
+            let FromContext(app_state): dioxus::prelude::FromContext<state::AppState> =
+                extract().await?;
+            get_pow_challenge(&app_state, &network)
         }
-        let hash = send_sepolia_eth(addr).await?;
-        insert_timestamp(&string_address, time).await?;
+        .await,
+    )
+}
+
+#[server(Captcha)]
+pub async fn get_captcha_data() -> Result<Option<CaptchaWidget>, ServerFnError> {
+    let FromContext(app_state): dioxus::prelude::FromContext<state::AppState> = extract().await?;
+    Ok(get_captcha_widget(&app_state))
+}
 
-        Ok(hash)
-    }
-    .await;
-    match &result {
-        Ok(hash) => {
-            insert_log(time, true, input, hash.to_string()).await.ok();
+#[server(name = ClaimStatus, prefix = "/api", endpoint = "claim_status")]
+pub async fn claim_status_server(
+    log_key: LogKey,
+    network: String,
+) -> Result<Option<TxStatus>, ServerFnError<RateLimited>> {
+    limited(
+        async move {
+            let FromContext(app_state): dioxus::prelude::FromContext<state::AppState> =
+                extract().await?;
+            claim_status(&app_state, &network, log_key).await
         }
-        Err(e) => {
-            insert_log(time, false, input, e.to_string()).await.ok();
+        .await,
+    )
+}
+
+#[server(name = Payout, prefix = "/api", endpoint = "payout")]
+pub async fn get_payout_range_data(
+    network: String,
+) -> Result<PayoutRange, ServerFnError<RateLimited>> {
+    limited(
+        async move {
+            let FromContext(app_state): dioxus::prelude::FromContext<state::AppState> =
+                extract().await?;
+            get_payout_range(&app_state, &network).await
         }
-    }
-    result
+        .await,
+    )
 }
 
-#[server(Payout)]
-pub async fn get_payout_range_data() -> Result<PayoutRange, ServerFnError> {
-    Ok(get_payout_range().await?)
+#[server(Budget)]
+pub async fn get_budget_data(network: String) -> Result<BudgetRemaining, ServerFnError> {
+    let FromContext(app_state): dioxus::prelude::FromContext<state::AppState> = extract().await?;
+    get_budget_remaining(&app_state, &network)
 }
 
+use shared_types::AccessEntry;
+use shared_types::AccessList;
+use shared_types::BudgetRemaining;
+use shared_types::CaptchaWidget;
+use shared_types::ClaimProof;
+use shared_types::ClaimResult;
 use shared_types::LogEntry;
+use shared_types::LogKey;
+use shared_types::NetworkInfo;
 use shared_types::PayoutRange;
+use shared_types::PowChallenge;
+use shared_types::RateLimited;
+use shared_types::TxStatus;
+
+#[server(name = GetLogs, prefix = "/api", endpoint = "logs")]
+pub async fn get_all_logs(network: String) -> Result<Vec<LogEntry>, ServerFnError<RateLimited>> {
+    limited(
+        async move {
+            let FromContext(app_state): dioxus::prelude::FromContext<state::AppState> =
+                extract().await?;
+            get_logs(&app_state, &network)
+        }
+        .await,
+    )
+}
+
+#[server(GetNetworks)]
+pub async fn get_networks_data() -> Result<Vec<NetworkInfo>, ServerFnError> {
+    let FromContext(app_state): dioxus::prelude::FromContext<state::AppState> = extract().await?;
+    Ok(get_networks(&app_state))
+}
+
+// admin functions, each takes ADMIN_TOKEN. fixed endpoints, the --admin cli posts to them
+#[server(name = AdminAddAccess, prefix = "/api", endpoint = "admin/add")]
+pub async fn admin_add_access(
+    token: String,
+    list: AccessList,
+    entry: String,
+    reason: String,
+) -> Result<AccessEntry, ServerFnError<RateLimited>> {
+    limited(
+        async move {
+            let FromContext(app_state): dioxus::prelude::FromContext<state::AppState> =
+                extract().await?;
+            crate::admin::add_access_entry(&app_state, &token, list, &entry, &reason)
+        }
+        .await,
+    )
+}
 
-#[server(GetLogs)]
-pub async fn get_all_logs() -> Result<Vec<LogEntry>, ServerFnError> {
-    Ok(get_logs().await?)
+#[server(name = AdminRemoveAccess, prefix = "/api", endpoint = "admin/remove")]
+pub async fn admin_remove_access(
+    token: String,
+    list: AccessList,
+    entry: String,
+) -> Result<bool, ServerFnError<RateLimited>> {
+    limited(
+        async move {
+            let FromContext(app_state): dioxus::prelude::FromContext<state::AppState> =
+                extract().await?;
+            crate::admin::remove_access_entry(&app_state, &token, list, &entry)
+        }
+        .await,
+    )
+}
+
+#[server(name = AdminAccessEntries, prefix = "/api", endpoint = "admin/list")]
+pub async fn admin_access_entries(
+    token: String,
+    list: AccessList,
+) -> Result<Vec<AccessEntry>, ServerFnError<RateLimited>> {
+    limited(
+        async move {
+            let FromContext(app_state): dioxus::prelude::FromContext<state::AppState> =
+                extract().await?;
+            crate::admin::access_entries(&app_state, &token, list)
+        }
+        .await,
+    )
 }
@@ -1,7 +1,11 @@
+use crate::captcha::{Captcha, CaptchaConfig};
+use crate::config::{Config, ConfigError};
+use crate::curve::PayoutCurve;
+use crate::pow::PowConfig;
 use db::*;
-use dotenv::dotenv;
-use std::env;
-use zeroxname_ethereum::Address;
+use shared_types::{ClaimSource, NATIVE_ASSET};
+use std::sync::Arc;
+use zeroxname_ethereum::signer::SignerConfig;
 use zeroxname_ethereum::*;
 
 #[derive(Debug, thiserror::Error)]
@@ -10,48 +14,347 @@ pub enum AppStateErrors {
     DBError(#[from] DBErrors),
     #[error("Eth lib Init Error: {0}")]
     ETHError(#[from] EthErrors),
-    #[error("Env vars Init Error: {0}")]
-    ENVError(#[from] std::env::VarError),
-    #[error("Parse error: should be u64")]
-    ENVIntError(#[from] std::num::ParseIntError),
-    #[error("Parse error: should be f64")]
-    ENVFloatError(#[from] std::num::ParseFloatError),
+    #[error("{0}")]
+    ConfigError(#[from] ConfigError),
+}
+
+#[derive(Clone)]
+pub struct Network {
+    pub config: NetworkConfig,
+    pub sender: Arc<dyn PayoutSender>,
+    // the native coin first, then the configured tokens
+    pub assets: Vec<Asset>,
+}
+
+impl Network {
+    // empty id picks the native coin
+    pub fn asset(&self, id: &str) -> Option<&Asset> {
+        let id = match id {
+            "" => NATIVE_ASSET,
+            _ => id,
+        };
+        self.assets.iter().find(|a| a.id == id)
+    }
+}
+
+// a configured network, before its sender is connected
+#[derive(Clone, Debug)]
+pub struct NetworkSettings {
+    pub config: NetworkConfig,
+    pub assets: Vec<Asset>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum AssetKind {
+    // paid by the payout curve
+    Native,
+    // fixed amount per claim, in token base units
+    Erc20 { token: Address, amount: U256 },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Asset {
+    pub id: String,
+    pub symbol: String,
+    pub kind: AssetKind,
+    // replaces the window of every cooldown identity, None keeps the global windows
+    pub cooldown_sec: Option<u64>,
+}
+
+impl Asset {
+    pub fn native() -> Self {
+        Self {
+            id: NATIVE_ASSET.to_string(),
+            symbol: "ETH".to_string(),
+            kind: AssetKind::Native,
+            cooldown_sec: None,
+        }
+    }
+}
+
+// what a claim cooldown is counted against
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CooldownIdentity {
+    Address,
+    TokenId,
+    Community,
+}
+
+#[derive(Clone, Debug)]
+pub struct Cooldown {
+    pub identity: CooldownIdentity,
+    pub window_sec: u64,
+}
+
+// claim terms of a community, the tln after '@' in 0xNAMEs
+#[derive(Clone, Debug, PartialEq)]
+pub struct CommunityPolicy {
+    pub id: String,
+    // native payouts are multiplied by it
+    pub multiplier: f64,
+    // claims per UTC day and network, 0 is unlimited
+    pub daily_quota: u64,
+    // replaces the window of every cooldown identity, a token's own override comes first
+    pub cooldown_sec: Option<u64>,
+    // names that may claim, empty allows every name that isn't denied
+    pub allow: Vec<String>,
+    pub deny: Vec<String>,
+}
+
+impl Default for CommunityPolicy {
+    fn default() -> Self {
+        Self {
+            id: String::new(),
+            multiplier: 1.0,
+            daily_quota: 0,
+            cooldown_sec: None,
+            allow: Vec::new(),
+            deny: Vec::new(),
+        }
+    }
+}
+
+impl CommunityPolicy {
+    pub fn admits(&self, name: &str) -> bool {
+        let listed = |names: &[String]| names.iter().any(|n| n.eq_ignore_ascii_case(name));
+        !listed(&self.deny) && (self.allow.is_empty() || listed(&self.allow))
+    }
+
+    // db quota key
+    pub fn quota(&self) -> String {
+        format!("community:{}", self.id)
+    }
+}
+
+// terms of a claim source, see shared_types::ClaimSource. sources without one can't claim
+#[derive(Clone, Debug, PartialEq)]
+pub struct SourcePolicy {
+    pub source: ClaimSource,
+    // native payouts are multiplied by it, on top of the community's
+    pub multiplier: f64,
+}
+
+// claims are queued and paid together through the network's disperse contract
+#[derive(Clone, Debug)]
+pub struct BatchConfig {
+    // oldest queued claim waits at most this long
+    pub interval_sec: u64,
+    // a full batch is sent right away
+    pub max_claims: usize,
+}
+
+// low faucet balance handling, per network wallet. 0 disables a mark
+#[derive(Clone, Debug, Default)]
+pub struct FundsConfig {
+    // below this balance payouts shrink, down to nothing at the floor
+    pub low_water_wei: U256,
+    // at or below this balance claims are paused
+    pub floor_wei: U256,
+}
+
+impl FundsConfig {
+    pub fn is_paused(&self, balance: U256) -> bool {
+        !self.floor_wei.is_zero() && balance <= self.floor_wei
+    }
+
+    // share of the payout still paid at `balance`, 1.0 from the low-water mark up
+    pub fn payout_scale(&self, balance: U256) -> f64 {
+        if balance >= self.low_water_wei {
+            return 1.0;
+        }
+        let range = self.low_water_wei.saturating_sub(self.floor_wei);
+        if range.is_zero() {
+            return 0.0;
+        }
+        wei_to_f64(balance.saturating_sub(self.floor_wei)) / wei_to_f64(range)
+    }
+}
+
+// native wei a network may pay out per UTC day and hour, token payouts don't count. 0 disables a limit
+#[derive(Clone, Debug, Default)]
+pub struct BudgetConfig {
+    pub daily_wei: U256,
+    pub hourly_wei: U256,
+}
+
+impl BudgetConfig {
+    // the enabled limits, as db::DB::reserve_spend takes them
+    pub fn limits(&self) -> Vec<(BudgetPeriod, u128)> {
+        [
+            (BudgetPeriod::Day, self.daily_wei),
+            (BudgetPeriod::Hour, self.hourly_wei),
+        ]
+        .into_iter()
+        .filter(|(_, limit)| !limit.is_zero())
+        .map(|(period, limit)| (period, u128::try_from(limit).unwrap_or(u128::MAX)))
+        .collect()
+    }
+}
+
+// mainnet activity a recipient needs, against wallets made to farm the faucet. 0 disables a
+// threshold, with both set meeting either one will do
+#[derive(Clone, Debug, Default)]
+pub struct EligibilityConfig {
+    pub min_balance_wei: U256,
+    pub min_nonce: u64,
+}
+
+impl EligibilityConfig {
+    pub fn is_enabled(&self) -> bool {
+        !self.min_balance_wei.is_zero() || self.min_nonce > 0
+    }
+
+    pub fn admits(&self, activity: &MainnetActivity) -> bool {
+        let balance_ok =
+            !self.min_balance_wei.is_zero() && activity.balance >= self.min_balance_wei;
+        let nonce_ok = self.min_nonce > 0 && activity.nonce >= self.min_nonce;
+        !self.is_enabled() || balance_ok || nonce_ok
+    }
+}
+
+// registry checks against throwaway 0xNAMEs, off by default
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NameChecks {
+    // the name hasn't expired
+    pub unexpired: bool,
+    // the name is owned by the address it resolves to, and is its primary name
+    pub primary: bool,
+}
+
+impl NameChecks {
+    pub fn is_enabled(&self) -> bool {
+        self.unexpired || self.primary
+    }
+}
+
+// who may claim besides the denylist, see shared_types::AccessList
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum AccessMode {
+    // everyone who isn't denylisted
+    #[default]
+    Open,
+    // only allowlisted addresses and names
+    Allowlist,
+}
+
+pub(crate) fn wei_to_f64(wei: U256) -> f64 {
+    u128::try_from(wei).map_or(f64::MAX, |wei| wei as f64)
 }
 
 #[derive(Clone)]
 pub struct AppState {
-    pub zx: ZeroxnameEthereum,
+    pub resolver: Arc<dyn NameResolver>,
+    // in NETWORKS order, the first one is the default
+    pub networks: Vec<Network>,
     pub db: DB,
-    pub cooldown_sec: u64,
-    pub payout_adjustment: f64,
+    pub cooldowns: Vec<Cooldown>,
+    pub curve: PayoutCurve,
+    // payouts not mined within this window are logged as failed
+    pub receipt_timeout_sec: u64,
+    // unmined payouts are resent with bumped fees after this long, 0 disables
+    pub rebroadcast_after_sec: u64,
+    // None sends every claim on its own
+    pub batch: Option<BatchConfig>,
+    pub funds: FundsConfig,
+    pub budget: BudgetConfig,
+    // communities without a policy claim on the default terms
+    pub communities: Vec<CommunityPolicy>,
+    // sources without a policy can't claim
+    pub sources: Vec<SourcePolicy>,
+    pub access_mode: AccessMode,
+    pub eligibility: EligibilityConfig,
+    pub name_checks: NameChecks,
+    // None disables the admin functions
+    pub admin_token: Option<String>,
+    // None takes claims without proof of work
+    pub pow: Option<PowConfig>,
+    // None takes claims without a captcha
+    pub captcha: Option<Captcha>,
 }
 
 impl AppState {
     pub fn new() -> Result<Self, AppStateErrors> {
-        dotenv().ok();
-        let rpc_mainnet = env::var("RPC_MAINNET")?;
-        let rpc_sepolia = env::var("RPC_SEPOLIA")?;
-        let private_key = env::var("PRIVATE_KEY")?;
-        let db_path = env::var("DB_PATH")?;
-        let faucet_limit: u64 = env::var("FAUCET_LIMIT")?.parse()?;
-        let fee_threshold: f64 = env::var("FEE_THRESHOLD")?.parse()?;
-        let cooldown_sec: u64 = env::var("COOLDOWN_SEC")?.parse()?;
-        let payout_adjustment: f64 = env::var("PAYOUT_ADJUSTMENT")?.parse()?;
-        let partition_size_limit: u64 = env::var("PARTITION_SIZE_LIMIT")?.parse()?;
-        let zx = ZeroxnameEthereum::new(
-            &rpc_mainnet,
-            &rpc_sepolia,
-            &private_key,
-            faucet_limit,
-            fee_threshold,
-        )?;
-        let db = DB::new(&db_path, partition_size_limit)?;
+        Self::from_config(Config::load()?)
+    }
+
+    pub fn from_config(config: Config) -> Result<Self, AppStateErrors> {
+        // every key is a pool wallet with its own nonces and balance, loaded once for all networks
+        let signers = config
+            .signers
+            .iter()
+            .map(SignerConfig::load)
+            .collect::<Result<Vec<_>, _>>()?;
+        let ids: Vec<&str> = config
+            .networks
+            .iter()
+            .map(|n| n.config.id.as_str())
+            .collect();
+        // senders keep their nonces in the db
+        let db = DB::new(&config.db_path, config.partition_size_limit, &ids)?;
+
+        let mut networks = Vec::new();
+        // name resolution always goes to mainnet, any network's instance will do
+        let mut resolver: Option<Arc<dyn NameResolver>> = None;
+        for NetworkSettings {
+            config: network,
+            assets,
+        } in config.networks
+        {
+            let zx = Arc::new(ZeroxnameEthereum::new(
+                &config.rpc_mainnet,
+                &network,
+                &signers,
+                config.wallet_strategy,
+                db.clone(),
+            )?);
+            resolver.get_or_insert(zx.clone());
+            networks.push(Network {
+                config: network,
+                sender: zx,
+                assets,
+            });
+        }
+        // the config always holds a network
+        let resolver = resolver.ok_or_else(|| ConfigError::new("NETWORKS", "is empty"))?;
 
         Ok(Self {
-            zx,
+            resolver,
+            networks,
             db,
-            cooldown_sec,
-            payout_adjustment,
+            cooldowns: config.cooldowns,
+            curve: config.curve,
+            receipt_timeout_sec: config.receipt_timeout_sec,
+            rebroadcast_after_sec: config.rebroadcast_after_sec,
+            batch: config.batch,
+            funds: config.funds,
+            budget: config.budget,
+            communities: config.communities,
+            sources: config.sources,
+            access_mode: config.access_mode,
+            eligibility: config.eligibility,
+            name_checks: config.name_checks,
+            admin_token: config.admin_token,
+            pow: config.pow,
+            captcha: config.captcha.as_ref().map(CaptchaConfig::captcha),
         })
     }
+
+    pub fn source(&self, source: ClaimSource) -> Option<&SourcePolicy> {
+        self.sources.iter().find(|p| p.source == source)
+    }
+
+    // the claimed community as typed, ids are lower case
+    pub fn community(&self, id: &str) -> Option<&CommunityPolicy> {
+        self.communities
+            .iter()
+            .find(|c| c.id.eq_ignore_ascii_case(id))
+    }
+
+    // empty id picks the default network
+    pub fn network(&self, id: &str) -> Option<&Network> {
+        match id {
+            "" => self.networks.first(),
+            _ => self.networks.iter().find(|n| n.config.id == id),
+        }
+    }
 }
@@ -0,0 +1,45 @@
+// to run from root directory cargo test -p server --features server --test captcha_test
+#![cfg(feature = "server")]
+
+use server::captcha::{CaptchaError, CaptchaVerifier, HCaptcha, Turnstile, mock};
+
+const SECRET: &str = "0x0000000000000000000000000000000000000000";
+
+#[tokio::test]
+async fn test_hcaptcha_verifies_tokens_once() {
+    let url = mock::serve(SECRET, &["solved"])
+        .await
+        .expect("should serve");
+    let hcaptcha = HCaptcha::new(SECRET, "site", Some(&url));
+
+    assert!(!hcaptcha.verify("wrong").await.expect("should verify"));
+    assert!(hcaptcha.verify("solved").await.expect("should verify"));
+    // tokens are single use
+    assert!(!hcaptcha.verify("solved").await.expect("should verify"));
+}
+
+#[tokio::test]
+async fn test_turnstile_verifies_tokens_once() {
+    let url = mock::serve(SECRET, &["solved"])
+        .await
+        .expect("should serve");
+    let turnstile = Turnstile::new(SECRET, Some(&url));
+
+    assert!(!turnstile.verify("").await.expect("should verify"));
+    assert!(turnstile.verify("solved").await.expect("should verify"));
+    assert!(!turnstile.verify("solved").await.expect("should verify"));
+}
+
+#[tokio::test]
+async fn test_wrong_secret_is_a_provider_error() {
+    let url = mock::serve(SECRET, &["solved"])
+        .await
+        .expect("should serve");
+    let turnstile = Turnstile::new("wrong", Some(&url));
+
+    let err = turnstile
+        .verify("solved")
+        .await
+        .expect_err("wrong secret should fail");
+    assert!(matches!(err, CaptchaError::Provider(code) if code == "invalid-input-secret"));
+}
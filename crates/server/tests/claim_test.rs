@@ -0,0 +1,1586 @@
+// to run from root directory cargo test -p server --features server --test claim_test
+#![cfg(feature = "server")]
+
+use db::DB;
+use server::admin::{access_entries, add_access_entry, remove_access_entry};
+use server::captcha::{CaptchaConfig, mock};
+use server::curve::{PayoutCurve, PayoutMode};
+use server::pow::PowConfig;
+use server::server::{
+    check_pending_claims, claim, claim_status, flush_batch, get_budget_remaining,
+    get_captcha_widget, get_logs, get_payout_range, get_pow_challenge, now_timestamp,
+};
+use server::state::{
+    AccessMode, AppState, Asset, AssetKind, BatchConfig, BudgetConfig, CommunityPolicy, Cooldown,
+    CooldownIdentity, EligibilityConfig, FundsConfig, NameChecks, Network, SourcePolicy,
+};
+use shared_types::{
+    AccessList, CaptchaProvider, ClaimProof, ClaimSource, LogKey, LogValue, PowSolution,
+    Prevention, TxStatus,
+};
+use std::sync::Arc;
+use tempfile::{TempDir, tempdir};
+use zeroxname_ethereum::mock::{MOCK_WALLET, MockChain};
+use zeroxname_ethereum::{Address, NetworkConfig, U256};
+
+const FAUCET_LIMIT: u64 = 50_000_000_000_000_000;
+const USDC_AMOUNT: u64 = 10_000_000;
+const ADMIN_TOKEN: &str = "0123456789abcdef";
+
+fn alice() -> Address {
+    "0xf0E5D3Cc05206987a125afC404b719e54Fa942a8"
+        .parse()
+        .expect("valid address")
+}
+
+fn usdc() -> Address {
+    "0x1c7D4B196Cb0C7B01d743Fbc6116a902379C7238"
+        .parse()
+        .expect("valid address")
+}
+
+fn mock_network(id: &str, chain: Arc<MockChain>) -> Network {
+    Network {
+        config: NetworkConfig {
+            id: id.to_string(),
+            name: id.to_string(),
+            rpc_url: "http://localhost:8545".to_string(),
+            chain_id: 1337,
+            faucet_limit: FAUCET_LIMIT,
+            fee_threshold: 0.1,
+            explorer_url: "http://localhost".to_string(),
+            disperse_address: None,
+        },
+        sender: chain,
+        assets: vec![
+            Asset::native(),
+            Asset {
+                id: "usdc".to_string(),
+                symbol: "USDC".to_string(),
+                kind: AssetKind::Erc20 {
+                    token: usdc(),
+                    amount: U256::from(USDC_AMOUNT),
+                },
+                cooldown_sec: Some(3600),
+            },
+        ],
+    }
+}
+
+fn mock_state(chain: Arc<MockChain>) -> (AppState, TempDir) {
+    let dir = tempdir().expect("should create temp dir");
+    let db = DB::new(
+        dir.path().to_str().expect("valid path"),
+        1024 * 1024,
+        &["sepolia", "hoodi"],
+    )
+    .expect("should create DB");
+    let state = AppState {
+        resolver: chain.clone(),
+        networks: vec![
+            mock_network("sepolia", chain.clone()),
+            mock_network("hoodi", chain),
+        ],
+        db,
+        cooldowns: vec![Cooldown {
+            identity: CooldownIdentity::Address,
+            window_sec: 86400,
+        }],
+        curve: PayoutCurve::default(),
+        receipt_timeout_sec: 600,
+        rebroadcast_after_sec: 180,
+        batch: None,
+        funds: FundsConfig::default(),
+        budget: BudgetConfig::default(),
+        communities: Vec::new(),
+        sources: vec![SourcePolicy {
+            source: ClaimSource::ZeroxName,
+            multiplier: 1.0,
+        }],
+        access_mode: AccessMode::Open,
+        eligibility: EligibilityConfig::default(),
+        name_checks: NameChecks::default(),
+        admin_token: Some(ADMIN_TOKEN.to_string()),
+        pow: None,
+        captcha: None,
+    };
+    (state, dir)
+}
+
+#[tokio::test]
+async fn test_claim_sends_and_logs() {
+    let chain = Arc::new(MockChain::new(FAUCET_LIMIT, 0.1).with_name("alice", "eth", alice()));
+    let (state, _dir) = mock_state(chain.clone());
+
+    let hash = claim(
+        &state,
+        "alice@eth".to_string(),
+        String::new(),
+        String::new(),
+        ClaimProof::default(),
+    )
+    .await
+    .expect("claim should succeed")
+    .tx_hash;
+
+    let sent = chain.sent();
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].0, alice());
+    let logs = get_logs(&state, "sepolia").expect("should get logs");
+    assert_eq!(logs.len(), 1);
+    assert_eq!(logs[0].key.status, 1);
+    assert_eq!(logs[0].value.result, hash);
+    assert_eq!(logs[0].value.wallet, MOCK_WALLET.to_string());
+}
+
+#[tokio::test]
+async fn test_claim_cooldown_and_unknown_name() {
+    let chain = Arc::new(MockChain::new(FAUCET_LIMIT, 0.1).with_name("alice", "eth", alice()));
+    let (state, _dir) = mock_state(chain.clone());
+
+    claim(
+        &state,
+        "alice@eth".to_string(),
+        String::new(),
+        String::new(),
+        ClaimProof::default(),
+    )
+    .await
+    .expect("first claim should succeed");
+    let err = claim(
+        &state,
+        "alice@eth".to_string(),
+        String::new(),
+        String::new(),
+        ClaimProof::default(),
+    )
+    .await
+    .expect_err("second claim should be refused");
+    assert!(err.to_string().contains("Cooldown is not ended"));
+
+    let err = claim(
+        &state,
+        "nobody@eth".to_string(),
+        String::new(),
+        String::new(),
+        ClaimProof::default(),
+    )
+    .await
+    .expect_err("unknown name should be refused");
+    assert!(
+        err.to_string()
+            .contains("Unable to resolve the name: No name found")
+    );
+    assert_eq!(chain.sent().len(), 1);
+}
+
+#[tokio::test]
+async fn test_failed_claim_releases_cooldown() {
+    let chain = Arc::new(MockChain::new(FAUCET_LIMIT, 0.1).with_name("alice", "eth", alice()));
+    let (state, _dir) = mock_state(chain.clone());
+
+    chain.set_gas_fee(FAUCET_LIMIT as u128);
+    let err = claim(
+        &state,
+        "alice@eth".to_string(),
+        String::new(),
+        String::new(),
+        ClaimProof::default(),
+    )
+    .await
+    .expect_err("high fee should be refused");
+    assert!(err.to_string().contains("Network Fee is too high"));
+
+    chain.set_gas_fee(0);
+    chain.set_fail_sends(true);
+    let err = claim(
+        &state,
+        "alice@eth".to_string(),
+        String::new(),
+        String::new(),
+        ClaimProof::default(),
+    )
+    .await
+    .expect_err("failed send should be refused");
+    assert!(err.to_string().contains("Unable to send sepolia ETH"));
+    // failures aren't prevented claims
+    let logs = get_logs(&state, "sepolia").expect("should get logs");
+    assert_eq!(logs.len(), 2);
+    assert!(logs.iter().all(|entry| entry.value.prevented.is_none()));
+
+    chain.set_fail_sends(false);
+    claim(
+        &state,
+        "alice@eth".to_string(),
+        String::new(),
+        String::new(),
+        ClaimProof::default(),
+    )
+    .await
+    .expect("claim after failures should succeed");
+    assert_eq!(chain.sent().len(), 1);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 8)]
+async fn test_parallel_claims_pay_once() {
+    let chain = Arc::new(MockChain::new(FAUCET_LIMIT, 0.1).with_name("alice", "eth", alice()));
+    let (state, _dir) = mock_state(chain.clone());
+
+    let handles: Vec<_> = (0..64)
+        .map(|_| {
+            let state = state.clone();
+            tokio::spawn(async move {
+                claim(
+                    &state,
+                    "alice@eth".to_string(),
+                    String::new(),
+                    String::new(),
+                    ClaimProof::default(),
+                )
+                .await
+            })
+        })
+        .collect();
+    let mut paid = 0;
+    for handle in handles {
+        if handle.await.expect("task should finish").is_ok() {
+            paid += 1;
+        }
+    }
+
+    assert_eq!(paid, 1, "Only one parallel claim should be paid");
+    assert_eq!(chain.sent().len(), 1);
+}
+
+#[tokio::test]
+async fn test_claim_per_network() {
+    let chain = Arc::new(MockChain::new(FAUCET_LIMIT, 0.1).with_name("alice", "eth", alice()));
+    let (state, _dir) = mock_state(chain.clone());
+
+    // cooldown on one network doesn't block another
+    claim(
+        &state,
+        "alice@eth".to_string(),
+        "sepolia".to_string(),
+        String::new(),
+        ClaimProof::default(),
+    )
+    .await
+    .expect("sepolia claim should succeed");
+    claim(
+        &state,
+        "alice@eth".to_string(),
+        "hoodi".to_string(),
+        String::new(),
+        ClaimProof::default(),
+    )
+    .await
+    .expect("hoodi claim should succeed");
+    claim(
+        &state,
+        "alice@eth".to_string(),
+        "hoodi".to_string(),
+        String::new(),
+        ClaimProof::default(),
+    )
+    .await
+    .expect_err("second hoodi claim should be refused");
+
+    let err = claim(
+        &state,
+        "alice@eth".to_string(),
+        "mainnet".to_string(),
+        String::new(),
+        ClaimProof::default(),
+    )
+    .await
+    .expect_err("unknown network should be refused");
+    assert!(err.to_string().contains("Unknown network: mainnet"));
+
+    assert_eq!(chain.sent().len(), 2);
+    assert_eq!(
+        get_logs(&state, "sepolia").expect("should get logs").len(),
+        1
+    );
+    assert_eq!(get_logs(&state, "hoodi").expect("should get logs").len(), 2);
+}
+
+#[tokio::test]
+async fn test_claim_token() {
+    let chain = Arc::new(
+        MockChain::new(FAUCET_LIMIT, 0.1)
+            .with_name("alice", "eth", alice())
+            .with_token(usdc(), U256::from(USDC_AMOUNT * 3 / 2)),
+    );
+    let (state, _dir) = mock_state(chain.clone());
+
+    claim(
+        &state,
+        "alice@eth".to_string(),
+        String::new(),
+        "usdc".to_string(),
+        ClaimProof::default(),
+    )
+    .await
+    .expect("token claim should succeed");
+    assert_eq!(
+        chain.sent_tokens(),
+        vec![(usdc(), alice(), U256::from(USDC_AMOUNT))]
+    );
+
+    // token cooldown is its own, ETH can still be claimed
+    let err = claim(
+        &state,
+        "alice@eth".to_string(),
+        String::new(),
+        "usdc".to_string(),
+        ClaimProof::default(),
+    )
+    .await
+    .expect_err("second token claim should be refused");
+    assert!(err.to_string().contains("Cooldown is not ended"));
+    claim(
+        &state,
+        "alice@eth".to_string(),
+        String::new(),
+        "eth".to_string(),
+        ClaimProof::default(),
+    )
+    .await
+    .expect("ETH claim should succeed");
+    assert_eq!(chain.sent().len(), 1);
+
+    let err = claim(
+        &state,
+        "alice@eth".to_string(),
+        String::new(),
+        "dai".to_string(),
+        ClaimProof::default(),
+    )
+    .await
+    .expect_err("unknown asset should be refused");
+    assert!(err.to_string().contains("Unknown asset dai"));
+
+    let logs = get_logs(&state, "sepolia").expect("should get logs");
+    assert_eq!(logs[0].value.asset, "usdc");
+}
+
+#[tokio::test]
+async fn test_claim_token_out_of_balance() {
+    let chain = Arc::new(
+        MockChain::new(FAUCET_LIMIT, 0.1)
+            .with_name("alice", "eth", alice())
+            .with_token(usdc(), U256::from(USDC_AMOUNT - 1)),
+    );
+    let (state, _dir) = mock_state(chain.clone());
+
+    let err = claim(
+        &state,
+        "alice@eth".to_string(),
+        String::new(),
+        "usdc".to_string(),
+        ClaimProof::default(),
+    )
+    .await
+    .expect_err("empty faucet should be refused");
+    assert!(err.to_string().contains("Faucet is out of sepolia USDC"));
+    assert!(chain.sent_tokens().is_empty());
+
+    // refused claim doesn't start the cooldown
+    chain.set_token_balance(usdc(), U256::from(USDC_AMOUNT));
+    claim(
+        &state,
+        "alice@eth".to_string(),
+        String::new(),
+        "usdc".to_string(),
+        ClaimProof::default(),
+    )
+    .await
+    .expect("claim after refill should succeed");
+}
+
+#[tokio::test]
+async fn test_claim_receipt_confirmed() {
+    let chain = Arc::new(MockChain::new(FAUCET_LIMIT, 0.1).with_name("alice", "eth", alice()));
+    let (state, _dir) = mock_state(chain.clone());
+
+    let key = claim(
+        &state,
+        "alice@eth".to_string(),
+        String::new(),
+        String::new(),
+        ClaimProof::default(),
+    )
+    .await
+    .expect("claim should succeed")
+    .log_key
+    .expect("claim should be logged");
+    let logs = get_logs(&state, "sepolia").expect("should get logs");
+    assert_eq!(logs[0].value.tx_status, Some(TxStatus::Pending));
+
+    let now = now_timestamp().expect("should get time");
+    check_pending_claims(&state, now)
+        .await
+        .expect("watcher pass should succeed");
+    let logs = get_logs(&state, "sepolia").expect("should get logs");
+    assert_eq!(logs[0].value.tx_status, Some(TxStatus::Confirmed));
+    assert_eq!(
+        claim_status(&state, "sepolia", key)
+            .await
+            .expect("should get status"),
+        Some(TxStatus::Confirmed)
+    );
+
+    let unknown = LogKey {
+        seq: key.seq + 1,
+        ..key
+    };
+    let err = claim_status(&state, "sepolia", unknown)
+        .await
+        .expect_err("unknown claim should be refused");
+    assert!(err.to_string().contains("Unknown claim"));
+}
+
+#[tokio::test]
+async fn test_claim_receipt_failed() {
+    let chain = Arc::new(MockChain::new(FAUCET_LIMIT, 0.1).with_name("alice", "eth", alice()));
+    let (state, _dir) = mock_state(chain.clone());
+    let now = now_timestamp().expect("should get time");
+
+    // reverted
+    chain.set_receipt(Some(false));
+    let key = claim(
+        &state,
+        "alice@eth".to_string(),
+        "sepolia".to_string(),
+        String::new(),
+        ClaimProof::default(),
+    )
+    .await
+    .expect("claim should succeed")
+    .log_key
+    .expect("claim should be logged");
+    assert_eq!(
+        claim_status(&state, "sepolia", key)
+            .await
+            .expect("should get status"),
+        Some(TxStatus::Failed)
+    );
+
+    // never mined, pending until the receipt timeout
+    chain.set_receipt(None);
+    let key = claim(
+        &state,
+        "alice@eth".to_string(),
+        "hoodi".to_string(),
+        String::new(),
+        ClaimProof::default(),
+    )
+    .await
+    .expect("claim should succeed")
+    .log_key
+    .expect("claim should be logged");
+    assert_eq!(
+        claim_status(&state, "hoodi", key)
+            .await
+            .expect("should get status"),
+        Some(TxStatus::Pending)
+    );
+    check_pending_claims(&state, now)
+        .await
+        .expect("watcher pass should succeed");
+    let logs = get_logs(&state, "hoodi").expect("should get logs");
+    assert_eq!(logs[0].value.tx_status, Some(TxStatus::Pending));
+
+    check_pending_claims(&state, now + 600)
+        .await
+        .expect("watcher pass should succeed");
+    let logs = get_logs(&state, "hoodi").expect("should get logs");
+    assert_eq!(logs[0].value.tx_status, Some(TxStatus::Failed));
+    assert_eq!(
+        get_logs(&state, "sepolia").expect("should get logs")[0]
+            .value
+            .tx_status,
+        Some(TxStatus::Failed)
+    );
+}
+
+#[tokio::test]
+async fn test_stuck_claim_replaced() {
+    let chain = Arc::new(MockChain::new(FAUCET_LIMIT, 0.1).with_name("alice", "eth", alice()));
+    let (state, _dir) = mock_state(chain.clone());
+    let now = now_timestamp().expect("should get time");
+
+    chain.set_receipt(None);
+    let result = claim(
+        &state,
+        "alice@eth".to_string(),
+        String::new(),
+        String::new(),
+        ClaimProof::default(),
+    )
+    .await
+    .expect("claim should succeed");
+    let hash = result.tx_hash;
+
+    check_pending_claims(&state, now + 180)
+        .await
+        .expect("watcher pass should succeed");
+    assert_eq!(chain.replaced(), vec![hash.clone()]);
+    let logs = get_logs(&state, "sepolia").expect("should get logs");
+    assert_ne!(logs[0].value.result, hash);
+    assert_eq!(logs[0].value.replaced.len(), 1);
+    assert_eq!(logs[0].value.replaced[0].tx_hash, hash);
+    assert_eq!(logs[0].value.tx_status, Some(TxStatus::Pending));
+
+    // not again before rebroadcast_after_sec since the last replacement
+    check_pending_claims(&state, now + 200)
+        .await
+        .expect("watcher pass should succeed");
+    assert_eq!(chain.replaced().len(), 1);
+
+    // bumped fee over the fee threshold, left as is
+    chain.set_gas_fee(FAUCET_LIMIT as u128);
+    check_pending_claims(&state, now + 400)
+        .await
+        .expect("watcher pass should succeed");
+    assert_eq!(chain.replaced().len(), 1);
+
+    // the claim is still found after its hash changed
+    chain.set_receipt(Some(true));
+    let key = result.log_key.expect("claim should be logged");
+    assert_eq!(
+        claim_status(&state, "sepolia", key)
+            .await
+            .expect("should get status"),
+        Some(TxStatus::Confirmed)
+    );
+}
+
+// batches of 3 on sepolia, hoodi has no disperse contract
+fn batch_state(chain: Arc<MockChain>) -> (AppState, TempDir) {
+    let (mut state, dir) = mock_state(chain);
+    state.networks[0].config.disperse_address = Some(Address::with_last_byte(0xd1));
+    state.batch = Some(BatchConfig {
+        interval_sec: 60,
+        max_claims: 3,
+    });
+    (state, dir)
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_full_batch_sent_together() {
+    let users = ["alice", "bobby", "carol"];
+    let chain = Arc::new(
+        users
+            .iter()
+            .enumerate()
+            .fold(MockChain::new(FAUCET_LIMIT, 0.1), |chain, (i, name)| {
+                chain.with_name(name, "eth", Address::with_last_byte(i as u8 + 1))
+            }),
+    );
+    let (state, _dir) = batch_state(chain.clone());
+
+    let handles: Vec<_> = users
+        .iter()
+        .map(|name| {
+            let state = state.clone();
+            let input = format!("{}@eth", name);
+            tokio::spawn(async move {
+                claim(
+                    &state,
+                    input,
+                    String::new(),
+                    String::new(),
+                    ClaimProof::default(),
+                )
+                .await
+            })
+        })
+        .collect();
+
+    let now = now_timestamp().expect("should get time");
+    let network = &state.networks[0];
+    let mut batch_hash = None;
+    for _ in 0..50 {
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        batch_hash = flush_batch(&state, network, now)
+            .await
+            .expect("flush should succeed");
+        if batch_hash.is_some() {
+            break;
+        }
+    }
+    let batch_hash = batch_hash.expect("full batch should be sent");
+
+    for handle in handles {
+        let hash = handle
+            .await
+            .expect("task should finish")
+            .expect("claim should succeed")
+            .tx_hash;
+        assert_eq!(hash, batch_hash);
+    }
+    assert_eq!(chain.batches().len(), 1);
+    assert_eq!(chain.batches()[0].len(), 3);
+    // claims queued in the same second keep an entry each
+    let logs = get_logs(&state, "sepolia").expect("should get logs");
+    let mut inputs: Vec<&str> = logs
+        .iter()
+        .map(|entry| entry.value.input.as_str())
+        .collect();
+    inputs.sort();
+    assert_eq!(inputs, ["alice@eth", "bobby@eth", "carol@eth"]);
+    assert!(logs.iter().all(|entry| entry.value.result == batch_hash
+        && entry.value.tx_status == Some(TxStatus::Pending)
+        && entry.value.wallet == MOCK_WALLET.to_string()));
+
+    // one receipt settles every claim of the batch
+    check_pending_claims(&state, now)
+        .await
+        .expect("watcher pass should succeed");
+    let logs = get_logs(&state, "sepolia").expect("should get logs");
+    assert!(
+        logs.iter()
+            .all(|entry| entry.value.tx_status == Some(TxStatus::Confirmed))
+    );
+}
+
+#[tokio::test]
+async fn test_batch_sent_after_interval() {
+    let chain = Arc::new(MockChain::new(FAUCET_LIMIT, 0.1).with_name("alice", "eth", alice()));
+    let (state, _dir) = batch_state(chain.clone());
+    let now = now_timestamp().expect("should get time");
+
+    let pending = {
+        let state = state.clone();
+        tokio::spawn(async move {
+            claim(
+                &state,
+                "alice@eth".to_string(),
+                String::new(),
+                String::new(),
+                ClaimProof::default(),
+            )
+            .await
+        })
+    };
+    let network = &state.networks[0];
+    while state.db.queue_len("sepolia").expect("should get") == 0 {
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    }
+    let logs = get_logs(&state, "sepolia").expect("should get logs");
+    assert_eq!(logs[0].value.tx_status, Some(TxStatus::Queued));
+
+    assert_eq!(
+        flush_batch(&state, network, now)
+            .await
+            .expect("flush should succeed"),
+        None,
+        "A lone claim waits for the interval"
+    );
+    let batch_hash = flush_batch(&state, network, now + 60)
+        .await
+        .expect("flush should succeed")
+        .expect("due batch should be sent");
+    assert_eq!(
+        pending
+            .await
+            .expect("task should finish")
+            .expect("claim should succeed")
+            .tx_hash,
+        batch_hash
+    );
+    assert_eq!(chain.sent(), vec![(alice(), chain.sent()[0].1)]);
+
+    // networks without a disperse contract pay right away
+    claim(
+        &state,
+        "alice@eth".to_string(),
+        "hoodi".to_string(),
+        String::new(),
+        ClaimProof::default(),
+    )
+    .await
+    .expect("hoodi claim should succeed");
+    assert_eq!(chain.batches().len(), 1);
+    assert_eq!(chain.sent().len(), 2);
+}
+
+#[tokio::test]
+async fn test_failing_batch_gives_up() {
+    let chain = Arc::new(MockChain::new(FAUCET_LIMIT, 0.1).with_name("alice", "eth", alice()));
+    let (mut state, _dir) = batch_state(chain.clone());
+    state.curve.adjustment = 0.0;
+    state.budget.daily_wei = U256::from(2 * FAUCET_LIMIT);
+    let now = now_timestamp().expect("should get time");
+    let budget = get_budget_remaining(&state, "sepolia")
+        .expect("should get budget")
+        .today;
+
+    chain.set_fail_sends(true);
+    let pending = {
+        let state = state.clone();
+        tokio::spawn(async move {
+            claim(
+                &state,
+                "alice@eth".to_string(),
+                String::new(),
+                String::new(),
+                ClaimProof::default(),
+            )
+            .await
+        })
+    };
+    let network = &state.networks[0];
+    while state.db.queue_len("sepolia").expect("should get") == 0 {
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    }
+
+    // retried with the next flushes, then given up on
+    for attempt in 1..=5 {
+        flush_batch(&state, network, now + 60)
+            .await
+            .expect_err("failing batch should be refused");
+        let logs = get_logs(&state, "sepolia").expect("should get logs");
+        let status = match attempt {
+            5 => TxStatus::Failed,
+            _ => TxStatus::Queued,
+        };
+        assert_eq!(logs[0].value.tx_status, Some(status));
+    }
+    assert_eq!(state.db.queue_len("sepolia").expect("should get"), 0);
+    let err = pending
+        .await
+        .expect("task should finish")
+        .expect_err("failed batch should be refused");
+    assert!(err.to_string().contains("couldn't be sent"));
+
+    // the claim's cooldown and budget share are given back
+    assert_eq!(
+        get_budget_remaining(&state, "sepolia")
+            .expect("should get budget")
+            .today,
+        budget
+    );
+    chain.set_fail_sends(false);
+    let pending = {
+        let state = state.clone();
+        tokio::spawn(async move {
+            claim(
+                &state,
+                "alice@eth".to_string(),
+                String::new(),
+                String::new(),
+                ClaimProof::default(),
+            )
+            .await
+        })
+    };
+    while state.db.queue_len("sepolia").expect("should get") == 0 {
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    }
+    let batch_hash = flush_batch(&state, network, now + 120)
+        .await
+        .expect("flush should succeed")
+        .expect("due batch should be sent");
+    assert_eq!(
+        pending
+            .await
+            .expect("task should finish")
+            .expect("claim should succeed")
+            .tx_hash,
+        batch_hash
+    );
+}
+
+#[tokio::test]
+async fn test_claim_left_queued() {
+    let chain = Arc::new(MockChain::new(FAUCET_LIMIT, 0.1).with_name("alice", "eth", alice()));
+    let (mut state, _dir) = batch_state(chain.clone());
+    state.batch = Some(BatchConfig {
+        interval_sec: 0,
+        max_claims: 3,
+    });
+
+    // nothing flushes, the claimer gets the queued claim after the grace period
+    let result = claim(
+        &state,
+        "alice@eth".to_string(),
+        String::new(),
+        String::new(),
+        ClaimProof::default(),
+    )
+    .await
+    .expect("queued claim should succeed");
+    assert!(result.tx_hash.is_empty());
+    let key = result.log_key.expect("claim should be logged");
+    assert_eq!(
+        claim_status(&state, "sepolia", key)
+            .await
+            .expect("should get status"),
+        Some(TxStatus::Queued)
+    );
+
+    let now = now_timestamp().expect("should get time");
+    let batch_hash = flush_batch(&state, &state.networks[0], now)
+        .await
+        .expect("flush should succeed")
+        .expect("due batch should be sent");
+    let logs = get_logs(&state, "sepolia").expect("should get logs");
+    assert_eq!(logs[0].value.result, batch_hash);
+    assert_eq!(
+        claim_status(&state, "sepolia", key)
+            .await
+            .expect("should get status"),
+        Some(TxStatus::Confirmed)
+    );
+}
+
+const ETH: u128 = 1_000_000_000_000_000_000;
+
+fn low_funds_state(chain: Arc<MockChain>) -> (AppState, TempDir) {
+    let (mut state, dir) = mock_state(chain);
+    state.funds = FundsConfig {
+        low_water_wei: U256::from(10 * ETH),
+        floor_wei: U256::from(2 * ETH),
+    };
+    (state, dir)
+}
+
+#[tokio::test]
+async fn test_low_balance_scales_payout() {
+    let chain = Arc::new(MockChain::new(FAUCET_LIMIT, 0.1).with_name("alice", "eth", alice()));
+    let (state, _dir) = low_funds_state(chain.clone());
+
+    claim(
+        &state,
+        "alice@eth".to_string(),
+        "sepolia".to_string(),
+        String::new(),
+        ClaimProof::default(),
+    )
+    .await
+    .expect("sepolia claim should succeed");
+
+    // halfway between the floor and the low-water mark pays half
+    chain.set_balance(U256::from(6 * ETH));
+    claim(
+        &state,
+        "alice@eth".to_string(),
+        "hoodi".to_string(),
+        String::new(),
+        ClaimProof::default(),
+    )
+    .await
+    .expect("hoodi claim should succeed");
+
+    let sent = chain.sent();
+    assert_eq!(sent.len(), 2);
+    assert_eq!(sent[1].1, sent[0].1 / U256::from(2));
+}
+
+#[tokio::test]
+async fn test_claims_paused_at_floor() {
+    let chain = Arc::new(MockChain::new(FAUCET_LIMIT, 0.1).with_name("alice", "eth", alice()));
+    let (state, _dir) = low_funds_state(chain.clone());
+
+    chain.set_balance(U256::from(2 * ETH));
+    let range = get_payout_range(&state, "sepolia")
+        .await
+        .expect("should get payout range");
+    assert!(range.paused);
+    assert_eq!(range.claims_left, 0);
+    assert_eq!(range.balance, "2.000000000000000000");
+
+    for asset in ["", "usdc"] {
+        let err = claim(
+            &state,
+            "alice@eth".to_string(),
+            String::new(),
+            asset.to_string(),
+            ClaimProof::default(),
+        )
+        .await
+        .expect_err("claim at the floor should be refused");
+        assert!(err.to_string().contains("Faucet is running dry"));
+    }
+    assert!(chain.sent().is_empty());
+    let logs = get_logs(&state, "sepolia").expect("should get logs");
+    assert!(logs.iter().all(|log| log.key.status == 0));
+
+    // refilled, the refused claim didn't take the cooldown
+    chain.set_balance(U256::from(100 * ETH));
+    let range = get_payout_range(&state, "sepolia")
+        .await
+        .expect("should get payout range");
+    assert!(!range.paused);
+    let current: f64 = range.current.parse().expect("valid amount");
+    assert_eq!(range.claims_left, (98.0 / current) as u64);
+    claim(
+        &state,
+        "alice@eth".to_string(),
+        String::new(),
+        String::new(),
+        ClaimProof::default(),
+    )
+    .await
+    .expect("claim after a refill should succeed");
+    assert_eq!(chain.sent().len(), 1);
+}
+
+#[tokio::test]
+async fn test_demand_payout_shares_budget() {
+    let chain = Arc::new(MockChain::new(FAUCET_LIMIT, 0.1).with_name("alice", "eth", alice()));
+    let (mut state, _dir) = mock_state(chain.clone());
+    state.curve.mode = PayoutMode::Demand {
+        budget_wei: U256::from(8 * FAUCET_LIMIT),
+    };
+
+    // a quiet day pays the whole budget's share, capped at the curve's max
+    let range = get_payout_range(&state, "sepolia")
+        .await
+        .expect("should get payout range");
+    assert_eq!(range.current, "0.400000000000000000");
+    assert_eq!(range.min, "0.050000000000000000");
+    assert_eq!(range.max, "1.250000000000000000");
+
+    // three claims in the trailing day, the one before doesn't count
+    let now = now_timestamp().expect("timestamp");
+    for ts in [now - 90_000, now - 300, now - 200, now - 100] {
+        let log = LogValue {
+            input: "bob@eth".to_string(),
+            result: "0x1".to_string(),
+            asset: String::new(),
+            tx_status: Some(TxStatus::Confirmed),
+            replaced: Vec::new(),
+            wallet: String::new(),
+            prevented: None,
+        };
+        state
+            .db
+            .insert_k_v_logs("sepolia", ts, true, log)
+            .expect("should insert log");
+    }
+    claim(
+        &state,
+        "alice@eth".to_string(),
+        String::new(),
+        String::new(),
+        ClaimProof::default(),
+    )
+    .await
+    .expect("claim should succeed");
+    assert_eq!(chain.sent()[0].1, U256::from(2 * FAUCET_LIMIT));
+}
+
+#[tokio::test]
+async fn test_daily_budget_refuses_claims() {
+    let chain = Arc::new(
+        MockChain::new(FAUCET_LIMIT, 0.1)
+            .with_name("alice", "eth", alice())
+            .with_token(usdc(), U256::from(USDC_AMOUNT)),
+    );
+    let (mut state, _dir) = mock_state(chain.clone());
+    // base payouts only, no cooldown between them
+    state.curve.adjustment = 0.0;
+    state.cooldowns.clear();
+    state.budget.daily_wei = U256::from(2 * FAUCET_LIMIT);
+
+    let remaining = get_budget_remaining(&state, "sepolia").expect("should get budget");
+    assert_eq!(remaining.today.as_deref(), Some("0.100000000000000000"));
+    assert_eq!(remaining.this_hour, None);
+
+    // a failed send gives its share back
+    chain.set_fail_sends(true);
+    claim(
+        &state,
+        "alice@eth".to_string(),
+        String::new(),
+        String::new(),
+        ClaimProof::default(),
+    )
+    .await
+    .expect_err("failed send should be refused");
+    chain.set_fail_sends(false);
+    for _ in 0..2 {
+        claim(
+            &state,
+            "alice@eth".to_string(),
+            String::new(),
+            String::new(),
+            ClaimProof::default(),
+        )
+        .await
+        .expect("claim within the budget should succeed");
+    }
+    let err = claim(
+        &state,
+        "alice@eth".to_string(),
+        String::new(),
+        String::new(),
+        ClaimProof::default(),
+    )
+    .await
+    .expect_err("claim over the budget should be refused");
+    assert!(err.to_string().contains("daily budget is spent"));
+    assert_eq!(chain.sent().len(), 2);
+
+    let remaining = get_budget_remaining(&state, "sepolia").expect("should get budget");
+    assert_eq!(remaining.today.as_deref(), Some("0.000000000000000000"));
+    // token payouts don't count
+    claim(
+        &state,
+        "alice@eth".to_string(),
+        String::new(),
+        "usdc".to_string(),
+        ClaimProof::default(),
+    )
+    .await
+    .expect("token claim should succeed");
+    // other networks keep their own budget
+    claim(
+        &state,
+        "alice@eth".to_string(),
+        "hoodi".to_string(),
+        String::new(),
+        ClaimProof::default(),
+    )
+    .await
+    .expect("hoodi claim should succeed");
+}
+
+#[tokio::test]
+async fn test_community_policy() {
+    let bob = Address::with_last_byte(7);
+    let chain = Arc::new(
+        MockChain::new(FAUCET_LIMIT, 0.1)
+            .with_name("alice", "eth", alice())
+            .with_name("bob", "dao", bob)
+            .with_name("bob", "DAO", bob),
+    );
+    let (mut state, _dir) = mock_state(chain.clone());
+    state.curve.adjustment = 0.0;
+    state.communities = vec![CommunityPolicy {
+        id: "dao".to_string(),
+        multiplier: 2.0,
+        daily_quota: 2,
+        // no cooldown within the quota
+        cooldown_sec: Some(0),
+        deny: vec!["mallory".to_string()],
+        ..CommunityPolicy::default()
+    }];
+
+    for left in [1, 0] {
+        let result = claim(
+            &state,
+            "bob@dao".to_string(),
+            String::new(),
+            String::new(),
+            ClaimProof::default(),
+        )
+        .await
+        .expect("claim within the quota should succeed");
+        let terms = result.community.expect("dao has terms");
+        assert_eq!(terms.multiplier, 2.0);
+        assert_eq!(terms.claims_left_today, Some(left));
+    }
+    // resolved as typed, but the community's terms apply in any case, it's no way around the quota
+    let err = claim(
+        &state,
+        "bob@DAO".to_string(),
+        String::new(),
+        String::new(),
+        ClaimProof::default(),
+    )
+    .await
+    .expect_err("claim over the quota should be refused");
+    assert!(err.to_string().contains("used its 2 daily claims"));
+    let err = claim(
+        &state,
+        "mallory@dao".to_string(),
+        String::new(),
+        String::new(),
+        ClaimProof::default(),
+    )
+    .await
+    .expect_err("denied name should be refused");
+    assert!(err.to_string().contains("not allowed"));
+
+    // other communities keep the default terms
+    let result = claim(
+        &state,
+        "alice@eth".to_string(),
+        String::new(),
+        String::new(),
+        ClaimProof::default(),
+    )
+    .await
+    .expect("claim should succeed");
+    assert_eq!(result.community, None);
+
+    let sent = chain.sent();
+    assert_eq!(sent.len(), 3);
+    assert_eq!(sent[0], (bob, U256::from(2 * FAUCET_LIMIT)));
+    assert_eq!(sent[2], (alice(), U256::from(FAUCET_LIMIT)));
+}
+
+#[tokio::test]
+async fn test_denylist_blocks_claims() {
+    let chain = Arc::new(MockChain::new(FAUCET_LIMIT, 0.1).with_name("alice", "eth", alice()));
+    let (state, _dir) = mock_state(chain.clone());
+    // listed in any case, matched by the address the name resolves to
+    add_access_entry(
+        &state,
+        ADMIN_TOKEN,
+        AccessList::Deny,
+        &alice().to_string(),
+        "drained the faucet",
+    )
+    .expect("should add");
+
+    let err = claim(
+        &state,
+        "alice@eth".to_string(),
+        String::new(),
+        String::new(),
+        ClaimProof::default(),
+    )
+    .await
+    .expect_err("denylisted address should be refused");
+    assert!(
+        err.to_string()
+            .contains("blocked from this faucet: drained the faucet")
+    );
+    assert!(chain.sent().is_empty());
+    let logs = get_logs(&state, "sepolia").expect("should get logs");
+    assert_eq!(logs.len(), 1);
+    assert_eq!(logs[0].key.status, 0);
+    assert!(logs[0].value.result.contains("drained the faucet"));
+    assert_eq!(logs[0].value.prevented, Some(Prevention::Denylisted));
+
+    // names are refused before they're resolved
+    add_access_entry(&state, ADMIN_TOKEN, AccessList::Deny, "Mallory@eth", "").expect("should add");
+    let err = claim(
+        &state,
+        "mallory@eth".to_string(),
+        String::new(),
+        String::new(),
+        ClaimProof::default(),
+    )
+    .await
+    .expect_err("denylisted name should be refused");
+    assert!(err.to_string().contains("blocked from this faucet!"));
+
+    assert!(
+        remove_access_entry(&state, ADMIN_TOKEN, AccessList::Deny, &alice().to_string())
+            .expect("should remove")
+    );
+    claim(
+        &state,
+        "alice@eth".to_string(),
+        String::new(),
+        String::new(),
+        ClaimProof::default(),
+    )
+    .await
+    .expect("claim should succeed once removed");
+}
+
+#[tokio::test]
+async fn test_allowlist_mode() {
+    let bob = Address::with_last_byte(7);
+    let chain = Arc::new(
+        MockChain::new(FAUCET_LIMIT, 0.1)
+            .with_name("alice", "eth", alice())
+            .with_name("bob", "eth", bob),
+    );
+    let (mut state, _dir) = mock_state(chain.clone());
+    state.access_mode = AccessMode::Allowlist;
+    add_access_entry(&state, ADMIN_TOKEN, AccessList::Allow, "alice@eth", "team")
+        .expect("should add");
+    add_access_entry(
+        &state,
+        ADMIN_TOKEN,
+        AccessList::Allow,
+        &bob.to_string(),
+        "team",
+    )
+    .expect("should add");
+
+    // listed by name and by address
+    for name in ["alice@eth", "bob@eth"] {
+        claim(
+            &state,
+            name.to_string(),
+            String::new(),
+            String::new(),
+            ClaimProof::default(),
+        )
+        .await
+        .expect("allowlisted claim should succeed");
+    }
+    remove_access_entry(&state, ADMIN_TOKEN, AccessList::Allow, "alice@eth")
+        .expect("should remove");
+    let err = claim(
+        &state,
+        "alice@eth".to_string(),
+        "hoodi".to_string(),
+        String::new(),
+        ClaimProof::default(),
+    )
+    .await
+    .expect_err("unlisted claim should be refused");
+    assert!(err.to_string().contains("not on this faucet's allowlist"));
+    assert_eq!(chain.sent().len(), 2);
+    let logs = get_logs(&state, "hoodi").expect("should get logs");
+    assert_eq!(logs[0].value.prevented, Some(Prevention::NotAllowlisted));
+}
+
+#[test]
+fn test_admin_token() {
+    let chain = Arc::new(MockChain::new(FAUCET_LIMIT, 0.1));
+    let (mut state, _dir) = mock_state(chain);
+
+    let err = access_entries(&state, "0123456789abcdeF", AccessList::Deny)
+        .expect_err("wrong token should be refused");
+    assert!(err.to_string().contains("Invalid admin token"));
+    let err = add_access_entry(&state, ADMIN_TOKEN, AccessList::Deny, "alice", "")
+        .expect_err("entry should be an address or a name");
+    assert!(err.to_string().contains("neither an address nor a name"));
+    let added = add_access_entry(&state, ADMIN_TOKEN, AccessList::Deny, " Alice.ETH ", "")
+        .expect("should add an ENS name");
+    assert_eq!(added.key, "alice.eth");
+    assert!(
+        remove_access_entry(&state, ADMIN_TOKEN, AccessList::Deny, "alice.eth")
+            .expect("should remove")
+    );
+    let added = add_access_entry(
+        &state,
+        ADMIN_TOKEN,
+        AccessList::Deny,
+        &alice().to_string(),
+        " spam ",
+    )
+    .expect("should add");
+    assert_eq!(added.key, "0xf0e5d3cc05206987a125afc404b719e54fa942a8");
+    assert_eq!(added.reason, "spam");
+    assert_eq!(
+        access_entries(&state, ADMIN_TOKEN, AccessList::Deny).expect("should list"),
+        vec![added]
+    );
+
+    state.admin_token = None;
+    let err = access_entries(&state, ADMIN_TOKEN, AccessList::Deny)
+        .expect_err("admin functions should be disabled");
+    assert!(err.to_string().contains("disabled"));
+}
+
+#[tokio::test]
+async fn test_claim_needs_proof_of_work() {
+    let chain = Arc::new(MockChain::new(FAUCET_LIMIT, 0.1).with_name("alice", "eth", alice()));
+    let (mut state, _dir) = mock_state(chain.clone());
+    state.pow = Some(PowConfig {
+        difficulty: 4,
+        max_difficulty: 12,
+        demand_claims: 10,
+        ttl_sec: 300,
+        secret: b"0123456789abcdef".to_vec(),
+    });
+    let claim_with = |pow: Option<PowSolution>| {
+        claim(
+            &state,
+            "alice@eth".to_string(),
+            String::new(),
+            String::new(),
+            ClaimProof { pow, captcha: None },
+        )
+    };
+
+    let err = claim_with(None)
+        .await
+        .expect_err("claim without proof should be refused");
+    assert!(err.to_string().contains("proof of work is required"));
+
+    let solve = || {
+        let challenge = get_pow_challenge(&state, "")
+            .expect("should issue")
+            .expect("proof of work is on");
+        let nonce = (0..)
+            .find(|nonce| challenge.is_solved_by("alice@eth", *nonce))
+            .expect("should solve");
+        (challenge, nonce)
+    };
+    let (challenge, nonce) = solve();
+    assert_eq!(challenge.difficulty, 4);
+    let solution = PowSolution {
+        challenge: challenge.challenge.clone(),
+        nonce,
+    };
+    // the signature covers the difficulty
+    let tampered = PowSolution {
+        challenge: challenge.challenge.replacen(".4.", ".0.", 1),
+        nonce,
+    };
+    let err = claim_with(Some(tampered))
+        .await
+        .expect_err("tampered challenge should be refused");
+    assert!(err.to_string().contains("Invalid proof of work"));
+
+    // the proof is taken before anything reaches the chain, a refused claim uses it up
+    add_access_entry(&state, ADMIN_TOKEN, AccessList::Deny, "alice@eth", "").expect("should deny");
+    let err = claim_with(Some(solution.clone()))
+        .await
+        .expect_err("denied claim should be refused");
+    assert!(err.to_string().contains("blocked"));
+    let err = claim_with(Some(solution))
+        .await
+        .expect_err("replayed solution should be refused");
+    assert!(err.to_string().contains("already used"));
+    remove_access_entry(&state, ADMIN_TOKEN, AccessList::Deny, "alice@eth").expect("should remove");
+
+    let (challenge, nonce) = solve();
+    let solution = PowSolution {
+        challenge: challenge.challenge,
+        nonce,
+    };
+    claim_with(Some(solution.clone()))
+        .await
+        .expect("solved claim should succeed");
+    let err = claim_with(Some(solution))
+        .await
+        .expect_err("replayed solution should be refused");
+    assert!(err.to_string().contains("already used"));
+    assert_eq!(chain.sent().len(), 1);
+}
+
+#[test]
+fn test_pow_difficulty_follows_demand() {
+    let pow = PowConfig {
+        difficulty: 16,
+        max_difficulty: 20,
+        demand_claims: 10,
+        ttl_sec: 300,
+        secret: Vec::new(),
+    };
+    assert_eq!(pow.difficulty(0), 16);
+    assert_eq!(pow.difficulty(9), 16);
+    assert_eq!(pow.difficulty(10), 17);
+    assert_eq!(pow.difficulty(30), 18);
+    assert_eq!(pow.difficulty(10_000), 20);
+}
+
+#[tokio::test]
+async fn test_claim_needs_captcha() {
+    let chain = Arc::new(MockChain::new(FAUCET_LIMIT, 0.1).with_name("alice", "eth", alice()));
+    let (mut state, _dir) = mock_state(chain.clone());
+    let verify_url = mock::serve("secret", &["solved"])
+        .await
+        .expect("should serve");
+    state.captcha = Some(
+        CaptchaConfig {
+            provider: CaptchaProvider::Turnstile,
+            secret: "secret".to_string(),
+            site_key: "site".to_string(),
+            verify_url: Some(verify_url),
+        }
+        .captcha(),
+    );
+    let claim_with = |input: &str, captcha: Option<&str>| {
+        claim(
+            &state,
+            input.to_string(),
+            String::new(),
+            String::new(),
+            ClaimProof {
+                pow: None,
+                captcha: captcha.map(String::from),
+            },
+        )
+    };
+
+    let err = claim_with("alice@eth", None)
+        .await
+        .expect_err("claim without captcha should be refused");
+    assert!(err.to_string().contains("solve the captcha"));
+    // checked before the name is resolved
+    let err = claim_with("nobody@eth", Some("wrong"))
+        .await
+        .expect_err("wrong captcha should be refused");
+    assert!(err.to_string().contains("Captcha check failed"));
+
+    claim_with("alice@eth", Some("solved"))
+        .await
+        .expect("solved claim should succeed");
+    assert_eq!(chain.sent().len(), 1);
+    assert_eq!(
+        get_captcha_widget(&state).map(|w| w.site_key),
+        Some("site".to_string())
+    );
+}
+
+#[tokio::test]
+async fn test_claim_needs_mainnet_activity() {
+    let bob = Address::with_last_byte(0xb0);
+    let chain = Arc::new(
+        MockChain::new(FAUCET_LIMIT, 0.1)
+            .with_name("alice", "eth", alice())
+            .with_name("bob", "eth", bob)
+            .with_activity(alice(), U256::ZERO, 3),
+    );
+    let (mut state, _dir) = mock_state(chain.clone());
+    state.eligibility = EligibilityConfig {
+        min_balance_wei: U256::from(10_000_000_000_000_000u64),
+        min_nonce: 1,
+    };
+
+    let err = claim(
+        &state,
+        "bob@eth".to_string(),
+        String::new(),
+        String::new(),
+        ClaimProof::default(),
+    )
+    .await
+    .expect_err("fresh wallet should be refused");
+    assert!(err.to_string().ends_with(
+        "bob@eth is not eligible: 0x00000000000000000000000000000000000000b0 needs at least \
+         0.01 ETH or 1 transaction on mainnet, it has 0 ETH and 0 transactions"
+    ));
+
+    // either threshold will do
+    claim(
+        &state,
+        "alice@eth".to_string(),
+        String::new(),
+        String::new(),
+        ClaimProof::default(),
+    )
+    .await
+    .expect("used wallet should claim");
+    assert_eq!(chain.sent().len(), 1);
+}
+
+#[tokio::test]
+async fn test_claim_needs_primary_unexpired_name() {
+    let bob = Address::with_last_byte(0xb0);
+    let chain = Arc::new(
+        MockChain::new(FAUCET_LIMIT, 0.1)
+            .with_name("alice", "eth", alice())
+            .with_primary_name(alice(), "alice", "eth")
+            .with_name("bob", "eth", bob)
+            .with_primary_name(bob, "bob", "eth")
+            .with_name_expiry("bob", "eth", 1)
+            .with_name("carol", "eth", alice()),
+    );
+    let (mut state, _dir) = mock_state(chain.clone());
+    state.name_checks = NameChecks {
+        unexpired: true,
+        primary: true,
+    };
+    let claim_to = |input: &str| {
+        claim(
+            &state,
+            input.to_string(),
+            String::new(),
+            String::new(),
+            ClaimProof::default(),
+        )
+    };
+
+    let err = claim_to("bob@eth")
+        .await
+        .expect_err("expired name should be refused");
+    assert!(
+        err.to_string()
+            .ends_with("bob@eth expired, renew it to claim!")
+    );
+
+    // a second name of the same address
+    let err = claim_to("carol@eth")
+        .await
+        .expect_err("name that isn't primary should be refused");
+    assert!(err.to_string().ends_with(
+        "carol@eth is not the primary name of 0xf0e5d3cc05206987a125afc404b719e54fa942a8, \
+         set it as primary name to claim!"
+    ));
+    assert!(chain.sent().is_empty());
+
+    claim_to("alice@eth")
+        .await
+        .expect("primary name should claim");
+    assert_eq!(chain.sent().len(), 1);
+}
+
+#[tokio::test]
+async fn test_claim_sources() {
+    let bob = Address::with_last_byte(0xb0);
+    let carol = "0x00000000000000000000000000000000000000C0".to_string();
+    let chain = Arc::new(
+        MockChain::new(FAUCET_LIMIT, 0.1)
+            .with_name("alice", "eth", alice())
+            .with_ens("bob.eth", bob),
+    );
+    let (mut state, _dir) = mock_state(chain.clone());
+    state.curve.adjustment = 0.0;
+    let claim_to = |input: &str| {
+        claim(
+            &state,
+            input.to_string(),
+            String::new(),
+            String::new(),
+            ClaimProof::default(),
+        )
+    };
+
+    let err = claim_to(&carol)
+        .await
+        .expect_err("raw addresses should be off by default");
+    assert!(
+        err.to_string()
+            .contains("doesn't pay out to raw addresses, use 0xNAMEs")
+    );
+
+    state.sources = vec![
+        SourcePolicy {
+            source: ClaimSource::Ens,
+            multiplier: 1.0,
+        },
+        SourcePolicy {
+            source: ClaimSource::Address,
+            multiplier: 0.5,
+        },
+    ];
+    let claim_to = |input: &str| {
+        claim(
+            &state,
+            input.to_string(),
+            String::new(),
+            String::new(),
+            ClaimProof::default(),
+        )
+    };
+    claim_to(" Bob.eth ")
+        .await
+        .expect("ENS claim should succeed");
+    claim_to(&carol)
+        .await
+        .expect("address claim should succeed");
+    let err = claim_to("0xf0e5D3Cc05206987a125afC404b719e54Fa942a8")
+        .await
+        .expect_err("bad checksum should be refused");
+    assert!(err.to_string().contains("Invalid address checksum"));
+    let err = claim_to("alice@eth")
+        .await
+        .expect_err("0xNAMEs are off now");
+    assert!(err.to_string().contains("doesn't pay out to 0xNAMEs"));
+
+    let sent = chain.sent();
+    assert_eq!(
+        sent,
+        vec![
+            (bob, U256::from(FAUCET_LIMIT)),
+            (Address::with_last_byte(0xc0), U256::from(FAUCET_LIMIT / 2)),
+        ]
+    );
+}
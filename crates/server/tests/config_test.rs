@@ -0,0 +1,232 @@
+// to run from root directory cargo test -p server --features server --test config_test
+#![cfg(feature = "server")]
+
+use server::config::{Config, ConfigError};
+use server::curve::PayoutCurve;
+use server::state::{AssetKind, CooldownIdentity};
+use std::collections::HashMap;
+use zeroxname_ethereum::U256;
+use zeroxname_ethereum::pool::WalletStrategy;
+use zeroxname_ethereum::signer::SignerConfig;
+
+const KEY: &str = "7a28b5ba57c53603b0b07b56bba752f7784bf506fa95edc395f5cf6c7514fe9d";
+
+const TOML: &str = r#"
+rpc_mainnet = "https://eth.example.org"
+db_path = "/tmp/faucet-db"
+faucet_limit = 50000000000000000
+fee_threshold = 0.2
+cooldown_sec = 3600
+private_key = ["7a28b5ba57c53603b0b07b56bba752f7784bf506fa95edc395f5cf6c7514fe9d"]
+wallet_strategy = "most-funded"
+
+[networks.sepolia]
+rpc = "https://sepolia.example.org"
+
+[networks.sepolia.assets.usdc]
+address = "0x1c7D4B196Cb0C7B01d743Fbc6116a902379C7238"
+amount = "10000000"
+
+[networks.devnet]
+name = "Devnet"
+rpc = "http://localhost:8545"
+chain_id = 1337
+explorer_url = "http://localhost:4000"
+fee_threshold = 0.5
+
+[communities.dao]
+multiplier = 1.5
+daily_quota = 50
+deny = ["mallory", "trudy"]
+"#;
+
+fn env(vars: &[(&str, &str)]) -> HashMap<String, String> {
+    vars.iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect()
+}
+
+fn minimal_env() -> HashMap<String, String> {
+    env(&[
+        ("RPC_MAINNET", "https://eth.example.org"),
+        ("DB_PATH", "/tmp/faucet-db"),
+        ("FAUCET_LIMIT", "50000000000000000"),
+        ("PRIVATE_KEY", KEY),
+        ("RPC_SEPOLIA", "https://sepolia.example.org"),
+    ])
+}
+
+fn error_with(vars: &[(&str, &str)]) -> ConfigError {
+    let mut env = minimal_env();
+    env.extend(self::env(vars));
+    Config::parse("", env).expect_err("config should be rejected")
+}
+
+#[test]
+fn test_config_from_toml() {
+    let config = Config::parse(TOML, HashMap::new()).expect("valid config");
+    assert_eq!(config.db_path, "/tmp/faucet-db");
+    assert_eq!(config.wallet_strategy, WalletStrategy::MostFunded);
+    assert!(matches!(&config.signers[..], [SignerConfig::PrivateKey(key)] if key == KEY));
+    // defaults for what the file leaves out
+    assert_eq!(config.curve, PayoutCurve::default());
+    assert_eq!(config.receipt_timeout_sec, 600);
+    assert!(config.batch.is_none());
+    assert_eq!(config.cooldowns.len(), 1);
+    assert_eq!(config.cooldowns[0].identity, CooldownIdentity::Address);
+    assert_eq!(config.cooldowns[0].window_sec, 3600);
+
+    // tables keep their file order, the first network is the default
+    let ids: Vec<&str> = config
+        .networks
+        .iter()
+        .map(|n| n.config.id.as_str())
+        .collect();
+    assert_eq!(ids, ["sepolia", "devnet"]);
+    let sepolia = &config.networks[0];
+    assert_eq!(sepolia.config.chain_id, 11155111);
+    assert_eq!(sepolia.config.fee_threshold, 0.2);
+    assert_eq!(sepolia.assets.len(), 2);
+    assert_eq!(sepolia.assets[1].symbol, "USDC");
+    assert!(matches!(
+        sepolia.assets[1].kind,
+        AssetKind::Erc20 { amount, .. } if amount == U256::from(10_000_000)
+    ));
+    let devnet = &config.networks[1];
+    assert_eq!(devnet.config.name, "Devnet");
+    assert_eq!(devnet.config.chain_id, 1337);
+    assert_eq!(devnet.config.fee_threshold, 0.5);
+    assert_eq!(devnet.assets.len(), 1);
+
+    let dao = &config.communities[0];
+    assert_eq!(dao.id, "dao");
+    assert_eq!(dao.multiplier, 1.5);
+    assert_eq!(dao.daily_quota, 50);
+    assert!(dao.allow.is_empty());
+    assert!(!dao.admits("Trudy"));
+    assert!(dao.admits("bob"));
+}
+
+#[test]
+fn test_env_overrides_toml() {
+    let config = Config::parse(
+        TOML,
+        env(&[
+            ("FEE_THRESHOLD", "0.3"),
+            ("NETWORKS", "devnet"),
+            ("RPC_DEVNET", "http://devnet:8545"),
+            // empty vars don't hide the file
+            ("DB_PATH", ""),
+        ]),
+    )
+    .expect("valid config");
+    assert_eq!(config.db_path, "/tmp/faucet-db");
+    assert_eq!(config.networks.len(), 1);
+    assert_eq!(config.networks[0].config.rpc_url, "http://devnet:8545");
+    // the network's own threshold still wins over the global one
+    assert_eq!(config.networks[0].config.fee_threshold, 0.5);
+
+    let config = Config::parse("", minimal_env()).expect("env alone is enough");
+    assert_eq!(config.networks[0].config.id, "sepolia");
+}
+
+#[test]
+fn test_invalid_values_name_the_key() {
+    for (vars, key) in [
+        (&[("FEE_THRESHOLD", "0")][..], "FEE_THRESHOLD"),
+        (&[("FEE_THRESHOLD", "1.5")][..], "FEE_THRESHOLD"),
+        (
+            &[("FEE_THRESHOLD_SEPOLIA", "-0.1")][..],
+            "FEE_THRESHOLD_SEPOLIA",
+        ),
+        (&[("PAYOUT_ADJUSTMENT", "-1")][..], "PAYOUT_ADJUSTMENT"),
+        (&[("FAUCET_LIMIT", "lots")][..], "FAUCET_LIMIT"),
+        (&[("COOLDOWN_SEC", "-5")][..], "COOLDOWN_SEC"),
+        (&[("RPC_MAINNET", "not a url")][..], "RPC_MAINNET"),
+        (&[("WALLET_STRATEGY", "random")][..], "WALLET_STRATEGY"),
+        (&[("ACCESS_MODE", "closed")][..], "ACCESS_MODE"),
+        (&[("ADMIN_TOKEN", "hunter2")][..], "ADMIN_TOKEN"),
+        (&[("MIN_MAINNET_NONCE", "-1")][..], "MIN_MAINNET_NONCE"),
+        (
+            &[("CLAIM_SOURCES", "0xname,ens,0xname")][..],
+            "CLAIM_SOURCES",
+        ),
+        (
+            &[("CLAIM_SOURCES", "address"), ("ADDRESS_MULTIPLIER", "-0.5")][..],
+            "ADDRESS_MULTIPLIER",
+        ),
+        (
+            &[("RATE_LIMIT_CLAIM_BURST", "0")][..],
+            "RATE_LIMIT_CLAIM_BURST",
+        ),
+        (
+            &[("TRUSTED_PROXIES", "10.0.0.1,proxy")][..],
+            "TRUSTED_PROXIES",
+        ),
+        (&[("POW_DIFFICULTY", "65")][..], "POW_DIFFICULTY"),
+        (&[("POW_DIFFICULTY", "4294967295")][..], "POW_DIFFICULTY"),
+        (&[("CAPTCHA_PROVIDER", "recaptcha")][..], "CAPTCHA_PROVIDER"),
+        (
+            &[
+                ("CAPTCHA_PROVIDER", "hcaptcha"),
+                ("CAPTCHA_SECRET", "secret"),
+            ][..],
+            "CAPTCHA_SITE_KEY",
+        ),
+        (&[("PAYOUT_STEP_SEC", "0")][..], "PAYOUT_STEP_SEC"),
+        (
+            &[("PAYOUT_CURVE", "steps"), ("PAYOUT_STEPS", "0,2,1")][..],
+            "PAYOUT_STEPS",
+        ),
+        (
+            &[("PAYOUT_CURVE", "exponential"), ("PAYOUT_CURVE_RATE", "1")][..],
+            "PAYOUT_CURVE_RATE",
+        ),
+        (
+            &[("LOW_BALANCE_WEI", "10"), ("MIN_BALANCE_WEI", "20")][..],
+            "LOW_BALANCE_WEI",
+        ),
+        (&[("NETWORKS", "sepolia,sepolia")][..], "NETWORKS"),
+        (&[("NETWORKS", "Mainnet")][..], "NETWORKS"),
+        (
+            &[("COMMUNITIES", "dao"), ("COMMUNITY_MULTIPLIER_DAO", "-2")][..],
+            "COMMUNITY_MULTIPLIER_DAO",
+        ),
+        (
+            &[("ASSETS_SEPOLIA", "usdc")][..],
+            "TOKEN_ADDRESS_SEPOLIA_USDC",
+        ),
+    ] {
+        assert_eq!(error_with(vars).key, key, "{:?}", vars);
+    }
+    let err = error_with(&[("FEE_THRESHOLD", "1.5")]);
+    assert_eq!(
+        err.to_string(),
+        "Config error in FEE_THRESHOLD: should be in (0, 1]"
+    );
+}
+
+#[test]
+fn test_pow_max_difficulty_is_capped() {
+    let mut env = minimal_env();
+    env.insert("POW_DIFFICULTY".to_string(), "60".to_string());
+    let config = Config::parse("", env).expect("valid config");
+    let pow = config.pow.expect("proof of work is on");
+    assert_eq!((pow.difficulty, pow.max_difficulty), (60, 64));
+}
+
+#[test]
+fn test_missing_and_unknown_keys() {
+    let mut env = minimal_env();
+    env.remove("DB_PATH");
+    let err = Config::parse("", env).expect_err("DB_PATH is required");
+    assert_eq!(err, ConfigError::new("DB_PATH", "is required"));
+
+    let err = Config::parse("fee_treshold = 0.1", minimal_env()).expect_err("typo");
+    assert_eq!(err.key, "fee_treshold");
+    let err = Config::parse("[networks.sepolia]\nrpc_url = \"http://x\"", minimal_env())
+        .expect_err("typo");
+    assert_eq!(err.key, "networks.sepolia.rpc_url");
+    let err = Config::parse("db_path = [", minimal_env()).expect_err("broken toml");
+    assert_eq!(err.key, "config file");
+}
@@ -0,0 +1,87 @@
+// to run from root directory cargo test -p server --features server --test curve_test
+#![cfg(feature = "server")]
+
+use server::curve::{CurveShape, PayoutCurve};
+use zeroxname_ethereum::U256;
+
+const HOUR: u64 = 3600;
+
+fn curve(shape: CurveShape, adjustment: f64) -> PayoutCurve {
+    PayoutCurve {
+        shape,
+        adjustment,
+        ..PayoutCurve::default()
+    }
+}
+
+#[test]
+fn test_linear_curve() {
+    let curve = curve(CurveShape::Linear, 0.5);
+    assert_eq!(curve.steps(), 24);
+    assert_eq!(curve.min(), 0.0);
+    assert_eq!(curve.max(), 12.0);
+    // partial steps don't count
+    assert_eq!(curve.coefficient(HOUR - 1), 0.0);
+    assert_eq!(curve.coefficient(10 * HOUR), 5.0);
+    // capped at the period
+    assert_eq!(curve.coefficient(100 * HOUR), curve.max());
+}
+
+#[test]
+fn test_exponential_curve() {
+    let curve = curve(CurveShape::Exponential { rate: 0.2 }, 1.0);
+    let linear = self::curve(CurveShape::Linear, 1.0);
+    assert_eq!(curve.min(), 0.0);
+    assert!((curve.max() - linear.max()).abs() < 1e-9);
+    assert_eq!(curve.coefficient(100 * HOUR), curve.max());
+    // ahead of the linear curve in between, and still growing
+    let mut last = curve.min();
+    for step in 1..24 {
+        let coefficient = curve.coefficient(step * HOUR);
+        assert!(coefficient > linear.coefficient(step * HOUR));
+        assert!(coefficient > last);
+        last = coefficient;
+    }
+}
+
+#[test]
+fn test_step_table_curve() {
+    let curve = curve(CurveShape::Steps(vec![0.0, 0.5, 1.0, 4.0]), 2.0);
+    assert_eq!(curve.min(), 0.0);
+    // the last entry holds once the table runs out
+    assert_eq!(curve.max(), 8.0);
+    assert_eq!(curve.coefficient(HOUR), 1.0);
+    assert_eq!(curve.coefficient(3 * HOUR), 8.0);
+    assert_eq!(curve.coefficient(20 * HOUR), 8.0);
+
+    // a table can start above 0
+    let curve = self::curve(CurveShape::Steps(vec![1.0]), 1.0);
+    assert_eq!(curve.min(), 1.0);
+    assert_eq!(curve.max(), 1.0);
+}
+
+#[test]
+fn test_zero_adjustment_pays_base() {
+    for shape in [
+        CurveShape::Linear,
+        CurveShape::Exponential { rate: 0.5 },
+        CurveShape::Steps(vec![0.0, 3.0]),
+    ] {
+        let curve = curve(shape, 0.0);
+        assert_eq!(curve.min(), 0.0);
+        assert_eq!(curve.max(), 0.0);
+    }
+}
+
+#[test]
+fn test_demand_coefficient() {
+    let curve = curve(CurveShape::Linear, 0.25);
+    let base = 1_000;
+    let budget = U256::from(12_000);
+    // the budget over the claims so far plus this one
+    assert_eq!(curve.demand_coefficient(2, base, budget), 3.0);
+    assert_eq!(curve.demand_coefficient(5, base, budget), 1.0);
+    // never below the curve's min or above its max
+    assert_eq!(curve.demand_coefficient(100, base, budget), curve.min());
+    assert_eq!(curve.demand_coefficient(0, base, budget), curve.max());
+}
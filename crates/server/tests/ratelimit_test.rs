@@ -0,0 +1,151 @@
+// to run from root directory cargo test -p server --features server --test ratelimit_test
+#![cfg(feature = "server")]
+
+use dioxus::prelude::ServerFnError;
+use dioxus::prelude::server_fn::error::ServerFnErrorSerde;
+use server::ratelimit::{Limited, RateLimit, RateLimitConfig, RateLimiter};
+use shared_types::RateLimited;
+use std::net::IpAddr;
+
+const NOW: u64 = 1755907200;
+
+fn ip(ip: &str) -> IpAddr {
+    ip.parse().expect("valid ip")
+}
+
+fn limiter() -> RateLimiter {
+    RateLimiter::new(RateLimitConfig {
+        claim: Some(RateLimit {
+            per_min: 6,
+            burst: 2,
+        }),
+        payout: Some(RateLimit {
+            per_min: 60,
+            burst: 1,
+        }),
+        logs: None,
+        status: None,
+        challenge: None,
+        admin: Some(RateLimit {
+            per_min: 10,
+            burst: 2,
+        }),
+        trusted_proxies: vec![ip("10.0.0.1"), ip("10.0.0.2")],
+    })
+}
+
+#[test]
+fn test_bucket_refills() {
+    let limiter = limiter();
+    let client = ip("203.0.113.7");
+
+    for _ in 0..2 {
+        limiter
+            .check("/api/claim", client, NOW)
+            .expect("burst should pass");
+    }
+    // one token every 10 seconds
+    assert_eq!(
+        limiter.check("/api/claim", client, NOW + 1),
+        Err(RateLimited { retry_after_sec: 9 })
+    );
+    limiter
+        .check("/api/claim", client, NOW + 10)
+        .expect("refilled token should pass");
+    assert!(limiter.check("/api/claim", client, NOW + 10).is_err());
+
+    // the client reads the response body back into the typed error
+    let limited = RateLimited { retry_after_sec: 9 };
+    assert_eq!(limited.to_string(), "Too many requests, try again in 9s");
+    let body = ServerFnError::WrappedServerError(limited)
+        .ser()
+        .expect("should serialize");
+    assert_eq!(
+        ServerFnError::<RateLimited>::de(&body),
+        ServerFnError::WrappedServerError(limited)
+    );
+}
+
+#[test]
+fn test_limited_paths() {
+    for (path, limited) in [
+        ("/api/claim", Some(Limited::Claim)),
+        ("/api/payout", Some(Limited::Payout)),
+        ("/api/logs", Some(Limited::Logs)),
+        ("/api/claim_status", Some(Limited::Status)),
+        ("/api/challenge", Some(Limited::Challenge)),
+        ("/api/admin/add", Some(Limited::Admin)),
+        ("/api/admin/list", Some(Limited::Admin)),
+        ("/api/networks", None),
+        ("/api/claimx", None),
+    ] {
+        assert_eq!(Limited::from_path(path), limited, "{}", path);
+    }
+}
+
+#[test]
+fn test_admin_functions_share_a_bucket() {
+    let limiter = limiter();
+    let client = ip("203.0.113.7");
+
+    limiter
+        .check("/api/admin/add", client, NOW)
+        .expect("burst should pass");
+    limiter
+        .check("/api/admin/remove", client, NOW)
+        .expect("burst should pass");
+    assert!(
+        limiter.check("/api/admin/list", client, NOW).is_err(),
+        "token guesses across admin functions count together"
+    );
+}
+
+#[test]
+fn test_limits_are_apart() {
+    let limiter = limiter();
+    let client = ip("203.0.113.7");
+
+    limiter
+        .check("/api/payout", client, NOW)
+        .expect("first request should pass");
+    assert!(limiter.check("/api/payout", client, NOW).is_err());
+    // other functions, other clients and unlimited paths have their own buckets
+    limiter
+        .check("/api/claim", client, NOW)
+        .expect("claim should pass");
+    limiter
+        .check("/api/payout", ip("203.0.113.8"), NOW)
+        .expect("other client should pass");
+    for _ in 0..100 {
+        limiter
+            .check("/api/logs", client, NOW)
+            .expect("logs are unlimited");
+        limiter
+            .check("/api/networks", client, NOW)
+            .expect("unlisted paths are unlimited");
+    }
+}
+
+#[test]
+fn test_client_ip_behind_trusted_proxies() {
+    let limiter = limiter();
+    let client = ip("203.0.113.7");
+
+    // untrusted peers can't pick their address
+    assert_eq!(limiter.client_ip(client, Some("198.51.100.1")), client);
+    assert_eq!(
+        limiter.client_ip(ip("10.0.0.1"), Some("203.0.113.7")),
+        client
+    );
+    // spoofed hops left of the first untrusted address don't count
+    assert_eq!(
+        limiter.client_ip(ip("10.0.0.1"), Some("198.51.100.1, 203.0.113.7, 10.0.0.2")),
+        client
+    );
+    // a trusted proxy without the header is the client
+    assert_eq!(limiter.client_ip(ip("10.0.0.1"), None), ip("10.0.0.1"));
+    assert_eq!(
+        limiter.client_ip(ip("10.0.0.1"), Some("garbage")),
+        ip("10.0.0.1")
+    );
+}
@@ -1,20 +1,286 @@
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::str::FromStr;
+
+mod target;
+pub use target::{ClaimSource, ClaimTarget, ClaimTargetError};
+
+// asset id of the network's own coin, every network dispenses it
+pub const NATIVE_ASSET: &str = "eth";
 
 #[derive(Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct LogValue {
     pub input: String,
     pub result: String,
+    // empty for native ETH claims and entries written before token payouts
+    #[serde(default)]
+    pub asset: String,
+    // receipt of the payout, None for refused claims and entries written before tracking
+    #[serde(default)]
+    pub tx_status: Option<TxStatus>,
+    // stuck transactions `result` has replaced, oldest first
+    #[serde(default)]
+    pub replaced: Vec<TxReplacement>,
+    // faucet wallet that signed the payout, empty until it's sent and for entries written
+    // before the wallet pool
+    #[serde(default)]
+    pub wallet: String,
+    // the faucet's rule that refused the claim. None for payouts, failed claims and entries
+    // written before it was recorded
+    #[serde(default)]
+    pub prevented: Option<Prevention>,
+}
+
+// native payout waiting for the next batch
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct QueuedPayout {
+    pub receiver: String,
+    // wei, decimal
+    pub amount: String,
+    // log entry of the claim, updated with the batch tx hash
+    pub log_key: LogKey,
+    pub queued_at: u64,
+    // claim slots (key, previous claim) and community quota reserved for it,
+    // given back if the batch is never sent
+    #[serde(default)]
+    pub claim_slots: Vec<(String, Option<u64>)>,
+    #[serde(default)]
+    pub quota: Option<String>,
+    // failed sends of the batch it's in
+    #[serde(default)]
+    pub attempts: u32,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct TxReplacement {
+    pub tx_hash: String,
+    pub timestamp: u64,
+}
+
+// payout transaction lifecycle
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum TxStatus {
+    // waiting in the batch queue, not sent yet
+    Queued,
+    Pending,
+    Confirmed,
+    // reverted, or not mined within the receipt timeout
+    Failed,
+}
+
+// a claim refused by the faucet's rules rather than failed
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum Prevention {
+    // the faucet balance is at the floor
+    Paused,
+    // the name's community doesn't admit it
+    Community,
+    Denylisted,
+    NotAllowlisted,
+    // no address for the name
+    Unresolved,
+    Expired,
+    NotPrimary,
+    // not enough mainnet activity
+    Ineligible,
+    Cooldown,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct LogEntry {
-    pub key: (u64, u8),
+    pub key: LogKey,
     pub value: LogValue,
 }
 
+// entries are ordered by timestamp, claims logged in the same second tell apart by `seq`
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct LogKey {
+    pub timestamp: u64,
+    // 1 for sent or queued payouts, 0 for refused claims
+    pub status: u8,
+    // 0 for entries written before it
+    #[serde(default)]
+    pub seq: u64,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct PayoutRange {
     pub min: String,
     pub current: String,
     pub max: String,
+    // faucet wallet balance, in ETH
+    pub balance: String,
+    // claims the balance still covers at the current payout
+    pub claims_left: u64,
+    // balance is at the floor, claims are refused
+    pub paused: bool,
+}
+
+// anti-bot proofs a claim carries, each is only checked where the faucet asks for it
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ClaimProof {
+    pub pow: Option<PowSolution>,
+    // the widget's response token
+    pub captcha: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CaptchaProvider {
+    HCaptcha,
+    Turnstile,
+}
+
+// what the claim form needs to render the faucet's captcha widget
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CaptchaWidget {
+    pub provider: CaptchaProvider,
+    pub site_key: String,
+}
+
+// hashcash-style proof of work asked for before a claim, signed and checked by the server
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PowChallenge {
+    pub challenge: String,
+    // leading zero bits the solution's hash needs
+    pub difficulty: u32,
+    pub expires_at: u64,
+}
+
+impl PowChallenge {
+    // solutions are bound to the claim input, they can't be reused for another name
+    pub fn is_solved_by(&self, input: &str, nonce: u64) -> bool {
+        pow_zero_bits(&self.challenge, input, nonce) >= self.difficulty
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PowSolution {
+    pub challenge: String,
+    pub nonce: u64,
+}
+
+// leading zero bits of sha256(challenge, input, nonce)
+pub fn pow_zero_bits(challenge: &str, input: &str, nonce: u64) -> u32 {
+    let hash = Sha256::new()
+        .chain_update(challenge)
+        .chain_update([0])
+        .chain_update(input)
+        .chain_update([0])
+        .chain_update(nonce.to_be_bytes())
+        .finalize();
+    let mut bits = 0;
+    for byte in hash {
+        bits += byte.leading_zeros();
+        if byte != 0 {
+            break;
+        }
+    }
+    bits
+}
+
+// a paid claim: its transaction, and the terms of the claimer's community if it has any
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ClaimResult {
+    // empty while the claim is still queued for a batch
+    pub tx_hash: String,
+    // where the claim was logged, its status is looked up by it. None if it couldn't be logged
+    pub log_key: Option<LogKey>,
+    pub community: Option<CommunityTerms>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CommunityTerms {
+    pub id: String,
+    // native payouts are multiplied by it
+    pub multiplier: f64,
+    // None without a daily quota
+    pub claims_left_today: Option<u64>,
+}
+
+// what's left of a network's native payout budget, in ETH. None where there's no limit
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct BudgetRemaining {
+    pub today: Option<String>,
+    pub this_hour: Option<String>,
+}
+
+// recipient lists kept by the admin. the denylist always applies, the allowlist only with
+// ACCESS_MODE=allowlist
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AccessList {
+    Allow,
+    Deny,
+}
+
+impl AccessList {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Allow => "allow",
+            Self::Deny => "deny",
+        }
+    }
+}
+
+impl FromStr for AccessList {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "allow" => Ok(Self::Allow),
+            "deny" => Ok(Self::Deny),
+            _ => Err(format!("unknown list '{}', expected allow or deny", s)),
+        }
+    }
+}
+
+// an address in lower case hex or a name like alice@eth, and why it was listed
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct AccessEntry {
+    pub key: String,
+    pub reason: String,
+}
+
+// a request the rate limiter refused, the custom error of the rate limited server functions.
+// server functions send custom errors as text, FromStr reads it back
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct RateLimited {
+    pub retry_after_sec: u64,
+}
+
+const RATE_LIMITED: &str = "Too many requests, try again in ";
+
+impl fmt::Display for RateLimited {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}s", RATE_LIMITED, self.retry_after_sec)
+    }
+}
+
+impl FromStr for RateLimited {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let retry_after_sec = s
+            .strip_prefix(RATE_LIMITED)
+            .and_then(|rest| rest.strip_suffix('s'))
+            .and_then(|secs| secs.parse().ok())
+            .ok_or(())?;
+        Ok(Self { retry_after_sec })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct NetworkInfo {
+    pub id: String,
+    pub name: String,
+    pub explorer_url: String,
+    pub assets: Vec<AssetInfo>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AssetInfo {
+    pub id: String,
+    pub symbol: String,
 }
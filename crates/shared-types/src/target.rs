@@ -0,0 +1,205 @@
+// what a claim is sent to, parsed the same way by the claim form and the server
+use std::fmt;
+use std::str::FromStr;
+use tiny_keccak::{Hasher, Keccak};
+
+// a source of claims, each with its own policy on the server
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ClaimSource {
+    ZeroxName,
+    Ens,
+    Address,
+}
+
+impl ClaimSource {
+    pub const ALL: [Self; 3] = [Self::ZeroxName, Self::Ens, Self::Address];
+
+    // config name, CLAIM_SOURCES lists these
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::ZeroxName => "0xname",
+            Self::Ens => "ens",
+            Self::Address => "address",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::ZeroxName => "0xNAMEs",
+            Self::Ens => "ENS names",
+            Self::Address => "raw addresses",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ClaimTarget {
+    // name@community, kept as typed, the registry hashes it that way
+    ZeroxName { name: String, community: String },
+    // lower case ASCII, resolved on mainnet
+    Ens(String),
+    Address([u8; 20]),
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, thiserror::Error)]
+pub enum ClaimTargetError {
+    #[error("Type a 0xNAME like alice@eth, an ENS name like alice.eth or an address")]
+    Empty,
+    // whitespace, control and invisible characters, anywhere but around the input
+    #[error("Invalid character {0:?} in the input")]
+    InvalidChar(char),
+    #[error("Invalid 0xNAME: the name before '@' is 3-15 characters, not {0}")]
+    NameLength(usize),
+    #[error("Invalid 0xNAME: the tln after '@' is 3-10 characters, not {0}")]
+    CommunityLength(usize),
+    #[error("Invalid ENS name: only letters, digits and hyphens between the dots")]
+    Ens,
+    // names aren't ENSIP-15 normalized, unicode ones could resolve to another name's address
+    #[error("Only ASCII ENS names are supported, use the address of {0} instead")]
+    EnsNotAscii(String),
+    #[error("Invalid address checksum, copy the address again")]
+    Checksum,
+    #[error("Invalid input: use a 0xNAME like alice@eth, an ENS name like alice.eth or an address")]
+    Unknown,
+}
+
+// zero width, bidi and other format characters that would hide in a name
+fn is_invisible(c: char) -> bool {
+    matches!(
+        c,
+        '\u{00AD}'
+            | '\u{034F}'
+            | '\u{061C}'
+            | '\u{115F}'
+            | '\u{1160}'
+            | '\u{180E}'
+            | '\u{200B}'..='\u{200F}'
+            | '\u{202A}'..='\u{202E}'
+            | '\u{2060}'..='\u{206F}'
+            | '\u{3164}'
+            | '\u{FE00}'..='\u{FE0F}'
+            | '\u{FEFF}'
+            | '\u{FFA0}'
+    )
+}
+
+fn keccak256(data: &[u8]) -> [u8; 32] {
+    let mut hash = [0; 32];
+    let mut keccak = Keccak::v256();
+    keccak.update(data);
+    keccak.finalize(&mut hash);
+    hash
+}
+
+// EIP-55 mixed case hex, without the 0x
+fn checksum_hex(address: &[u8; 20]) -> String {
+    let hex: String = address.iter().map(|b| format!("{:02x}", b)).collect();
+    let hash = keccak256(hex.as_bytes());
+    hex.chars()
+        .enumerate()
+        .map(|(i, c)| {
+            let nibble = hash[i / 2] >> (4 * (1 - i % 2)) & 0xf;
+            match nibble >= 8 {
+                true => c.to_ascii_uppercase(),
+                false => c,
+            }
+        })
+        .collect()
+}
+
+fn parse_address(hex: &str) -> Result<ClaimTarget, ClaimTargetError> {
+    let mut address = [0; 20];
+    for (i, byte) in address.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[2 * i..2 * i + 2], 16)
+            .map_err(|_| ClaimTargetError::Unknown)?;
+    }
+    // single case addresses carry no checksum
+    let mixed_case =
+        hex.chars().any(|c| c.is_ascii_lowercase()) && hex.chars().any(|c| c.is_ascii_uppercase());
+    match !mixed_case || checksum_hex(&address) == hex {
+        true => Ok(ClaimTarget::Address(address)),
+        false => Err(ClaimTargetError::Checksum),
+    }
+}
+
+impl FromStr for ClaimTarget {
+    type Err = ClaimTargetError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let input = s.trim();
+        if input.is_empty() {
+            return Err(ClaimTargetError::Empty);
+        }
+        if let Some(c) = input
+            .chars()
+            .find(|c| c.is_whitespace() || c.is_control() || is_invisible(*c))
+        {
+            return Err(ClaimTargetError::InvalidChar(c));
+        }
+        if let Some(hex) = input.strip_prefix("0x")
+            && hex.len() == 40
+            && hex.chars().all(|c| c.is_ascii_hexdigit())
+        {
+            return parse_address(hex);
+        }
+        if let Some((name, community)) = input.split_once('@') {
+            if community.contains('@') {
+                return Err(ClaimTargetError::InvalidChar('@'));
+            }
+            let (name_len, community_len) = (name.chars().count(), community.chars().count());
+            if !(3..=15).contains(&name_len) {
+                return Err(ClaimTargetError::NameLength(name_len));
+            }
+            if !(3..=10).contains(&community_len) {
+                return Err(ClaimTargetError::CommunityLength(community_len));
+            }
+            return Ok(Self::ZeroxName {
+                name: name.to_string(),
+                community: community.to_string(),
+            });
+        }
+        let ens = input.to_ascii_lowercase();
+        if ens.ends_with(".eth") {
+            if !ens.is_ascii() {
+                return Err(ClaimTargetError::EnsNotAscii(input.to_string()));
+            }
+            let valid = ens.split('.').all(|label| {
+                !label.is_empty() && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+            });
+            return match valid && ens.len() <= 255 {
+                true => Ok(Self::Ens(ens)),
+                false => Err(ClaimTargetError::Ens),
+            };
+        }
+        Err(ClaimTargetError::Unknown)
+    }
+}
+
+// parses back to the same target, addresses are checksummed
+impl fmt::Display for ClaimTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ZeroxName { name, community } => write!(f, "{}@{}", name, community),
+            Self::Ens(name) => write!(f, "{}", name),
+            Self::Address(address) => write!(f, "0x{}", checksum_hex(address)),
+        }
+    }
+}
+
+impl ClaimTarget {
+    pub fn source(&self) -> ClaimSource {
+        match self {
+            Self::ZeroxName { .. } => ClaimSource::ZeroxName,
+            Self::Ens(_) => ClaimSource::Ens,
+            Self::Address(_) => ClaimSource::Address,
+        }
+    }
+
+    // ("", "") for the other sources
+    pub fn name_and_community(&self) -> (&str, &str) {
+        match self {
+            Self::ZeroxName { name, community } => (name, community),
+            _ => ("", ""),
+        }
+    }
+}
@@ -0,0 +1,137 @@
+// to run from root directory cargo test -p shared-types --test target_test
+
+use proptest::prelude::*;
+use shared_types::{ClaimSource, ClaimTarget, ClaimTargetError};
+
+const ADDRESS: &str = "0xf0E5D3Cc05206987a125afC404b719e54Fa942a8";
+
+fn parse(input: &str) -> Result<ClaimTarget, ClaimTargetError> {
+    input.parse()
+}
+
+#[test]
+fn test_parse_sources() {
+    assert_eq!(
+        parse(" alice@eth "),
+        Ok(ClaimTarget::ZeroxName {
+            name: "alice".to_string(),
+            community: "eth".to_string(),
+        })
+    );
+    // the registry hashes names as typed
+    assert_eq!(
+        parse("Alice@ETH"),
+        Ok(ClaimTarget::ZeroxName {
+            name: "Alice".to_string(),
+            community: "ETH".to_string(),
+        })
+    );
+    assert_eq!(
+        parse("Alice.ETH"),
+        Ok(ClaimTarget::Ens("alice.eth".to_string()))
+    );
+    for input in [
+        ADDRESS,
+        &ADDRESS.to_lowercase(),
+        &ADDRESS.to_uppercase().replacen("0X", "0x", 1),
+    ] {
+        let target = parse(input).expect("valid address");
+        assert_eq!(target.source(), ClaimSource::Address);
+        assert_eq!(target.to_string(), ADDRESS);
+    }
+}
+
+#[test]
+fn test_parse_errors() {
+    for (input, err) in [
+        ("", ClaimTargetError::Empty),
+        ("\u{3000}\t", ClaimTargetError::Empty),
+        (
+            "0xf0e5D3Cc05206987a125afC404b719e54Fa942a8",
+            ClaimTargetError::Checksum,
+        ),
+        ("al@eth", ClaimTargetError::NameLength(2)),
+        ("alice@", ClaimTargetError::CommunityLength(0)),
+        ("alice@e@th", ClaimTargetError::InvalidChar('@')),
+        ("ali ce@eth", ClaimTargetError::InvalidChar(' ')),
+        (
+            "ali\u{200B}ce@eth",
+            ClaimTargetError::InvalidChar('\u{200B}'),
+        ),
+        (
+            "alice@\u{202E}hte",
+            ClaimTargetError::InvalidChar('\u{202E}'),
+        ),
+        ("alice..eth", ClaimTargetError::Ens),
+        ("al_ice.eth", ClaimTargetError::Ens),
+        (
+            "алиса.eth",
+            ClaimTargetError::EnsNotAscii("алиса.eth".to_string()),
+        ),
+        (
+            "ali\u{0441}e.eth",
+            ClaimTargetError::EnsNotAscii("ali\u{0441}e.eth".to_string()),
+        ),
+        ("alice", ClaimTargetError::Unknown),
+        ("0x1234", ClaimTargetError::Unknown),
+    ] {
+        assert_eq!(parse(input), Err(err), "{:?}", input);
+    }
+    // lengths count characters, not bytes
+    assert!(parse("жук@эфир").is_ok());
+    assert_eq!(
+        parse("абвгдеёжзийклмно@eth"),
+        Err(ClaimTargetError::NameLength(16))
+    );
+}
+
+proptest! {
+    #[test]
+    fn prop_never_panics(input in "\\PC*") {
+        let _ = parse(&input);
+    }
+
+    #[test]
+    fn prop_display_parses_back(input in "\\PC{0,40}") {
+        if let Ok(target) = parse(&input) {
+            prop_assert_eq!(parse(&target.to_string()), Ok(target));
+        }
+    }
+
+    #[test]
+    fn prop_outer_whitespace_is_ignored(
+        input in "\\PC{0,30}",
+        before in "[ \t\r\n\u{00A0}\u{2003}\u{3000}]{0,3}",
+        after in "[ \t\r\n\u{00A0}\u{2003}\u{3000}]{0,3}",
+    ) {
+        prop_assert_eq!(parse(&format!("{}{}{}", before, input, after)), parse(&input));
+    }
+
+    #[test]
+    fn prop_hidden_characters_are_refused(
+        name in "[a-z]{3,7}",
+        community in "[a-z]{3,5}",
+        hidden in "[ \t\u{00A0}\u{00AD}\u{200B}\u{200D}\u{202E}\u{2060}\u{FEFF}]",
+        at in 1usize..3,
+    ) {
+        let input = format!("{}@{}", name, community);
+        let split = name.len() + at - 1;
+        let tampered = format!("{}{}{}", &input[..split], hidden, &input[split..]);
+        let c = hidden.chars().next().expect("one character");
+        prop_assert_eq!(parse(&tampered), Err(ClaimTargetError::InvalidChar(c)));
+    }
+
+    #[test]
+    fn prop_valid_names_parse(name in "[\\p{L}\\p{N}_]{3,15}", community in "[\\p{L}\\p{N}-]{3,10}") {
+        let target = parse(&format!("{}@{}", name, community));
+        prop_assert_eq!(target, Ok(ClaimTarget::ZeroxName { name, community }));
+    }
+
+    #[test]
+    fn prop_address_case(bytes in any::<[u8; 20]>()) {
+        let target = ClaimTarget::Address(bytes);
+        let checksummed = target.to_string();
+        prop_assert_eq!(parse(&checksummed.to_lowercase()), Ok(target.clone()));
+        prop_assert_eq!(parse(&checksummed), Ok(target));
+    }
+}
@@ -1,33 +1,155 @@
+use crate::network::{NetworkSelect, SelectedNetwork};
 use dioxus::prelude::*;
+use shared_types::{
+    CaptchaProvider, CaptchaWidget, ClaimProof, ClaimTarget, CommunityTerms, NATIVE_ASSET,
+    PowChallenge, PowSolution, TxStatus,
+};
 
 const CLAIM_CSS: Asset = asset!("/assets/styling/claim.css");
+// nonces tried between giving the browser a turn
+const POW_ROUND: u64 = 20_000;
 
 #[derive(PartialEq)]
 enum ResponseState {
     Loading,
+    // sent, waiting for the receipt
+    Pending,
     Success,
     Error,
     None,
 }
 
-fn is_valid_input(input: &str) -> bool {
-    input
-        .split_once('@')
-        .map_or(false, |(before_at, after_at)| {
-            (3..=15).contains(&before_at.len()) && (3..=10).contains(&after_at.len())
-        })
+// what the form shows of a claim. rendered as text, server messages echo the claimer's input
+#[derive(Clone, Default, PartialEq)]
+struct Response {
+    message: String,
+    // label and url, after the message
+    link: Option<(String, String)>,
+    // lines under it
+    notes: Vec<String>,
+}
+
+impl Response {
+    fn text(message: String) -> Self {
+        Self {
+            message,
+            ..Self::default()
+        }
+    }
+}
+
+// searches the nonce in rounds, so the page stays responsive while it works
+async fn solve_pow(challenge: &PowChallenge, input: &str) -> PowSolution {
+    let mut nonce = 0;
+    loop {
+        let round_end = nonce + POW_ROUND;
+        while nonce < round_end {
+            if challenge.is_solved_by(input, nonce) {
+                return PowSolution {
+                    challenge: challenge.challenge.clone(),
+                    nonce,
+                };
+            }
+            nonce += 1;
+        }
+        document::eval("await new Promise(resolve => setTimeout(resolve, 0));")
+            .await
+            .ok();
+    }
+}
+
+// script and global object of the provider's widget api, hCaptcha and Turnstile share its calls
+fn captcha_api(provider: CaptchaProvider) -> (&'static str, &'static str) {
+    match provider {
+        CaptchaProvider::HCaptcha => ("https://js.hcaptcha.com/1/api.js", "hcaptcha"),
+        CaptchaProvider::Turnstile => (
+            "https://challenges.cloudflare.com/turnstile/v0/api.js",
+            "turnstile",
+        ),
+    }
+}
+
+// loads the api once and renders the widget into #captcha
+fn render_captcha_js(widget: &CaptchaWidget) -> String {
+    let (src, api) = captcha_api(widget.provider);
+    format!(
+        r#"if (!window.{api}) {{
+            await new Promise(resolve => {{
+                window.faucetCaptchaLoaded = resolve;
+                const script = document.createElement("script");
+                script.src = "{src}?render=explicit&onload=faucetCaptchaLoaded";
+                script.async = true;
+                document.head.appendChild(script);
+            }});
+        }}
+        window.faucetCaptcha = {api}.render(document.getElementById("captcha"), {{ sitekey: {site_key:?} }});"#,
+        site_key = widget.site_key,
+    )
+}
+
+// the widget's token, empty until it's solved
+async fn captcha_token(widget: &CaptchaWidget) -> String {
+    let (_, api) = captcha_api(widget.provider);
+    document::eval(&format!(
+        "return window.{api} ? {api}.getResponse(window.faucetCaptcha) || '' : '';"
+    ))
+    .join::<String>()
+    .await
+    .unwrap_or_default()
+}
+
+// tokens are single use, the next claim needs a new one
+fn reset_captcha(widget: &CaptchaWidget) {
+    let (_, api) = captcha_api(widget.provider);
+    document::eval(&format!(
+        "window.{api} && {api}.reset(window.faucetCaptcha);"
+    ));
+}
+
+// the terms the claimer's community got
+fn community_note(terms: &CommunityTerms) -> String {
+    let mut note = format!("@{} community: {}x payout", terms.id, terms.multiplier);
+    if let Some(left) = terms.claims_left_today {
+        note.push_str(&format!(", {} claims left today", left));
+    }
+    note
 }
 
 #[component]
 pub fn Claim() -> Element {
-    let mut response = use_signal(|| String::new());
-    let mut name = use_signal(|| String::new());
+    let mut response = use_signal(Response::default);
+    let mut name = use_signal(String::new);
     let mut response_state = use_signal(|| ResponseState::None);
+    let network = use_context::<SelectedNetwork>();
+    let mut asset = use_signal(|| NATIVE_ASSET.to_string());
+    // None while the faucet doesn't ask for a captcha
+    let captcha =
+        use_resource(|| async move { server::shared::get_captcha_data().await.ok().flatten() });
 
     use_effect(move || {
         name.set(String::new());
     });
 
+    // assets differ between networks, start over from ETH
+    use_effect(move || {
+        network.0.read();
+        asset.set(NATIVE_ASSET.to_string());
+    });
+
+    // checked as it's typed, the server parses it the same way
+    let input_error = match name().trim() {
+        "" => None,
+        input => input.parse::<ClaimTarget>().err(),
+    };
+    let assets = network.assets();
+    let symbol = assets
+        .iter()
+        .find(|a| a.id == asset())
+        .map(|a| a.symbol.clone())
+        .unwrap_or("ETH".to_string());
+
+    let render_captcha = captcha().flatten().map(|widget| render_captcha_js(&widget));
+
     rsx! {
         document::Link { rel: "stylesheet", href: CLAIM_CSS }
 
@@ -36,77 +158,206 @@ pub fn Claim() -> Element {
             form {
                 onsubmit: move |_| async move {
                     response_state.set(ResponseState::Loading);
-                    if !is_valid_input(&name.to_string()) {
-                        response
-                            .set(
-                                "Invalid input: Use name@tln (name 3–15 chars, tln 3-10)."
-                                    .to_string(),
-                            );
-                        response_state.set(ResponseState::Error);
-                        return;
+                    let target = match name.to_string().parse::<ClaimTarget>() {
+                        Ok(target) => target,
+                        Err(e) => {
+                            response.set(Response::text(e.to_string()));
+                            response_state.set(ResponseState::Error);
+                            return;
+                        }
+                    };
+                    let explorer_url = network.explorer_url();
+                    let network_id = network.id();
+                    let widget = captcha().flatten();
+                    let mut proof = ClaimProof::default();
+                    if let Some(widget) = &widget {
+                        let token = captcha_token(widget).await;
+                        if token.is_empty() {
+                            response_state.set(ResponseState::Error);
+                            response.set(Response::text("Please solve the captcha first".to_string()));
+                            return;
+                        }
+                        proof.captcha = Some(token);
                     }
-                    match server::shared::claim_server(name.to_string()).await {
-                        Ok(data) => {
-                            response_state.set(ResponseState::Success);
+                    // the faucet may ask for proof of work too
+                    match server::shared::get_pow_challenge_data(network_id.clone()).await {
+                        Ok(Some(challenge)) => {
+                            proof.pow = Some(solve_pow(&challenge, &name.to_string()).await);
+                        }
+                        Ok(None) => {}
+                        Err(e) => {
+                            response_state.set(ResponseState::Error);
                             response
                                 .set(
-                                    format!(
-                                        "Sent! See tx on <a href='https://sepolia.etherscan.io/tx/{}' target='_blank'>Etherscan</a>",
-                                        data,
+                                    Response::text(
+                                        format!("Couldn't get the anti-bot challenge: {}", e),
                                     ),
                                 );
+                            return;
+                        }
+                    };
+                    let result = server::shared::claim_server(
+                            name.to_string(),
+                            network_id.clone(),
+                            asset(),
+                            proof,
+                        )
+                        .await;
+                    if let Some(widget) = &widget {
+                        reset_captcha(widget);
+                    }
+                    match result {
+                        Ok(result) => {
+                            let sent = |message: &str| Response {
+                                message: format!("{} See tx on", message),
+                                link: Some((
+                                    "Explorer".to_string(),
+                                    format!("{}/tx/{}", explorer_url, result.tx_hash),
+                                )),
+                                notes: result.community.iter().map(community_note).collect(),
+                            };
+                            // no hash yet, the batch it's queued for wasn't sent in time
+                            if result.tx_hash.is_empty() {
+                                response_state.set(ResponseState::Success);
+                                response
+                                    .set(Response {
+                                        message: "Queued! It will be sent with the next batch."
+                                            .to_string(),
+                                        link: None,
+                                        notes: result.community.iter().map(community_note).collect(),
+                                    });
+                                return;
+                            }
+                            let Some(log_key) = result.log_key else {
+                                response_state.set(ResponseState::Success);
+                                response.set(sent("Sent!"));
+                                return;
+                            };
+                            response_state.set(ResponseState::Pending);
+                            response.set(sent("Sent! Waiting for confirmation."));
+                            match server::shared::claim_status_server(log_key, network_id).await {
+                                Ok(Some(TxStatus::Failed)) => {
+                                    response_state.set(ResponseState::Error);
+                                    response.set(sent("Transaction failed!"));
+                                }
+                                Ok(Some(TxStatus::Confirmed)) => {
+                                    response_state.set(ResponseState::Success);
+                                    response.set(sent("Confirmed!"));
+                                }
+                                // still pending, the logs will show how it ends
+                                _ => {
+                                    response_state.set(ResponseState::Success);
+                                    response.set(sent("Sent!"));
+                                }
+                            }
                         }
                         Err(ServerFnError::ServerError(msg)) => {
                             response_state.set(ResponseState::Error);
-                            if msg.contains("Unable to resolve the name: No name found") {
-                                let clean_name = name
-                                    .to_string()
-                                    .split('@')
-                                    .next()
-                                    .unwrap_or_default()
-                                    .to_string();
-                                let suggested_name = format!("{}@eth", clean_name.to_lowercase());
+                            if let (true, ClaimTarget::ZeroxName { name, .. }) = (
+                                msg.contains("Unable to resolve the name: No name found"),
+                                &target,
+                            ) {
+                                let suggested_name = format!("{}@eth", name.to_lowercase());
                                 response
-                                    .set(
-                                        format!(
-                                            "{}. <br> <a href='https://app.0xname.foo/RegisterNameFinal/{}' target='_blank'> > GET FREE {} < </a>",
-                                            msg,
-                                            suggested_name,
-                                            suggested_name,
-                                        ),
-                                    );
+                                    .set(Response {
+                                        message: format!("{}.", msg),
+                                        link: Some((
+                                            format!("> GET FREE {} <", suggested_name),
+                                            format!(
+                                                "https://app.0xname.foo/RegisterNameFinal/{}",
+                                                suggested_name,
+                                            ),
+                                        )),
+                                        notes: Vec::new(),
+                                    });
+                            } else if msg.contains("is not eligible") {
+                                response
+                                    .set(Response {
+                                        message: format!("{}.", msg),
+                                        link: None,
+                                        notes: vec![
+                                            "The faucet only pays out to addresses that are in use on mainnet."
+                                                .to_string(),
+                                        ],
+                                    });
                             } else {
-                                response.set(msg);
+                                response.set(Response::text(msg));
                             }
                         }
+                        Err(ServerFnError::WrappedServerError(limited)) => {
+                            response_state.set(ResponseState::Error);
+                            response
+                                .set(
+                                    Response::text(
+                                        format!(
+                                            "Slow down! Too many claims from your network, try again in {} seconds.",
+                                            limited.retry_after_sec,
+                                        ),
+                                    ),
+                                );
+                        }
                         Err(_) => {
                             response_state.set(ResponseState::Error);
-                            response.set("An unexpected error occurred.".to_string());
+                            response.set(Response::text("An unexpected error occurred.".to_string()));
                         }
                     };
                 },
+                NetworkSelect {}
+                if assets.len() > 1 {
+                    select {
+                        id: "asset",
+                        onchange: move |event| asset.set(event.value()),
+                        for a in assets {
+                            option {
+                                value: "{a.id}",
+                                selected: a.id == asset(),
+                                "{a.symbol}"
+                            }
+                        }
+                    }
+                }
                 input {
-                    placeholder: "Type your 0xNAME like alice@eth",
+                    placeholder: "Type your 0xNAME, ENS name or address",
                     oninput: move |event| name.set(event.value()),
                     value: name,
                 }
+                if let Some(js) = render_captcha {
+                    div {
+                        id: "captcha",
+                        onmounted: move |_| {
+                            document::eval(&js);
+                        },
+                    }
+                }
                 button {
                     r#type: "submit",
-                    disabled: *response_state.read() == ResponseState::Loading,
-                    "Claim SepETH"
+                    disabled: *response_state.read() == ResponseState::Loading || input_error.is_some(),
+                    "Claim {network.name()} {symbol}"
                 }
             }
 
+            if let Some(error) = &input_error {
+                p { class: "error", "{error}" }
+            }
             if *response_state.read() == ResponseState::Loading {
                 p { class: "loading", "Processing your request..." }
-            } else if !response().is_empty() {
+            } else if !response().message.is_empty() {
                 p {
                     class: match *response_state.read() {
                         ResponseState::Success => "success",
+                        ResponseState::Pending => "loading",
                         ResponseState::Error => "error",
                         _ => "",
                     },
-                    dangerous_inner_html: "{response}",
+                    "{response().message}"
+                    if let Some((label, url)) = response().link {
+                        " "
+                        a { href: "{url}", target: "_blank", "{label}" }
+                    }
+                    for note in response().notes {
+                        br {}
+                        "{note}"
+                    }
                 }
             }
         }
@@ -3,8 +3,10 @@
 mod claim;
 mod faq;
 mod logs;
+mod network;
 mod payout;
 pub use claim::Claim;
 pub use faq::FAQ;
 pub use logs::Logs;
+pub use network::SelectedNetwork;
 pub use payout::Payout;
@@ -1,39 +1,61 @@
+use crate::network::{NetworkSelect, SelectedNetwork};
 use dioxus::prelude::*;
-use shared_types::LogEntry;
+use shared_types::{LogEntry, LogKey, TxStatus};
 const LOGS_CSS: Asset = asset!("/assets/styling/logs.css");
 use chrono::prelude::*;
 
 fn prepare_log(entry: &LogEntry) -> Element {
-    let (timestamp, status) = entry.key;
+    let LogKey {
+        timestamp, status, ..
+    } = entry.key;
     let result = &entry.value.result;
     let prefix = "error running server function:";
     let fmt_result = result.replace(prefix, "");
     //modify
-    let status_type = match status {
-        1 => "success".to_string(),
-        _ => {
-            if fmt_result.contains("Cooldown is not ended")
-                || fmt_result.contains("Unable to resolve the name: No name found")
-            {
-                "prevented".to_string()
-            } else {
-                "error".to_string()
-            }
-        }
+    let status_type = match (status, entry.value.tx_status) {
+        (1, Some(TxStatus::Queued)) => "queued",
+        (1, Some(TxStatus::Pending)) => "pending",
+        (1, Some(TxStatus::Failed)) => "failed",
+        (1, _) => "success",
+        // refused by the faucet's rules rather than failed
+        _ if entry.value.prevented.is_some() => "prevented",
+        _ => "error",
+    };
+
+    // token claims name their asset, native ETH claims don't
+    let asset = match entry.value.asset.as_str() {
+        "" => String::new(),
+        asset => format!(" {}", asset.to_uppercase()),
+    };
+
+    // stuck payouts resent with bumped fees
+    let replaced = match entry.value.replaced.len() {
+        0 => String::new(),
+        n => format!(", REPLACED: {}x", n),
+    };
+
+    // pool wallet that signed the payout, for audits and refills
+    let wallet = match entry.value.wallet.as_str() {
+        "" => String::new(),
+        wallet => format!(", FROM: {}", wallet),
     };
 
     let dt = Utc.timestamp_opt(timestamp as i64, 0).unwrap();
     rsx! {
         li {
             strong { class: "{status_type}", "{status_type}" }
-            span { " {dt} - INPUT: [{entry.value.input.to_string()}], RESULT: {fmt_result} " }
+            span { " {dt} - INPUT: [{entry.value.input.to_string()}{asset}], RESULT: {fmt_result}{replaced}{wallet} " }
         }
     }
 }
 
 #[component]
 pub fn Logs() -> Element {
-    let logs = use_resource(|| async move { server::shared::get_all_logs().await });
+    let network = use_context::<SelectedNetwork>();
+    let logs = use_resource(move || {
+        let id = network.id();
+        async move { server::shared::get_all_logs(id).await }
+    });
     let content = match &*logs.read_unchecked() {
         None => rsx! {
             li {
@@ -62,6 +84,7 @@ pub fn Logs() -> Element {
     rsx! {
         link { rel: "stylesheet", href: LOGS_CSS }
         div { id: "logs",
+            NetworkSelect {}
             ul { {content} }
         }
     }
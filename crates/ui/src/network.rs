@@ -0,0 +1,88 @@
+use dioxus::prelude::*;
+use shared_types::{AssetInfo, NATIVE_ASSET, NetworkInfo};
+
+const NETWORK_CSS: Asset = asset!("/assets/styling/network.css");
+
+// network picked in the selector, shared by Claim, Payout and Logs.
+// None until the network list is loaded, server functions then use the default network.
+#[derive(Clone, Copy)]
+pub struct SelectedNetwork(pub Signal<Option<NetworkInfo>>);
+
+impl SelectedNetwork {
+    pub fn id(&self) -> String {
+        self.0
+            .read()
+            .as_ref()
+            .map(|n| n.id.clone())
+            .unwrap_or_default()
+    }
+
+    pub fn name(&self) -> String {
+        self.0
+            .read()
+            .as_ref()
+            .map(|n| n.name.clone())
+            .unwrap_or("Sepolia".to_string())
+    }
+
+    pub fn explorer_url(&self) -> String {
+        self.0
+            .read()
+            .as_ref()
+            .map(|n| n.explorer_url.clone())
+            .unwrap_or("https://sepolia.etherscan.io".to_string())
+    }
+
+    // native ETH only until the network list is loaded
+    pub fn assets(&self) -> Vec<AssetInfo> {
+        self.0
+            .read()
+            .as_ref()
+            .map(|n| n.assets.clone())
+            .unwrap_or(vec![AssetInfo {
+                id: NATIVE_ASSET.to_string(),
+                symbol: "ETH".to_string(),
+            }])
+    }
+}
+
+#[component]
+pub fn NetworkSelect() -> Element {
+    let mut selected = use_context::<SelectedNetwork>().0;
+    let networks = use_resource(|| async move { server::shared::get_networks_data().await });
+
+    // default to the first configured network once the list is loaded
+    use_effect(move || {
+        if let Some(Ok(list)) = &*networks.read()
+            && selected.peek().is_none()
+        {
+            selected.set(list.first().cloned());
+        }
+    });
+
+    match &*networks.read() {
+        // nothing to pick from with a single network
+        Some(Ok(list)) if list.len() > 1 => {
+            let options = list.clone();
+            let list = list.clone();
+            rsx! {
+                document::Link { rel: "stylesheet", href: NETWORK_CSS }
+                select {
+                    id: "network",
+                    onchange: move |event| {
+                        let id = event.value();
+                        selected.set(list.iter().find(|n| n.id == id).cloned());
+                    },
+                    for network in options {
+                        option {
+                            value: "{network.id}",
+                            selected: selected.read().as_ref().is_some_and(|n| n.id == network.id),
+                            "{network.name}"
+                        }
+                    }
+                }
+            }
+        }
+        _ => rsx! {},
+    }
+}
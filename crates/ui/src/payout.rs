@@ -1,10 +1,20 @@
+use crate::network::SelectedNetwork;
 use dioxus::prelude::*;
 
 const PAYOUT_CSS: Asset = asset!("/assets/styling/payout.css");
 
 #[component]
 pub fn Payout() -> Element {
-    let payout_data = use_resource(|| async move { server::shared::get_payout_range_data().await });
+    let network = use_context::<SelectedNetwork>();
+    // re-fetches when another network is selected
+    let payout_data = use_resource(move || {
+        let id = network.id();
+        async move { server::shared::get_payout_range_data(id).await }
+    });
+    let budget_data = use_resource(move || {
+        let id = network.id();
+        async move { server::shared::get_budget_data(id).await }
+    });
 
     rsx! {
         document::Link { rel: "stylesheet", href: PAYOUT_CSS }
@@ -20,7 +30,7 @@ pub fn Payout() -> Element {
                         0.0
                     };
                     rsx! {
-                        h2 { "GET {current} SEPOLIA ETH NOW" }
+                        h2 { "GET {current} {network.name().to_uppercase()} ETH NOW" }
                         div { id: "labels",
                             h3 { "{min} ETH" }
                             h3 { "{max} ETH" }
@@ -28,6 +38,23 @@ pub fn Payout() -> Element {
                         div { id: "track",
                             div { id: "dot", style: "left: {pos}%;" }
                         }
+                        if data.paused {
+                            p { id: "funds", class: "paused",
+                                "Faucet is running dry, claims are paused until it's refilled"
+                            }
+                        } else {
+                            p { id: "funds",
+                                "Faucet balance: {data.balance} ETH, ~{data.claims_left} claims left"
+                            }
+                        }
+                        if let Some(Ok(budget)) = &*budget_data.read() {
+                            if let Some(today) = &budget.today {
+                                p { class: "budget", "Budget remaining today: {today} ETH" }
+                            }
+                            if let Some(this_hour) = &budget.this_hour {
+                                p { class: "budget", "Budget remaining this hour: {this_hour} ETH" }
+                            }
+                        }
                     }
                 }
                 Some(Err(err)) => rsx! {
@@ -3,6 +3,7 @@ use ui::Claim;
 use ui::FAQ;
 use ui::Logs;
 use ui::Payout;
+use ui::SelectedNetwork;
 
 const FAVICON: Asset = asset!("/assets/favicon.ico");
 const LOGO_IMAGE: Asset = asset!("/assets/logo.png");
@@ -48,10 +49,63 @@ fn LogsPage() -> Element {
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    dioxus::LaunchBuilder::new()
-        .with_context(server_only! {server::state::AppState::new()?})
-        .launch(App);
-    Ok(())
+    // validates the config and exits, without starting the server
+    #[cfg(feature = "server")]
+    if std::env::args().any(|arg| arg == "--check-config") {
+        let config = server::config::Config::load()?;
+        println!("config is valid, {} network(s)", config.networks.len());
+        return Ok(());
+    }
+    // manages the recipient lists of a running faucet
+    #[cfg(feature = "server")]
+    {
+        let args: Vec<String> = std::env::args().collect();
+        if let Some(at) = args.iter().position(|arg| arg == "--admin") {
+            println!("{}", server::admin::run_cli(&args[at + 1..])?);
+            return Ok(());
+        }
+    }
+    #[cfg(feature = "server")]
+    return serve();
+    #[cfg(not(feature = "server"))]
+    {
+        dioxus::launch(App);
+        Ok(())
+    }
+}
+
+// the fullstack server, with the rate limiter in front of the server functions
+#[cfg(feature = "server")]
+fn serve() -> Result<(), Box<dyn std::error::Error>> {
+    use server::ratelimit::{RateLimiter, rate_limit};
+    use std::any::Any;
+    use std::net::SocketAddr;
+    use std::sync::Arc;
+
+    let config = server::config::Config::load()?;
+    let limiter = RateLimiter::new(config.rate_limits.clone());
+    let state = server::state::AppState::from_config(config)?;
+    server::server::spawn_background_tasks(state.clone())?;
+    let context: Box<dyn Fn() -> Box<dyn Any> + Send + Sync> =
+        Box::new(move || Box::new(state.clone()));
+    tokio::runtime::Runtime::new()?.block_on(async move {
+        let router = axum::Router::new()
+            .serve_dioxus_application(
+                ServeConfig::builder().context_providers(Arc::new(vec![context])),
+                App,
+            )
+            .layer(axum::middleware::from_fn_with_state(limiter, rate_limit));
+        let listener =
+            tokio::net::TcpListener::bind(dioxus::cli_config::fullstack_address_or_localhost())
+                .await?;
+        // the limiter keys on the peer address
+        axum::serve(
+            listener,
+            router.into_make_service_with_connect_info::<SocketAddr>(),
+        )
+        .await?;
+        Ok::<_, Box<dyn std::error::Error>>(())
+    })
 }
 
 #[component]
@@ -124,6 +178,7 @@ fn Head() -> Element {
 
 #[component]
 fn App() -> Element {
+    use_context_provider(|| SelectedNetwork(Signal::new(None)));
     rsx! {
         Head {}
         Router::<Route> {}
@@ -1,28 +1,49 @@
+use Disperse::DisperseInstance;
+use IERC20::IERC20Instance;
 use NamesRegistry::NamesRegistryInstance;
 use alloy_dyn_abi::DynSolValue;
 use alloy_network::{EthereumWallet, TransactionBuilder};
 pub use alloy_primitives::Address;
 pub use alloy_primitives::ruint::aliases::U256;
 pub use alloy_primitives::utils::format_units;
-use alloy_primitives::{address, keccak256};
+use alloy_primitives::{B256, TxHash, address, keccak256};
 use alloy_provider::{
-    Identity, PendingTransactionError, Provider, ProviderBuilder, RootProvider,
+    Identity, PendingTransactionBuilder, PendingTransactionError, Provider, ProviderBuilder,
+    RootProvider, WatchTxError,
     fillers::{
         BlobGasFiller, ChainIdFiller, FillProvider, GasFiller, JoinFill, NonceFiller, WalletFiller,
     },
 };
-use alloy_rpc_types::TransactionRequest;
-use alloy_signer_local::{LocalSignerError, PrivateKeySigner};
+use alloy_rpc_types::{TransactionRequest, TransactionTrait};
+use alloy_signer_local::LocalSignerError;
 use alloy_sol_macro::*;
 use alloy_sol_types::*;
 use alloy_transport::{RpcError, TransportErrorKind};
+use async_trait::async_trait;
 
-use std::{str::FromStr, string::ParseError};
+use db::DB;
+use nonce::NonceManager;
+use pool::{HotWallet, WalletLoad, WalletStrategy, pick_wallet};
+use rebroadcast::{Eip1559Fees, bump_fees};
+use signer::FaucetSigner;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::{str::FromStr, string::ParseError, time::Duration};
 use thiserror::Error;
 
+#[cfg(feature = "mock")]
+pub mod mock;
+pub mod nonce;
+pub mod pool;
+pub mod rebroadcast;
+pub mod signer;
+
 pub static TX_GAS: u128 = 21000;
+// upper bound for a plain ERC-20 transfer
+pub static TOKEN_TX_GAS: u128 = 65000;
 pub static NAMES_REGISTRY_CONTRACT_ADDRESS: Address =
     address!("0x636518cb98F2F705082da540ba961E0A608C8220");
+pub static ENS_REGISTRY_ADDRESS: Address = address!("0x00000000000C2E074eC69A0dFb2997BA6C7d2e1e");
 
 #[derive(Debug, Error)]
 pub enum EthErrors {
@@ -40,6 +61,158 @@ pub enum EthErrors {
     InitSignerError(#[from] LocalSignerError),
     #[error("Error initing Provider: {0}")]
     InitParseURLError(#[from] ParseError),
+    #[error("Invalid transaction hash: {0}")]
+    TxHashError(String),
+    #[error("Transaction not found: {0}")]
+    TxNotFound(String),
+    #[error("Nonce ERROR: {0}")]
+    NonceError(String),
+    #[error("No disperse contract configured")]
+    NoDisperseContract,
+    #[error("Unknown wallet strategy: {0}, use round-robin, most-funded or least-pending")]
+    WalletStrategyError(String),
+    #[error("No faucet key configured")]
+    NoWallets,
+    #[error("Keystore ERROR: {0}")]
+    KeystoreError(String),
+    #[error("Signer ERROR: {0}")]
+    SignerError(String),
+    #[error("No faucet wallet holds enough funds")]
+    InsufficientFunds,
+}
+
+impl EthErrors {
+    // the transaction wasn't mined within the wait
+    pub fn is_tx_timeout(&self) -> bool {
+        matches!(
+            self,
+            EthErrors::PendingTxError(PendingTransactionError::TxWatcher(WatchTxError::Timeout))
+        )
+    }
+}
+
+// resolves 0xNAME names to addresses, on mainnet
+#[async_trait]
+pub trait NameResolver: Send + Sync {
+    async fn resolve_address(&self, name: &str, community: &str) -> Result<Address, EthErrors>;
+
+    // ENS name like alice.eth, already normalized
+    async fn resolve_ens(&self, name: &str) -> Result<Address, EthErrors>;
+
+    // owner and expiry of a registered 0xNAME
+    async fn name_record(&self, name: &str, community: &str) -> Result<NameRecord, EthErrors>;
+
+    // token id of the address's primary 0xNAME, its reverse record. None if it has none
+    async fn primary_token_id(&self, address: Address) -> Result<Option<U256>, EthErrors>;
+
+    // mainnet balance and nonce of an address, how much it was used
+    async fn mainnet_activity(&self, address: Address) -> Result<MainnetActivity, EthErrors>;
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct NameRecord {
+    pub owner: Address,
+    // unix seconds, 0 for names that don't expire
+    pub expires_at: u64,
+}
+
+impl NameRecord {
+    pub fn is_expired(&self, now: u64) -> bool {
+        self.expires_at != 0 && self.expires_at <= now
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct MainnetActivity {
+    pub balance: U256,
+    // transactions sent
+    pub nonce: u64,
+}
+
+// one testnet the faucet pays out on
+#[derive(Clone, Debug, PartialEq)]
+pub struct NetworkConfig {
+    pub id: String,
+    pub name: String,
+    pub rpc_url: String,
+    pub chain_id: u64,
+    pub faucet_limit: u64,
+    pub fee_threshold: f64,
+    pub explorer_url: String,
+    // multi-send contract for batched payouts, None sends every claim on its own
+    pub disperse_address: Option<Address>,
+}
+
+// a payout transaction and the pool wallet that signed it
+#[derive(Clone, Debug, PartialEq)]
+pub struct SentTx {
+    pub tx_hash: String,
+    pub wallet: Address,
+}
+
+// sends faucet payouts and prices them
+#[async_trait]
+pub trait PayoutSender: Send + Sync {
+    fn faucet_limit(&self) -> u64;
+
+    fn fee_threshold(&self) -> f64;
+
+    async fn send_eth(&self, receiver: Address, coefficient: f64) -> Result<SentTx, EthErrors>;
+
+    // one transaction paying every (receiver, value) through the disperse contract
+    async fn send_batch(&self, payouts: &[(Address, U256)]) -> Result<SentTx, EthErrors>;
+
+    // ERC-20 transfer of a fixed amount (in token base units) from the faucet wallet
+    async fn send_token(
+        &self,
+        token: Address,
+        receiver: Address,
+        amount: U256,
+    ) -> Result<SentTx, EthErrors>;
+
+    // ERC-20 token balance of the faucet, summed over its wallets
+    async fn token_balance(&self, token: Address) -> Result<U256, EthErrors>;
+
+    // native balance of the faucet in wei, summed over its wallets
+    async fn balance(&self) -> Result<U256, EthErrors>;
+
+    async fn total_gas_fee(&self) -> Result<u128, EthErrors>;
+
+    // waits up to `timeout` for the receipt, true if the transaction succeeded, false if it
+    // reverted. not mined in time is an error, see EthErrors::is_tx_timeout
+    async fn wait_for_receipt(&self, tx_hash: &str, timeout: Duration) -> Result<bool, EthErrors>;
+
+    // resends a stuck transaction with the same nonce and bumped fees, returns the new hash.
+    // None if it's already mined or the bumped fees go over max_fee_per_gas
+    async fn replace_tx(&self, tx_hash: &str) -> Result<Option<String>, EthErrors>;
+
+    // most a payout transaction may cost in fees, fee_threshold of the faucet limit
+    fn tx_cost_limit(&self) -> u128 {
+        (self.faucet_limit() as f64 * self.fee_threshold()) as u128
+    }
+
+    // most a payout may pay per gas, the price at which a plain transfer costs tx_cost_limit.
+    // batches use more gas for more payouts, they're held to the same price
+    fn max_fee_per_gas(&self) -> u128 {
+        self.tx_cost_limit() / TX_GAS
+    }
+
+    fn get_claim_amount(&self, coefficient: f64) -> U256 {
+        let base_value = self.faucet_limit() as f64;
+        U256::from(base_value + base_value * coefficient)
+    }
+
+    // compared per gas, a claim that goes out in a batch is checked the same way
+    async fn is_network_fee_ok(&self) -> Result<bool, EthErrors> {
+        let gas_price = self.total_gas_fee().await? / TX_GAS;
+        Ok(gas_price < self.max_fee_per_gas())
+    }
+
+    // same limit as for ETH, priced for the heavier token transfer
+    async fn is_token_fee_ok(&self) -> Result<bool, EthErrors> {
+        let total_gas_fee = self.total_gas_fee().await? / TX_GAS * TOKEN_TX_GAS;
+        Ok(total_gas_fee < self.tx_cost_limit())
+    }
 }
 
 sol!(
@@ -49,23 +222,62 @@ sol!(
     "assets/abis/NamesRegistry.json"
 );
 
+// disperse.app multi-send
+sol!(
+    #[allow(missing_docs)]
+    #[sol(rpc)]
+    interface Disperse {
+        function disperseEther(address[] recipients, uint256[] values) external payable;
+    }
+);
+
+sol!(
+    #[allow(missing_docs)]
+    #[sol(rpc)]
+    interface IERC20 {
+        function transfer(address to, uint256 amount) external returns (bool);
+        function balanceOf(address account) external view returns (uint256);
+    }
+);
+
+sol!(
+    #[allow(missing_docs)]
+    #[sol(rpc)]
+    interface EnsRegistry {
+        function resolver(bytes32 node) external view returns (address);
+    }
+);
+
+sol!(
+    #[allow(missing_docs)]
+    #[sol(rpc)]
+    interface EnsResolver {
+        function addr(bytes32 node) external view returns (address);
+    }
+);
+
 type DefaultFiller = JoinFill<
     Identity,
     JoinFill<GasFiller, JoinFill<BlobGasFiller, JoinFill<NonceFiller, ChainIdFiller>>>,
 >;
 
+// no NonceFiller, nonces come from the local NonceManager
 type DefaultFillerSender = JoinFill<
-    JoinFill<
-        Identity,
-        JoinFill<GasFiller, JoinFill<BlobGasFiller, JoinFill<NonceFiller, ChainIdFiller>>>,
-    >,
+    JoinFill<JoinFill<JoinFill<Identity, GasFiller>, BlobGasFiller>, ChainIdFiller>,
     WalletFiller<EthereumWallet>,
 >;
 
 #[derive(Clone, Debug)]
 pub struct ZeroxnameEthereum {
     names_registry: NamesRegistryInstance<(), FillProvider<DefaultFiller, RootProvider>>,
-    sepolia_sender: FillProvider<DefaultFillerSender, RootProvider>,
+    sender: FillProvider<DefaultFillerSender, RootProvider>,
+    // every key is registered with the sender, a tx is signed by the wallet in its `from`
+    wallets: Arc<Vec<HotWallet>>,
+    strategy: WalletStrategy,
+    // payouts so far, round robin turns
+    turn: Arc<AtomicUsize>,
+    disperse_address: Option<Address>,
+    chain_id: u64,
     faucet_limit: u64,
     fee_threshold: f64,
 }
@@ -78,30 +290,67 @@ fn encode_string_to_bytes32(name: &str, community: &str) -> Vec<u8> {
     name_at_community.abi_encode_packed()
 }
 
-fn hash(name: &str, community: &str) -> Result<U256, EthErrors> {
+fn is_nonce_too_low(e: &RpcError<TransportErrorKind>) -> bool {
+    e.as_error_resp()
+        .is_some_and(|resp| resp.message.to_lowercase().contains("nonce too low"))
+}
+
+fn parse_tx_hash(tx_hash: &str) -> Result<TxHash, EthErrors> {
+    TxHash::from_str(tx_hash).map_err(|_| EthErrors::TxHashError(tx_hash.to_string()))
+}
+
+// 0xNAME token id of name@community
+pub fn hash(name: &str, community: &str) -> Result<U256, EthErrors> {
     let encoded = encode_string_to_bytes32(name, community);
     let hash = keccak256(encoded);
-    let f: [u8; 32] = hash.try_into().map_err(|_| EthErrors::TokenIdError)?;
+    let f: [u8; 32] = hash.into();
     Ok(U256::from_be_bytes(f))
 }
 
+// ENS node of a normalized name, see EIP-137
+pub fn namehash(name: &str) -> B256 {
+    if name.is_empty() {
+        return B256::ZERO;
+    }
+    name.rsplit('.').fold(B256::ZERO, |node, label| {
+        keccak256([node.as_slice(), keccak256(label).as_slice()].concat())
+    })
+}
+
 //add error handling Result <Self,EthErrors>?
 impl ZeroxnameEthereum {
+    // the first signer is the pool's default one
     pub fn new(
         rpc_mainnet: &str,
-        rpc_sepolia: &str,
-        private_key: &str,
-        faucet_amount: u64,
-        fee_max: f64,
+        network: &NetworkConfig,
+        signers: &[FaucetSigner],
+        strategy: WalletStrategy,
+        db: DB,
     ) -> Result<Self, EthErrors> {
-        let signer =
-            PrivateKeySigner::from_str(private_key).map_err(|e| EthErrors::InitSignerError(e))?;
-
-        let wallet = EthereumWallet::from(signer);
+        let mut wallet: Option<EthereumWallet> = None;
+        let mut wallets = Vec::new();
+        for signer in signers.iter().cloned() {
+            let address = signer.address();
+            match wallet.as_mut() {
+                Some(wallet) => wallet.register_signer(signer),
+                None => wallet = Some(EthereumWallet::from(signer)),
+            }
+            let nonces = NonceManager::new(db.clone(), format!("{}:{}", network.id, address));
+            wallets.push(HotWallet::new(address, nonces));
+        }
+        let wallet = wallet.ok_or(EthErrors::NoWallets)?;
 
-        let sepolia_provider = ProviderBuilder::new()
+        let sender_provider = ProviderBuilder::default()
+            .with_gas_estimation()
+            .filler(BlobGasFiller)
+            .filler(ChainIdFiller::new(Some(network.chain_id)))
             .wallet(wallet)
-            .on_http(rpc_sepolia.parse().expect("Failed to parse sepolia URL"));
+            .on_http(
+                network
+                    .rpc_url
+                    .parse()
+                    .expect("Failed to parse network URL"),
+            );
 
         let provider = ProviderBuilder::new()
             .on_http(rpc_mainnet.parse().expect("Failed to parse mainnet URL"));
@@ -110,20 +359,137 @@ impl ZeroxnameEthereum {
             NamesRegistry::new(NAMES_REGISTRY_CONTRACT_ADDRESS, provider.clone());
         Ok(Self {
             names_registry: names_registry_instance,
-            sepolia_sender: sepolia_provider,
-            faucet_limit: faucet_amount,
-            fee_threshold: fee_max,
+            sender: sender_provider,
+            wallets: Arc::new(wallets),
+            strategy,
+            turn: Arc::default(),
+            disperse_address: network.disperse_address,
+            chain_id: network.chain_id,
+            faucet_limit: network.faucet_limit,
+            fee_threshold: network.fee_threshold,
         })
     }
+}
 
-    pub async fn resolve_address(&self, name: &str, community: &str) -> Result<Address, EthErrors> {
+impl ZeroxnameEthereum {
+    // sends from `wallet` with its next local nonce, a nonce the node didn't take is given back
+    async fn send_with_nonce(
+        &self,
+        wallet: &HotWallet,
+        tx: TransactionRequest,
+    ) -> Result<SentTx, EthErrors> {
+        let nonce = wallet
+            .nonces
+            .next(|| async {
+                self.sender
+                    .get_transaction_count(wallet.address)
+                    .pending()
+                    .await
+                    .map_err(EthErrors::RpcError)
+            })
+            .await?;
+        let tx = tx.with_from(wallet.address).with_nonce(nonce);
+        match self.sender.send_transaction(tx).await {
+            Ok(pending) => {
+                wallet.forget_balance();
+                Ok(SentTx {
+                    tx_hash: format!("{}", pending.tx_hash()),
+                    wallet: wallet.address,
+                })
+            }
+            Err(e) => {
+                wallet.nonces.release(nonce, is_nonce_too_low(&e))?;
+                Err(EthErrors::RpcError(e))
+            }
+        }
+    }
+
+    // the wallet to pay `amount` of `token` (None: native coin) from, by the pool strategy
+    async fn pick_wallet(
+        &self,
+        token: Option<Address>,
+        amount: U256,
+    ) -> Result<&HotWallet, EthErrors> {
+        let mut loads = Vec::with_capacity(self.wallets.len());
+        for wallet in self.wallets.iter() {
+            let balance = match token {
+                Some(token) => self.wallet_token_balance(wallet, token).await?,
+                None => self.wallet_balance(wallet).await?,
+            };
+            let pending = match self.strategy {
+                WalletStrategy::LeastPending => self.wallet_pending(wallet).await?,
+                _ => 0,
+            };
+            loads.push(WalletLoad { balance, pending });
+        }
+        let turn = self.turn.fetch_add(1, Ordering::Relaxed);
+        pick_wallet(self.strategy, &loads, amount, turn)
+            .map(|i| &self.wallets[i])
+            .ok_or(EthErrors::InsufficientFunds)
+    }
+
+    async fn wallet_balance(&self, wallet: &HotWallet) -> Result<U256, EthErrors> {
+        if let Some(balance) = wallet.cached_balance() {
+            return Ok(balance);
+        }
+        let balance = self.sender.get_balance(wallet.address).await?;
+        wallet.cache_balance(balance);
+        Ok(balance)
+    }
+
+    async fn wallet_token_balance(
+        &self,
+        wallet: &HotWallet,
+        token: Address,
+    ) -> Result<U256, EthErrors> {
+        Ok(self
+            .erc20(token)
+            .balanceOf(wallet.address)
+            .call()
+            .await
+            .map_err(EthErrors::ContractCallError)?
+            ._0)
+    }
+
+    // nonces handed out locally but not mined yet
+    async fn wallet_pending(&self, wallet: &HotWallet) -> Result<u64, EthErrors> {
+        let Some(next) = wallet.nonces.peek() else {
+            return Ok(0);
+        };
+        let mined = self
+            .sender
+            .get_transaction_count(wallet.address)
+            .latest()
+            .await
+            .map_err(EthErrors::RpcError)?;
+        Ok(next.saturating_sub(mined))
+    }
+
+    fn disperse(
+        &self,
+        address: Address,
+    ) -> DisperseInstance<(), &FillProvider<DefaultFillerSender, RootProvider>> {
+        Disperse::new(address, &self.sender)
+    }
+
+    fn erc20(
+        &self,
+        token: Address,
+    ) -> IERC20Instance<(), &FillProvider<DefaultFillerSender, RootProvider>> {
+        IERC20::new(token, &self.sender)
+    }
+}
+
+#[async_trait]
+impl NameResolver for ZeroxnameEthereum {
+    async fn resolve_address(&self, name: &str, community: &str) -> Result<Address, EthErrors> {
         let token_id = hash(name, community)?;
         let user_addr = self
             .names_registry
             .resolveAddressByTokenId(token_id)
             .call()
             .await
-            .map_err(|e| EthErrors::ContractCallError(e))?
+            .map_err(EthErrors::ContractCallError)?
             ._0;
 
         match user_addr.is_zero() {
@@ -132,41 +498,190 @@ impl ZeroxnameEthereum {
         }
     }
 
-    pub fn get_claim_amount(&self, coefficient: f64) -> U256 {
-        let base_value = self.faucet_limit as f64;
-        U256::from(base_value + base_value * coefficient)
+    async fn resolve_ens(&self, name: &str) -> Result<Address, EthErrors> {
+        let node = namehash(name);
+        let mainnet = self.names_registry.provider();
+        let resolver = EnsRegistry::new(ENS_REGISTRY_ADDRESS, mainnet)
+            .resolver(node)
+            .call()
+            .await?
+            ._0;
+        if resolver.is_zero() {
+            return Err(EthErrors::NameNotFound);
+        }
+        let addr = EnsResolver::new(resolver, mainnet)
+            .addr(node)
+            .call()
+            .await?
+            ._0;
+        match addr.is_zero() {
+            true => Err(EthErrors::NameNotFound),
+            _ => Ok(addr),
+        }
     }
 
-    pub async fn send_sepolia_eth(
-        &self,
-        receiver: Address,
-        coefficient: f64,
-    ) -> Result<String, EthErrors> {
+    async fn name_record(&self, name: &str, community: &str) -> Result<NameRecord, EthErrors> {
+        let token_id = hash(name, community)?;
+        let owner = self.names_registry.ownerOf(token_id).call().await?._0;
+        let expires_at = self.names_registry.expiresAt(token_id).call().await?._0;
+        Ok(NameRecord {
+            owner,
+            expires_at: u64::try_from(expires_at).unwrap_or(u64::MAX),
+        })
+    }
+
+    async fn primary_token_id(&self, address: Address) -> Result<Option<U256>, EthErrors> {
+        let token_id = self.names_registry.primaryTokenId(address).call().await?._0;
+        Ok((!token_id.is_zero()).then_some(token_id))
+    }
+
+    async fn mainnet_activity(&self, address: Address) -> Result<MainnetActivity, EthErrors> {
+        let mainnet = self.names_registry.provider();
+        Ok(MainnetActivity {
+            balance: mainnet.get_balance(address).await?,
+            nonce: mainnet.get_transaction_count(address).await?,
+        })
+    }
+}
+
+#[async_trait]
+impl PayoutSender for ZeroxnameEthereum {
+    fn faucet_limit(&self) -> u64 {
+        self.faucet_limit
+    }
+
+    fn fee_threshold(&self) -> f64 {
+        self.fee_threshold
+    }
+
+    async fn send_eth(&self, receiver: Address, coefficient: f64) -> Result<SentTx, EthErrors> {
         let value = self.get_claim_amount(coefficient);
+        let wallet = self.pick_wallet(None, value).await?;
+        // fixed chain id, a misconfigured RPC can't make the faucet sign for another chain
         let tx = TransactionRequest::default()
             .with_to(receiver)
-            .with_value(value);
+            .with_value(value)
+            .with_chain_id(self.chain_id);
 
-        let tx_hash = self
-            .sepolia_sender
-            .send_transaction(tx)
-            .await
-            .map_err(|e| EthErrors::RpcError(e))?;
-        Ok(format!("{}", tx_hash.tx_hash()))
+        self.send_with_nonce(wallet, tx).await
+    }
+
+    async fn send_batch(&self, payouts: &[(Address, U256)]) -> Result<SentTx, EthErrors> {
+        let disperse = self.disperse_address.ok_or(EthErrors::NoDisperseContract)?;
+        let (receivers, values): (Vec<Address>, Vec<U256>) = payouts.iter().copied().unzip();
+        let total: U256 = values.iter().sum();
+        let wallet = self.pick_wallet(None, total).await?;
+        let tx = self
+            .disperse(disperse)
+            .disperseEther(receivers, values)
+            .value(total)
+            .from(wallet.address)
+            .chain_id(self.chain_id)
+            .into_transaction_request();
+        self.send_with_nonce(wallet, tx).await
+    }
+
+    async fn send_token(
+        &self,
+        token: Address,
+        receiver: Address,
+        amount: U256,
+    ) -> Result<SentTx, EthErrors> {
+        let wallet = self.pick_wallet(Some(token), amount).await?;
+        let tx = self
+            .erc20(token)
+            .transfer(receiver, amount)
+            .from(wallet.address)
+            .chain_id(self.chain_id)
+            .into_transaction_request();
+        self.send_with_nonce(wallet, tx).await
+    }
+
+    async fn token_balance(&self, token: Address) -> Result<U256, EthErrors> {
+        let mut total = U256::ZERO;
+        for wallet in self.wallets.iter() {
+            total += self.wallet_token_balance(wallet, token).await?;
+        }
+        Ok(total)
+    }
+
+    async fn balance(&self) -> Result<U256, EthErrors> {
+        let mut total = U256::ZERO;
+        for wallet in self.wallets.iter() {
+            total += self.wallet_balance(wallet).await?;
+        }
+        Ok(total)
     }
 
     async fn total_gas_fee(&self) -> Result<u128, EthErrors> {
         let gas_price = self
-            .sepolia_sender
+            .sender
             .get_gas_price()
             .await
-            .map_err(|e| EthErrors::RpcError(e))?;
+            .map_err(EthErrors::RpcError)?;
         Ok(TX_GAS * gas_price)
     }
 
-    pub async fn is_network_fee_ok(&self) -> Result<bool, EthErrors> {
-        let tx_cost_limit = self.faucet_limit as f64 * self.fee_threshold;
-        let total_gas_fee = self.total_gas_fee().await?;
-        Ok(total_gas_fee < tx_cost_limit as u128)
+    async fn wait_for_receipt(&self, tx_hash: &str, timeout: Duration) -> Result<bool, EthErrors> {
+        let hash = parse_tx_hash(tx_hash)?;
+        let receipt = PendingTransactionBuilder::new(self.sender.root().clone(), hash)
+            .with_timeout(Some(timeout))
+            .get_receipt()
+            .await
+            .map_err(EthErrors::PendingTxError)?;
+        Ok(receipt.status())
+    }
+
+    async fn replace_tx(&self, tx_hash: &str) -> Result<Option<String>, EthErrors> {
+        let hash = parse_tx_hash(tx_hash)?;
+        let stuck = self
+            .sender
+            .get_transaction_by_hash(hash)
+            .await
+            .map_err(EthErrors::RpcError)?
+            .ok_or_else(|| EthErrors::TxNotFound(tx_hash.to_string()))?;
+        if stuck.block_number.is_some() {
+            return Ok(None);
+        }
+        let Some(to) = stuck.to() else {
+            return Ok(None);
+        };
+
+        let network = self
+            .sender
+            .estimate_eip1559_fees()
+            .await
+            .map_err(EthErrors::RpcError)?;
+        let Some(fees) = bump_fees(
+            Eip1559Fees {
+                max_fee_per_gas: stuck.max_fee_per_gas(),
+                max_priority_fee_per_gas: stuck.max_priority_fee_per_gas().unwrap_or_default(),
+            },
+            Eip1559Fees {
+                max_fee_per_gas: network.max_fee_per_gas,
+                max_priority_fee_per_gas: network.max_priority_fee_per_gas,
+            },
+            self.max_fee_per_gas(),
+        ) else {
+            return Ok(None);
+        };
+
+        let tx = TransactionRequest::default()
+            .with_to(to)
+            .with_value(stuck.value())
+            .with_input(stuck.input().clone())
+            .with_nonce(stuck.nonce())
+            .with_gas_limit(stuck.gas_limit())
+            .with_max_fee_per_gas(fees.max_fee_per_gas)
+            .with_max_priority_fee_per_gas(fees.max_priority_fee_per_gas)
+            .with_chain_id(self.chain_id)
+            // signed by the wallet that sent the stuck one
+            .with_from(stuck.inner.signer());
+        let tx_hash = self
+            .sender
+            .send_transaction(tx)
+            .await
+            .map_err(EthErrors::RpcError)?;
+        Ok(Some(format!("{}", tx_hash.tx_hash())))
     }
 }
@@ -0,0 +1,319 @@
+// in-memory chain backend, lets the faucet run without any RPC in tests. only built with the
+// mock feature
+use crate::rebroadcast::{Eip1559Fees, bump_fees};
+use crate::{
+    Address, EthErrors, MainnetActivity, NameRecord, NameResolver, PayoutSender, SentTx, TX_GAS,
+    U256, hash,
+};
+use alloy_primitives::{address, keccak256};
+use alloy_provider::{PendingTransactionError, WatchTxError};
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+// the one wallet every mock payout is signed by
+pub const MOCK_WALLET: Address = address!("0x00000000000000000000000000000000000000fa");
+
+// 1000 ETH, plenty for any test that doesn't set its own balance
+const MOCK_BALANCE: u128 = 1_000_000_000_000_000_000_000;
+
+#[derive(Debug, Default)]
+pub struct MockChain {
+    names: HashMap<U256, Address>,
+    // names that aren't listed never expire
+    expiries: HashMap<U256, u64>,
+    // reverse records
+    primary_names: HashMap<Address, U256>,
+    ens: HashMap<String, Address>,
+    // mainnet activity of an address, none by default
+    activity: HashMap<Address, MainnetActivity>,
+    faucet_limit: u64,
+    fee_threshold: f64,
+    gas_fee: Mutex<u128>,
+    fail_sends: AtomicBool,
+    // native balance, payouts are taken out of it
+    balance: Mutex<U256>,
+    sent: Mutex<Vec<(Address, U256)>>,
+    token_balances: Mutex<HashMap<Address, U256>>,
+    sent_tokens: Mutex<Vec<(Address, Address, U256)>>,
+    // receipt status of every sent tx, None is never mined
+    receipt: Mutex<Option<bool>>,
+    replaced: Mutex<Vec<String>>,
+    batches: Mutex<Vec<Vec<(Address, U256)>>>,
+}
+
+impl MockChain {
+    pub fn new(faucet_limit: u64, fee_threshold: f64) -> Self {
+        Self {
+            faucet_limit,
+            fee_threshold,
+            receipt: Mutex::new(Some(true)),
+            balance: Mutex::new(U256::from(MOCK_BALANCE)),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_name(mut self, name: &str, community: &str, addr: Address) -> Self {
+        let token_id = hash(name, community).expect("should hash name");
+        self.names.insert(token_id, addr);
+        self
+    }
+
+    pub fn with_name_expiry(mut self, name: &str, community: &str, expires_at: u64) -> Self {
+        let token_id = hash(name, community).expect("should hash name");
+        self.expiries.insert(token_id, expires_at);
+        self
+    }
+
+    pub fn with_primary_name(mut self, addr: Address, name: &str, community: &str) -> Self {
+        let token_id = hash(name, community).expect("should hash name");
+        self.primary_names.insert(addr, token_id);
+        self
+    }
+
+    pub fn with_ens(mut self, name: &str, addr: Address) -> Self {
+        self.ens.insert(name.to_string(), addr);
+        self
+    }
+
+    pub fn with_activity(mut self, addr: Address, balance: U256, nonce: u64) -> Self {
+        self.activity
+            .insert(addr, MainnetActivity { balance, nonce });
+        self
+    }
+
+    pub fn with_token(self, token: Address, balance: U256) -> Self {
+        self.set_token_balance(token, balance);
+        self
+    }
+
+    // faucet wallet holds `balance` of the token
+    pub fn set_token_balance(&self, token: Address, balance: U256) {
+        self.token_balances
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(token, balance);
+    }
+
+    // faucet wallet holds `balance` wei
+    pub fn set_balance(&self, balance: U256) {
+        *self.balance.lock().unwrap_or_else(|e| e.into_inner()) = balance;
+    }
+
+    pub fn set_gas_fee(&self, gas_fee: u128) {
+        *self.gas_fee.lock().unwrap_or_else(|e| e.into_inner()) = gas_fee;
+    }
+
+    // Some(true) mined, Some(false) reverted, None never mined
+    pub fn set_receipt(&self, receipt: Option<bool>) {
+        *self.receipt.lock().unwrap_or_else(|e| e.into_inner()) = receipt;
+    }
+
+    // every following send fails like a rejected RPC call
+    pub fn set_fail_sends(&self, fail: bool) {
+        self.fail_sends.store(fail, Ordering::SeqCst);
+    }
+
+    // (receiver, value) of every payout sent so far
+    pub fn sent(&self) -> Vec<(Address, U256)> {
+        self.sent.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    // (token, receiver, amount) of every token payout sent so far
+    pub fn sent_tokens(&self) -> Vec<(Address, Address, U256)> {
+        self.sent_tokens
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    // payouts of every batch sent so far, batched payouts are in sent() too
+    pub fn batches(&self) -> Vec<Vec<(Address, U256)>> {
+        self.batches
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    // hashes of the stuck transactions replaced so far
+    pub fn replaced(&self) -> Vec<String> {
+        self.replaced
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    fn spend(&self, value: U256) {
+        let mut balance = self.balance.lock().unwrap_or_else(|e| e.into_inner());
+        *balance = balance.saturating_sub(value);
+    }
+
+    fn check_fail(&self) -> Result<(), EthErrors> {
+        match self.fail_sends.load(Ordering::SeqCst) {
+            true => Err(EthErrors::RpcError(
+                alloy_transport::TransportErrorKind::custom_str("mock send failure"),
+            )),
+            false => Ok(()),
+        }
+    }
+}
+
+fn signed(tx_hash: String) -> SentTx {
+    SentTx {
+        tx_hash,
+        wallet: MOCK_WALLET,
+    }
+}
+
+#[async_trait]
+impl NameResolver for MockChain {
+    async fn resolve_address(&self, name: &str, community: &str) -> Result<Address, EthErrors> {
+        let token_id = hash(name, community)?;
+        self.names
+            .get(&token_id)
+            .copied()
+            .ok_or(EthErrors::NameNotFound)
+    }
+
+    async fn resolve_ens(&self, name: &str) -> Result<Address, EthErrors> {
+        self.ens.get(name).copied().ok_or(EthErrors::NameNotFound)
+    }
+
+    // names are owned by the address they resolve to
+    async fn name_record(&self, name: &str, community: &str) -> Result<NameRecord, EthErrors> {
+        let token_id = hash(name, community)?;
+        let owner = *self.names.get(&token_id).ok_or(EthErrors::NameNotFound)?;
+        Ok(NameRecord {
+            owner,
+            expires_at: self.expiries.get(&token_id).copied().unwrap_or_default(),
+        })
+    }
+
+    async fn primary_token_id(&self, address: Address) -> Result<Option<U256>, EthErrors> {
+        Ok(self.primary_names.get(&address).copied())
+    }
+
+    async fn mainnet_activity(&self, address: Address) -> Result<MainnetActivity, EthErrors> {
+        Ok(self.activity.get(&address).copied().unwrap_or_default())
+    }
+}
+
+#[async_trait]
+impl PayoutSender for MockChain {
+    fn faucet_limit(&self) -> u64 {
+        self.faucet_limit
+    }
+
+    fn fee_threshold(&self) -> f64 {
+        self.fee_threshold
+    }
+
+    async fn send_eth(&self, receiver: Address, coefficient: f64) -> Result<SentTx, EthErrors> {
+        self.check_fail()?;
+        let value = self.get_claim_amount(coefficient);
+        self.spend(value);
+        let mut sent = self.sent.lock().unwrap_or_else(|e| e.into_inner());
+        sent.push((receiver, value));
+        Ok(signed(format!(
+            "{}",
+            keccak256((sent.len() as u64).to_be_bytes())
+        )))
+    }
+
+    async fn send_batch(&self, payouts: &[(Address, U256)]) -> Result<SentTx, EthErrors> {
+        self.check_fail()?;
+        self.spend(payouts.iter().map(|(_, value)| *value).sum());
+        let mut batches = self.batches.lock().unwrap_or_else(|e| e.into_inner());
+        batches.push(payouts.to_vec());
+        self.sent
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .extend_from_slice(payouts);
+        Ok(signed(format!(
+            "{}",
+            keccak256(format!("batch{}", batches.len()).as_bytes())
+        )))
+    }
+
+    async fn send_token(
+        &self,
+        token: Address,
+        receiver: Address,
+        amount: U256,
+    ) -> Result<SentTx, EthErrors> {
+        self.check_fail()?;
+        let mut balances = self
+            .token_balances
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        let balance = balances.entry(token).or_default();
+        // a real transfer reverts, the mock refuses the same way
+        if *balance < amount {
+            return Err(EthErrors::RpcError(
+                alloy_transport::TransportErrorKind::custom_str("transfer amount exceeds balance"),
+            ));
+        }
+        *balance -= amount;
+        let mut sent = self.sent_tokens.lock().unwrap_or_else(|e| e.into_inner());
+        sent.push((token, receiver, amount));
+        Ok(signed(format!(
+            "{}",
+            keccak256((u64::MAX - sent.len() as u64).to_be_bytes())
+        )))
+    }
+
+    async fn token_balance(&self, token: Address) -> Result<U256, EthErrors> {
+        Ok(self
+            .token_balances
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&token)
+            .copied()
+            .unwrap_or_default())
+    }
+
+    async fn balance(&self) -> Result<U256, EthErrors> {
+        Ok(*self.balance.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+
+    async fn total_gas_fee(&self) -> Result<u128, EthErrors> {
+        Ok(*self.gas_fee.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+
+    async fn wait_for_receipt(
+        &self,
+        _tx_hash: &str,
+        _timeout: Duration,
+    ) -> Result<bool, EthErrors> {
+        let receipt = *self.receipt.lock().unwrap_or_else(|e| e.into_inner());
+        receipt.ok_or(EthErrors::PendingTxError(
+            PendingTransactionError::TxWatcher(WatchTxError::Timeout),
+        ))
+    }
+
+    // the stuck tx is taken to pay the current gas price, the replacement pays it bumped
+    async fn replace_tx(&self, tx_hash: &str) -> Result<Option<String>, EthErrors> {
+        self.check_fail()?;
+        let gas_fee = *self.gas_fee.lock().unwrap_or_else(|e| e.into_inner());
+        if bump_fees(
+            Eip1559Fees {
+                max_fee_per_gas: gas_fee / TX_GAS,
+                max_priority_fee_per_gas: 0,
+            },
+            Eip1559Fees {
+                max_fee_per_gas: 0,
+                max_priority_fee_per_gas: 0,
+            },
+            self.max_fee_per_gas(),
+        )
+        .is_none()
+        {
+            return Ok(None);
+        }
+        let mut replaced = self.replaced.lock().unwrap_or_else(|e| e.into_inner());
+        replaced.push(tx_hash.to_string());
+        Ok(Some(format!("{}", keccak256(tx_hash.as_bytes()))))
+    }
+}
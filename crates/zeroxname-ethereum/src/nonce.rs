@@ -0,0 +1,108 @@
+// local nonce assignment for a sending wallet.
+// the next nonce is kept in memory and persisted in the db, the node is only asked on startup
+// and after a gap, so concurrent payouts get consecutive nonces instead of racing the node.
+use crate::EthErrors;
+use db::DB;
+use std::sync::Mutex;
+
+#[derive(Debug, Default)]
+struct NonceState {
+    // None until synced with the chain
+    next: Option<u64>,
+    // a handed out nonce was never used, trust the chain over the stored value
+    gap: bool,
+}
+
+pub struct NonceManager {
+    db: DB,
+    key: String,
+    state: Mutex<NonceState>,
+}
+
+impl std::fmt::Debug for NonceManager {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NonceManager")
+            .field("key", &self.key)
+            .field("state", &self.state)
+            .finish()
+    }
+}
+
+impl NonceManager {
+    // `key` identifies the wallet on its network, e.g. "sepolia:0xf0e5..."
+    pub fn new(db: DB, key: String) -> Self {
+        Self {
+            db,
+            key,
+            state: Mutex::default(),
+        }
+    }
+
+    // hands out the next nonce. `chain_nonce` fetches the wallet's pending transaction count,
+    // it's only called when the local state has to be synced
+    pub async fn next<F, Fut>(&self, chain_nonce: F) -> Result<u64, EthErrors>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<u64, EthErrors>>,
+    {
+        if let Some(nonce) = self.take(None)? {
+            return Ok(nonce);
+        }
+        let chain = chain_nonce().await?;
+        self.take(Some(chain))?
+            .ok_or_else(|| EthErrors::NonceError("nonce not synced".to_string()))
+    }
+
+    // gives back a nonce whose transaction wasn't accepted by the node.
+    // `too_low` is the node saying the nonce is already used
+    pub fn release(&self, nonce: u64, too_low: bool) -> Result<(), EthErrors> {
+        let mut state = self.lock();
+        match state.next {
+            // nobody took a nonce after this one, just hand it out again
+            Some(next) if !too_low && next == nonce + 1 => self.store(&mut state, nonce),
+            // later nonces are in flight, or local and chain disagree: resync
+            _ => {
+                state.next = None;
+                state.gap = true;
+                Ok(())
+            }
+        }
+    }
+
+    // next nonce to hand out, None until synced with the chain
+    pub fn peek(&self) -> Option<u64> {
+        self.lock().next
+    }
+
+    // the stored value is only a lower bound, a node that lost its mempool or a wallet used
+    // elsewhere can move the chain ahead
+    fn take(&self, chain: Option<u64>) -> Result<Option<u64>, EthErrors> {
+        let mut state = self.lock();
+        let nonce = match (state.next, chain) {
+            (Some(next), _) => next,
+            (None, None) => return Ok(None),
+            (None, Some(chain)) if state.gap => chain,
+            (None, Some(chain)) => chain.max(self.stored()?.unwrap_or_default()),
+        };
+        state.gap = false;
+        self.store(&mut state, nonce + 1)?;
+        Ok(Some(nonce))
+    }
+
+    fn store(&self, state: &mut NonceState, next: u64) -> Result<(), EthErrors> {
+        state.next = Some(next);
+        self.db
+            .set_nonce(&self.key, next)
+            .map_err(|e| EthErrors::NonceError(e.to_string()))
+    }
+
+    fn stored(&self) -> Result<Option<u64>, EthErrors> {
+        self.db
+            .get_nonce(&self.key)
+            .map_err(|e| EthErrors::NonceError(e.to_string()))
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, NonceState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
@@ -0,0 +1,108 @@
+// hot wallet pool: several faucet keys, each with its own nonces and balance,
+// payouts are spread over them by a selection strategy
+use crate::nonce::NonceManager;
+use crate::{Address, EthErrors, U256};
+use std::cmp::Reverse;
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+// how long a fetched wallet balance is trusted, sends drop it right away
+const BALANCE_TTL: Duration = Duration::from_secs(10);
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum WalletStrategy {
+    // each payout goes to the next wallet in turn
+    #[default]
+    RoundRobin,
+    // the wallet holding the most of the paid asset
+    MostFunded,
+    // the wallet with the fewest unmined transactions
+    LeastPending,
+}
+
+impl FromStr for WalletStrategy {
+    type Err = EthErrors;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "round-robin" => Ok(Self::RoundRobin),
+            "most-funded" => Ok(Self::MostFunded),
+            "least-pending" => Ok(Self::LeastPending),
+            _ => Err(EthErrors::WalletStrategyError(s.to_string())),
+        }
+    }
+}
+
+// what a strategy knows about one wallet
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct WalletLoad {
+    // of the paid asset
+    pub balance: U256,
+    // sent but not mined yet
+    pub pending: u64,
+}
+
+// index of the wallet to pay `amount` from, only wallets holding it are picked.
+// `turn` counts the payouts so far, round robin starts looking at turn % len.
+// None when no wallet can cover the amount
+pub fn pick_wallet(
+    strategy: WalletStrategy,
+    loads: &[WalletLoad],
+    amount: U256,
+    turn: usize,
+) -> Option<usize> {
+    let funded = loads
+        .iter()
+        .enumerate()
+        .filter(|(_, load)| load.balance >= amount);
+    match strategy {
+        WalletStrategy::RoundRobin => {
+            let start = turn.checked_rem(loads.len())?;
+            funded
+                .min_by_key(|(i, _)| (*i + loads.len() - start) % loads.len())
+                .map(|(i, _)| i)
+        }
+        // ties go to the first wallet
+        WalletStrategy::MostFunded => funded
+            .min_by_key(|(_, load)| Reverse(load.balance))
+            .map(|(i, _)| i),
+        // ties go to the better funded wallet
+        WalletStrategy::LeastPending => funded
+            .min_by_key(|(_, load)| (load.pending, Reverse(load.balance)))
+            .map(|(i, _)| i),
+    }
+}
+
+#[derive(Debug)]
+pub struct HotWallet {
+    pub address: Address,
+    pub nonces: NonceManager,
+    // last fetched native balance
+    balance: Mutex<Option<(U256, Instant)>>,
+}
+
+impl HotWallet {
+    pub fn new(address: Address, nonces: NonceManager) -> Self {
+        Self {
+            address,
+            nonces,
+            balance: Mutex::default(),
+        }
+    }
+
+    pub fn cached_balance(&self) -> Option<U256> {
+        let cached = *self.balance.lock().unwrap_or_else(|e| e.into_inner());
+        cached
+            .filter(|(_, fetched)| fetched.elapsed() < BALANCE_TTL)
+            .map(|(balance, _)| balance)
+    }
+
+    pub fn cache_balance(&self, balance: U256) {
+        *self.balance.lock().unwrap_or_else(|e| e.into_inner()) = Some((balance, Instant::now()));
+    }
+
+    pub fn forget_balance(&self) {
+        *self.balance.lock().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+}
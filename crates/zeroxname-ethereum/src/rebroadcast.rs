@@ -0,0 +1,35 @@
+// replacing stuck payouts: same nonce, higher EIP-1559 fees
+
+// nodes only accept a replacement paying at least 10% more on both fees
+pub const FEE_BUMP_PERCENT: u128 = 12;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Eip1559Fees {
+    pub max_fee_per_gas: u128,
+    pub max_priority_fee_per_gas: u128,
+}
+
+fn bump(fee: u128) -> u128 {
+    fee + fee * FEE_BUMP_PERCENT / 100
+}
+
+// fees of a replacement: the stuck ones bumped, or the current network ones when those are higher.
+// None when the replacement would pay `max_fee_per_gas` wei per gas or more. capped per gas,
+// so a batch's replacement is held to the same price as a single payout's
+pub fn bump_fees(
+    stuck: Eip1559Fees,
+    network: Eip1559Fees,
+    max_fee_per_gas: u128,
+) -> Option<Eip1559Fees> {
+    let max_priority_fee_per_gas = bump(stuck.max_priority_fee_per_gas)
+        .max(network.max_priority_fee_per_gas)
+        .max(1);
+    let bumped_max_fee_per_gas = bump(stuck.max_fee_per_gas)
+        .max(network.max_fee_per_gas)
+        .max(max_priority_fee_per_gas);
+
+    (bumped_max_fee_per_gas < max_fee_per_gas).then_some(Eip1559Fees {
+        max_fee_per_gas: bumped_max_fee_per_gas,
+        max_priority_fee_per_gas,
+    })
+}
@@ -0,0 +1,208 @@
+// where faucet keys live: a raw key for dev, an encrypted keystore, or a remote signer
+// that never hands its keys out
+use crate::EthErrors;
+use alloy_consensus::{SignableTransaction, TxEnvelope};
+use alloy_network::eip2718::Decodable2718;
+use alloy_network::{TransactionBuilder, TxSigner};
+use alloy_primitives::{Address, Bytes, PrimitiveSignature as Signature};
+use alloy_provider::{Provider, RootProvider};
+use alloy_rpc_types::{TransactionInput, TransactionRequest};
+use alloy_signer_local::PrivateKeySigner;
+use async_trait::async_trait;
+use serde::Deserialize;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum RemoteSignerApi {
+    // eth_signTransaction, answers with the raw signed tx
+    #[default]
+    Web3Signer,
+    // account_signTransaction, answers with {raw, tx}
+    Clef,
+}
+
+impl FromStr for RemoteSignerApi {
+    type Err = EthErrors;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "web3signer" => Ok(Self::Web3Signer),
+            "clef" => Ok(Self::Clef),
+            _ => Err(EthErrors::SignerError(format!(
+                "unknown remote signer api {}, use web3signer or clef",
+                s
+            ))),
+        }
+    }
+}
+
+impl RemoteSignerApi {
+    fn method(&self) -> &'static str {
+        match self {
+            Self::Web3Signer => "eth_signTransaction",
+            Self::Clef => "account_signTransaction",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum SignResponse {
+    Raw(Bytes),
+    Clef { raw: Bytes },
+}
+
+// signs over JSON-RPC with a key held by the signer service
+#[derive(Clone, Debug)]
+pub struct RemoteSigner {
+    client: RootProvider,
+    address: Address,
+    api: RemoteSignerApi,
+}
+
+impl RemoteSigner {
+    pub fn new(url: &str, address: Address, api: RemoteSignerApi) -> Result<Self, EthErrors> {
+        let url = url
+            .parse()
+            .map_err(|_| EthErrors::SignerError(format!("invalid remote signer url {}", url)))?;
+        Ok(Self {
+            client: RootProvider::new_http(url),
+            address,
+            api,
+        })
+    }
+
+    // the fields of `tx`, as the signer expects them
+    fn request(&self, tx: &dyn SignableTransaction<Signature>) -> TransactionRequest {
+        let mut request = TransactionRequest::default()
+            .with_from(self.address)
+            .with_kind(tx.kind())
+            .with_value(tx.value())
+            .with_nonce(tx.nonce())
+            .with_gas_limit(tx.gas_limit());
+        // clef reads `data`, web3signer either
+        request.input = TransactionInput::both(tx.input().clone());
+        request.transaction_type = Some(tx.ty());
+        request.chain_id = tx.chain_id();
+        request.access_list = tx.access_list().cloned();
+        match tx.is_dynamic_fee() {
+            true => {
+                request.max_fee_per_gas = Some(tx.max_fee_per_gas());
+                request.max_priority_fee_per_gas = tx.max_priority_fee_per_gas();
+            }
+            false => request.gas_price = tx.gas_price(),
+        }
+        request
+    }
+}
+
+#[async_trait]
+impl TxSigner<Signature> for RemoteSigner {
+    fn address(&self) -> Address {
+        self.address
+    }
+
+    async fn sign_transaction(
+        &self,
+        tx: &mut dyn SignableTransaction<Signature>,
+    ) -> alloy_signer::Result<Signature> {
+        let response: SignResponse = self
+            .client
+            .raw_request(self.api.method().into(), (self.request(tx),))
+            .await
+            .map_err(alloy_signer::Error::other)?;
+        let raw = match response {
+            SignResponse::Raw(raw) | SignResponse::Clef { raw } => raw,
+        };
+        let signed =
+            TxEnvelope::decode_2718(&mut raw.as_ref()).map_err(alloy_signer::Error::other)?;
+        let signature = *signed.signature();
+        // the signer may fill or change fields, only a signature over our tx is usable
+        let signer = signature
+            .recover_address_from_prehash(&tx.signature_hash())
+            .map_err(alloy_signer::Error::other)?;
+        if signer != self.address {
+            return Err(alloy_signer::Error::other(format!(
+                "remote signer signed a different transaction or with another key ({})",
+                signer
+            )));
+        }
+        Ok(signature)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum FaucetSigner {
+    Local(PrivateKeySigner),
+    Remote(RemoteSigner),
+}
+
+impl FaucetSigner {
+    pub fn address(&self) -> Address {
+        match self {
+            Self::Local(signer) => signer.address(),
+            Self::Remote(signer) => signer.address,
+        }
+    }
+}
+
+#[async_trait]
+impl TxSigner<Signature> for FaucetSigner {
+    fn address(&self) -> Address {
+        FaucetSigner::address(self)
+    }
+
+    async fn sign_transaction(
+        &self,
+        tx: &mut dyn SignableTransaction<Signature>,
+    ) -> alloy_signer::Result<Signature> {
+        match self {
+            Self::Local(signer) => signer.sign_transaction(tx).await,
+            Self::Remote(signer) => signer.sign_transaction(tx).await,
+        }
+    }
+}
+
+// how to get one faucet key
+#[derive(Clone, Debug)]
+pub enum SignerConfig {
+    // hex key, for dev
+    PrivateKey(String),
+    Keystore {
+        path: PathBuf,
+        // the password is the whole file, minus a trailing newline
+        password_file: PathBuf,
+    },
+    Remote {
+        url: String,
+        address: Address,
+        api: RemoteSignerApi,
+    },
+}
+
+impl SignerConfig {
+    pub fn load(&self) -> Result<FaucetSigner, EthErrors> {
+        match self {
+            Self::PrivateKey(key) => Ok(FaucetSigner::Local(
+                PrivateKeySigner::from_str(key).map_err(EthErrors::InitSignerError)?,
+            )),
+            Self::Keystore {
+                path,
+                password_file,
+            } => {
+                let password = std::fs::read_to_string(password_file).map_err(|e| {
+                    EthErrors::KeystoreError(format!("{}: {}", password_file.display(), e))
+                })?;
+                let password = password.trim_end_matches(['\r', '\n']);
+                // a wrong password fails the keystore's MAC check
+                PrivateKeySigner::decrypt_keystore(path, password)
+                    .map(FaucetSigner::Local)
+                    .map_err(|e| EthErrors::KeystoreError(format!("{}: {}", path.display(), e)))
+            }
+            Self::Remote { url, address, api } => Ok(FaucetSigner::Remote(RemoteSigner::new(
+                url, *address, *api,
+            )?)),
+        }
+    }
+}
@@ -0,0 +1,20 @@
+// to run from root directory cargo test -p zeroxname-ethereum --test ens_test
+
+use zeroxname_ethereum::namehash;
+
+// vectors from EIP-137
+#[test]
+fn test_namehash() {
+    assert_eq!(
+        namehash("").to_string(),
+        "0x0000000000000000000000000000000000000000000000000000000000000000"
+    );
+    assert_eq!(
+        namehash("eth").to_string(),
+        "0x93cdeb708b7545dc668eb9280176169d1c33cfd8ed6f04690a0bcc88a93fc4ae"
+    );
+    assert_eq!(
+        namehash("foo.eth").to_string(),
+        "0xde9b09fd7c5f901e23a3f19fecc54828e9c848539801e86591bd9801b019f84f"
+    );
+}
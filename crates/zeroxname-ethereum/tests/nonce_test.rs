@@ -0,0 +1,106 @@
+// to run from root directory cargo test -p zeroxname-ethereum --test nonce_test
+
+use db::DB;
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tempfile::{TempDir, tempdir};
+use zeroxname_ethereum::EthErrors;
+use zeroxname_ethereum::nonce::NonceManager;
+
+const KEY: &str = "sepolia:0xf0E5D3Cc05206987a125afC404b719e54Fa942a8";
+
+fn new_db() -> (DB, TempDir) {
+    let dir = tempdir().expect("should create temp dir");
+    let db = DB::new(
+        dir.path().to_str().expect("valid path"),
+        1024 * 1024,
+        &["sepolia"],
+    )
+    .expect("should create DB");
+    (db, dir)
+}
+
+async fn chain(nonce: u64) -> Result<u64, EthErrors> {
+    Ok(nonce)
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 8)]
+async fn test_concurrent_nonces_are_consecutive() {
+    let (db, _dir) = new_db();
+    let nonces = Arc::new(NonceManager::new(db.clone(), KEY.to_string()));
+    let chain_calls = Arc::new(AtomicUsize::new(0));
+
+    let handles: Vec<_> = (0..64)
+        .map(|_| {
+            let nonces = nonces.clone();
+            let chain_calls = chain_calls.clone();
+            tokio::spawn(async move {
+                nonces
+                    .next(|| async move {
+                        chain_calls.fetch_add(1, Ordering::SeqCst);
+                        Ok(10)
+                    })
+                    .await
+            })
+        })
+        .collect();
+    let mut assigned = HashSet::new();
+    for handle in handles {
+        assigned.insert(
+            handle
+                .await
+                .expect("task should finish")
+                .expect("should get nonce"),
+        );
+    }
+
+    assert_eq!(assigned, (10..74).collect::<HashSet<u64>>());
+    assert!(chain_calls.load(Ordering::SeqCst) <= 64);
+    assert_eq!(db.get_nonce(KEY).expect("should get"), Some(74));
+    // synced, the chain isn't asked again
+    let next = nonces
+        .next(|| async { panic!("chain shouldn't be queried") })
+        .await
+        .expect("should get nonce");
+    assert_eq!(next, 74);
+}
+
+#[tokio::test]
+async fn test_nonce_survives_restart() {
+    let (db, _dir) = new_db();
+    let nonces = NonceManager::new(db.clone(), KEY.to_string());
+    for expected in 5..8 {
+        assert_eq!(nonces.next(|| chain(5)).await.expect("nonce"), expected);
+    }
+
+    // node lagging behind the stored nonce after a restart
+    let nonces = NonceManager::new(db.clone(), KEY.to_string());
+    assert_eq!(nonces.next(|| chain(6)).await.expect("nonce"), 8);
+
+    // chain ahead of the store, the wallet was used elsewhere
+    let nonces = NonceManager::new(db, KEY.to_string());
+    assert_eq!(nonces.next(|| chain(20)).await.expect("nonce"), 20);
+}
+
+#[tokio::test]
+async fn test_release_and_resync() {
+    let (db, _dir) = new_db();
+    let nonces = NonceManager::new(db, KEY.to_string());
+
+    // last nonce handed out is reused
+    let nonce = nonces.next(|| chain(3)).await.expect("nonce");
+    nonces.release(nonce, false).expect("should release");
+    assert_eq!(nonces.next(|| chain(3)).await.expect("nonce"), 3);
+
+    // a gap behind later nonces resyncs with the chain, not the store
+    let first = nonces.next(|| chain(3)).await.expect("nonce");
+    let _second = nonces.next(|| chain(3)).await.expect("nonce");
+    nonces.release(first, false).expect("should release");
+    assert_eq!(nonces.next(|| chain(4)).await.expect("nonce"), 4);
+
+    // node says too low, jump to the chain
+    let nonce = nonces.next(|| chain(0)).await.expect("nonce");
+    nonces.release(nonce, true).expect("should release");
+    assert_eq!(nonces.next(|| chain(9)).await.expect("nonce"), 9);
+}
@@ -0,0 +1,57 @@
+// to run from root directory cargo test -p zeroxname-ethereum --test pool_test
+
+use zeroxname_ethereum::U256;
+use zeroxname_ethereum::pool::{WalletLoad, WalletStrategy, pick_wallet};
+
+fn load(balance: u64, pending: u64) -> WalletLoad {
+    WalletLoad {
+        balance: U256::from(balance),
+        pending,
+    }
+}
+
+#[test]
+fn test_round_robin_skips_unfunded() {
+    let loads = [load(10, 0), load(0, 0), load(10, 0)];
+    let picked: Vec<_> = (0..4)
+        .map(|turn| pick_wallet(WalletStrategy::RoundRobin, &loads, U256::from(5), turn))
+        .collect();
+    // the empty wallet's turn goes to the next one
+    assert_eq!(picked, [Some(0), Some(2), Some(2), Some(0)]);
+}
+
+#[test]
+fn test_most_funded_and_least_pending() {
+    let loads = [load(10, 3), load(30, 5), load(20, 1), load(20, 1)];
+    assert_eq!(
+        pick_wallet(WalletStrategy::MostFunded, &loads, U256::from(5), 0),
+        Some(1)
+    );
+    assert_eq!(
+        pick_wallet(WalletStrategy::LeastPending, &loads, U256::from(5), 0),
+        Some(2)
+    );
+    // only wallets covering the amount count
+    assert_eq!(
+        pick_wallet(WalletStrategy::LeastPending, &loads, U256::from(25), 0),
+        Some(1)
+    );
+}
+
+#[test]
+fn test_no_wallet_covers_amount() {
+    let loads = [load(10, 0), load(20, 0)];
+    for strategy in [
+        WalletStrategy::RoundRobin,
+        WalletStrategy::MostFunded,
+        WalletStrategy::LeastPending,
+    ] {
+        assert_eq!(pick_wallet(strategy, &loads, U256::from(21), 7), None);
+        assert_eq!(pick_wallet(strategy, &[], U256::ZERO, 0), None);
+    }
+    assert_eq!(
+        "least-pending".parse::<WalletStrategy>().expect("known"),
+        WalletStrategy::LeastPending
+    );
+    assert!("random".parse::<WalletStrategy>().is_err());
+}
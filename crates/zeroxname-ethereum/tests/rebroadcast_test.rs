@@ -0,0 +1,53 @@
+// to run from root directory cargo test -p zeroxname-ethereum --test rebroadcast_test
+
+use zeroxname_ethereum::TX_GAS;
+use zeroxname_ethereum::rebroadcast::{Eip1559Fees, bump_fees};
+
+const GWEI: u128 = 1_000_000_000;
+
+fn fees(max_fee: u128, priority_fee: u128) -> Eip1559Fees {
+    Eip1559Fees {
+        max_fee_per_gas: max_fee,
+        max_priority_fee_per_gas: priority_fee,
+    }
+}
+
+#[test]
+fn test_bump_over_stuck_fees() {
+    let bumped =
+        bump_fees(fees(10 * GWEI, GWEI), fees(5 * GWEI, GWEI / 2), u128::MAX).expect("should bump");
+    // both fees go up by more than the 10% nodes require
+    assert!(bumped.max_fee_per_gas * 100 > 10 * GWEI * 110);
+    assert!(bumped.max_priority_fee_per_gas * 100 > GWEI * 110);
+}
+
+#[test]
+fn test_bump_follows_network_fees() {
+    let bumped = bump_fees(fees(10 * GWEI, GWEI), fees(30 * GWEI, 3 * GWEI), u128::MAX)
+        .expect("should bump");
+    assert_eq!(bumped, fees(30 * GWEI, 3 * GWEI));
+}
+
+#[test]
+fn test_bump_capped_per_gas() {
+    let stuck = fees(10 * GWEI, GWEI);
+    let network = fees(5 * GWEI, GWEI);
+    let bumped = bump_fees(stuck, network, u128::MAX).expect("should bump");
+
+    // exactly the bumped price is too much, whatever gas the stuck tx uses
+    let price = bumped.max_fee_per_gas;
+    assert_eq!(bump_fees(stuck, network, price), None);
+    assert_eq!(bump_fees(stuck, network, price + 1), Some(bumped));
+}
+
+#[test]
+fn test_batch_replaced_under_cap() {
+    // 5% of 0.05 ETH is what a plain transfer may cost, PayoutSender::max_fee_per_gas
+    let tx_cost_limit = 2_500_000_000_000_000;
+    let cap = tx_cost_limit / TX_GAS;
+
+    // a stuck batch of ten costs far more than one transfer, it's still replaced under the cap
+    let stuck = fees(cap / 2, GWEI);
+    let bumped = bump_fees(stuck, fees(0, 0), cap).expect("should bump");
+    assert!(bumped.max_fee_per_gas * 10 * TX_GAS > tx_cost_limit);
+}
@@ -0,0 +1,208 @@
+// to run from root directory cargo test -p zeroxname-ethereum --test signer_test
+
+use alloy_consensus::{SignableTransaction, TxEip1559, TxEnvelope};
+use alloy_network::eip2718::Encodable2718;
+use alloy_network::{TxSigner, TxSignerSync};
+use alloy_primitives::{Address, TxKind, U256, hex};
+use alloy_rpc_types::TransactionRequest;
+use alloy_signer_local::PrivateKeySigner;
+use serde_json::{Value, json};
+use std::path::Path;
+use tempfile::tempdir;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use zeroxname_ethereum::EthErrors;
+use zeroxname_ethereum::signer::{FaucetSigner, RemoteSignerApi, SignerConfig};
+
+// password "testpassword" for both keystores, pbkdf2 is the web3 secret storage spec vector
+const KEY: &str = "7a28b5ba57c53603b0b07b56bba752f7784bf506fa95edc395f5cf6c7514fe9d";
+const PBKDF2_KEYSTORE: &str = r#"{
+    "crypto": {
+        "cipher": "aes-128-ctr",
+        "cipherparams": { "iv": "6087dab2f9fdbbfaddc31a909735c1e6" },
+        "ciphertext": "5318b4d5bcd28de64ee5559e671353e16f075ecae9f99c7a79a38af5f869aa46",
+        "kdf": "pbkdf2",
+        "kdfparams": {
+            "c": 262144,
+            "dklen": 32,
+            "prf": "hmac-sha256",
+            "salt": "ae3cd4e7013836a3df6bd7241b12db061dbe2c6785853cce422d148a624ce0bd"
+        },
+        "mac": "517ead924a9d0dc3124507e3393d175ce3ff7c1e96529c6c555ce9e51205e9b2"
+    },
+    "id": "3198bc9c-6672-5ab3-d995-4942343ae5b6",
+    "version": 3
+}"#;
+// geth light scrypt parameters
+const SCRYPT_KEYSTORE: &str = r#"{
+    "crypto": {
+        "cipher": "aes-128-ctr",
+        "cipherparams": { "iv": "b03252554f35daa11588b7cfa147edfe" },
+        "ciphertext": "c3b290b5404d2141e75151d954eb4f945519126c4c62e6135871e29765e4a526",
+        "kdf": "scrypt",
+        "kdfparams": {
+            "dklen": 32,
+            "n": 4096,
+            "p": 6,
+            "r": 8,
+            "salt": "c6794cb9089cce9f33a979a3a0d67bb8b3fed981003325b1043ccf6135f64618"
+        },
+        "mac": "55fa4dcf878bb40b4a56c6cc8e53515a773e75a71e88995390a094704cabe03b"
+    },
+    "id": "7e59dc02-8d42-409d-b29a-a8a0f862cc81",
+    "version": 3
+}"#;
+
+fn keystore_config(dir: &Path, keystore: &str, password: &str) -> SignerConfig {
+    let path = dir.join("keystore.json");
+    let password_file = dir.join("password");
+    std::fs::write(&path, keystore).expect("should write keystore");
+    std::fs::write(&password_file, password).expect("should write password");
+    SignerConfig::Keystore {
+        path,
+        password_file,
+    }
+}
+
+#[test]
+fn test_keystore_decrypts() {
+    let dir = tempdir().expect("should create temp dir");
+    let expected: PrivateKeySigner = KEY.parse().expect("valid key");
+    for keystore in [PBKDF2_KEYSTORE, SCRYPT_KEYSTORE] {
+        // the password file's trailing newline isn't part of the password
+        let signer = keystore_config(dir.path(), keystore, "testpassword\n")
+            .load()
+            .expect("should decrypt keystore");
+        let FaucetSigner::Local(signer) = signer else {
+            panic!("keystore should load a local signer");
+        };
+        assert_eq!(signer.to_bytes(), expected.to_bytes());
+    }
+}
+
+#[test]
+fn test_keystore_wrong_password() {
+    let dir = tempdir().expect("should create temp dir");
+    let err = keystore_config(dir.path(), PBKDF2_KEYSTORE, "wrongpassword")
+        .load()
+        .expect_err("wrong password should fail");
+    assert!(matches!(err, EthErrors::KeystoreError(_)), "{}", err);
+    assert!(err.to_string().contains("Mac Mismatch"), "{}", err);
+}
+
+// one JSON-RPC request per connection, signed with `key` the way the api answers
+async fn serve_signer(key: PrivateKeySigner) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.expect("should bind");
+    let url = format!("http://{}", listener.local_addr().expect("bound address"));
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            let body = read_body(&mut stream).await;
+            let request: Value = serde_json::from_slice(&body).expect("json-rpc request");
+            let tx: TransactionRequest =
+                serde_json::from_value(request["params"][0].clone()).expect("tx param");
+            let mut tx = tx.build_typed_tx().expect("complete tx");
+            let signature = key.sign_transaction_sync(&mut tx).expect("should sign");
+            let raw =
+                hex::encode_prefixed(TxEnvelope::from(tx.into_signed(signature)).encoded_2718());
+            let result = match request["method"].as_str() {
+                Some("account_signTransaction") => json!({ "raw": raw, "tx": {} }),
+                _ => json!(raw),
+            };
+            let response =
+                json!({ "jsonrpc": "2.0", "id": request["id"], "result": result }).to_string();
+            let http = format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                response.len(),
+                response
+            );
+            stream
+                .write_all(http.as_bytes())
+                .await
+                .expect("should respond");
+        }
+    });
+    url
+}
+
+async fn read_body(stream: &mut tokio::net::TcpStream) -> Vec<u8> {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 4096];
+    loop {
+        let n = stream.read(&mut chunk).await.expect("should read");
+        buf.extend_from_slice(&chunk[..n]);
+        let text = String::from_utf8_lossy(&buf);
+        if let Some(end) = text.find("\r\n\r\n") {
+            let length = text[..end]
+                .lines()
+                .find_map(|line| {
+                    let (name, value) = line.split_once(':')?;
+                    name.eq_ignore_ascii_case("content-length")
+                        .then(|| value.trim().parse::<usize>().ok())?
+                })
+                .unwrap_or(0);
+            if buf.len() >= end + 4 + length {
+                return buf[end + 4..end + 4 + length].to_vec();
+            }
+        }
+        assert!(n > 0, "connection closed mid request");
+    }
+}
+
+fn payout_tx() -> TxEip1559 {
+    TxEip1559 {
+        chain_id: 11155111,
+        nonce: 7,
+        gas_limit: 21000,
+        max_fee_per_gas: 20_000_000_000,
+        max_priority_fee_per_gas: 1_000_000_000,
+        to: TxKind::Call(Address::with_last_byte(1)),
+        value: U256::from(50_000_000_000_000_000u64),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn test_remote_signer_signs() {
+    let key: PrivateKeySigner = KEY.parse().expect("valid key");
+    let url = serve_signer(key.clone()).await;
+
+    for api in [RemoteSignerApi::Web3Signer, RemoteSignerApi::Clef] {
+        let signer = SignerConfig::Remote {
+            url: url.clone(),
+            address: key.address(),
+            api,
+        }
+        .load()
+        .expect("should create remote signer");
+        let mut tx = payout_tx();
+        let signature = signer
+            .sign_transaction(&mut tx)
+            .await
+            .expect("remote signer should sign");
+        assert_eq!(
+            signature
+                .recover_address_from_prehash(&tx.signature_hash())
+                .expect("should recover"),
+            key.address()
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_remote_signer_wrong_key() {
+    let key: PrivateKeySigner = KEY.parse().expect("valid key");
+    let url = serve_signer(PrivateKeySigner::random()).await;
+
+    let signer = SignerConfig::Remote {
+        url,
+        address: key.address(),
+        api: RemoteSignerApi::Web3Signer,
+    }
+    .load()
+    .expect("should create remote signer");
+    let err = signer
+        .sign_transaction(&mut payout_tx())
+        .await
+        .expect_err("signature by another key should be refused");
+    assert!(err.to_string().contains("another key"));
+}